@@ -1,3 +1,4 @@
+#[allow(clippy::result_large_err)]
 fn main() -> noargs::Result<()> {
     let mut args = noargs::raw_args();
     args.metadata_mut().app_name = env!("CARGO_PKG_NAME");
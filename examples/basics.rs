@@ -1,10 +1,12 @@
+#[allow(clippy::result_large_err)]
 fn main() -> noargs::Result<()> {
     let mut args = noargs::raw_args();
     args.metadata_mut().app_name = env!("CARGO_PKG_NAME");
     args.metadata_mut().app_description = env!("CARGO_PKG_DESCRIPTION");
+    args.metadata_mut().app_version = env!("CARGO_PKG_VERSION");
 
-    if noargs::VERSION_FLAG.take(&mut args).is_present() {
-        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    if let Some(version) = noargs::VERSION_FLAG.take_version(&mut args) {
+        print!("{version}");
         return Ok(());
     }
     noargs::HELP_FLAG.take_help(&mut args);
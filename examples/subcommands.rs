@@ -1,3 +1,4 @@
+#[allow(clippy::result_large_err)]
 fn main() -> noargs::Result<()> {
     let mut args = noargs::raw_args();
     args.metadata_mut().app_name = env!("CARGO_PKG_NAME");
@@ -19,6 +20,7 @@ fn main() -> noargs::Result<()> {
     Ok(())
 }
 
+#[allow(clippy::result_large_err)]
 fn try_run_hello(args: &mut noargs::RawArgs) -> noargs::Result<bool> {
     if !noargs::cmd("hello")
         .doc("Print a greeting")
@@ -52,6 +54,7 @@ fn try_run_hello(args: &mut noargs::RawArgs) -> noargs::Result<bool> {
     Ok(true)
 }
 
+#[allow(clippy::result_large_err)]
 fn try_run_sum(args: &mut noargs::RawArgs) -> noargs::Result<bool> {
     if !noargs::cmd("sum")
         .doc("Add two integers")
@@ -90,6 +93,7 @@ fn try_run_sum(args: &mut noargs::RawArgs) -> noargs::Result<bool> {
     Ok(true)
 }
 
+#[allow(clippy::result_large_err)]
 fn try_run_echo(args: &mut noargs::RawArgs) -> noargs::Result<bool> {
     if !noargs::cmd("echo")
         .doc("Print a message")
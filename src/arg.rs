@@ -1,5 +1,5 @@
 use crate::{
-    args::{Metadata, RawArgs},
+    args::{Metadata, RawArgs, Taken, canonical_choice, is_env_reference, redact},
     error::Error,
 };
 
@@ -12,6 +12,12 @@ pub struct ArgSpec {
     /// Documentation.
     pub doc: &'static str,
 
+    /// Environment variable name.
+    ///
+    /// If a non-empty value is set for this environment variable,
+    /// it will be used as the value of this argument when the argument is not specified in [`RawArgs`].
+    pub env: Option<&'static str>,
+
     /// Default value.
     pub default: Option<&'static str>,
 
@@ -19,6 +25,39 @@ pub struct ArgSpec {
     ///
     /// This is only used if `RawArgs::metadata().help_mode` is `true`.
     pub example: Option<&'static str>,
+
+    /// If set, only raw arguments whose indices are greater than this value are considered when taking this argument
+    /// (e.g., the index of a subcommand to ignore the arguments before it).
+    pub after_index: Option<usize>,
+
+    /// If set, only raw arguments whose indices are less than this value are considered when taking this argument
+    /// (e.g., the index of `--` to ignore the arguments after it).
+    pub before_index: Option<usize>,
+
+    /// Stable identifier of this argument (see [`ArgSpec::id()`]).
+    pub stable_id: Option<&'static str>,
+
+    /// If `true`, this argument is not shown in the help text (but is parsed as usual).
+    pub hidden: bool,
+
+    /// If `true`, the value of this argument is considered sensitive (e.g., a password),
+    /// and is shown as `[redacted]` in error messages, help examples and [`Debug`](std::fmt::Debug) output.
+    ///
+    /// See also: [`Arg::is_sensitive()`]
+    pub sensitive: bool,
+
+    /// If set, this argument is bound to the positional argument at this position (0-based)
+    /// instead of the first remaining one (see [`ArgSpec::at()`]).
+    pub position: Option<usize>,
+
+    /// Possible values.
+    ///
+    /// If this is not empty, the values are shown in the help text,
+    /// and [`Arg::then()`] reports an error if the value is not one of them.
+    pub choices: &'static [&'static str],
+
+    /// If `true`, values matching [`ArgSpec::choices`] ignoring ASCII case are replaced with the canonical spelling.
+    pub ignore_case: bool,
 }
 
 impl ArgSpec {
@@ -26,8 +65,17 @@ impl ArgSpec {
     pub const DEFAULT: Self = Self {
         name: "<ARGUMENT>",
         doc: "",
+        env: None,
         default: None,
         example: None,
+        after_index: None,
+        before_index: None,
+        stable_id: None,
+        hidden: false,
+        sensitive: false,
+        position: None,
+        choices: &[],
+        ignore_case: false,
     };
 
     /// Makes an [`ArgSpec`] instance with a specified name (equivalent to `noargs::arg(name)`).
@@ -44,6 +92,12 @@ impl ArgSpec {
         self
     }
 
+    /// Updates the value of [`ArgSpec::env`].
+    pub const fn env(mut self, variable_name: &'static str) -> Self {
+        self.env = Some(variable_name);
+        self
+    }
+
     /// Updates the value of [`ArgSpec::default`].
     pub const fn default(mut self, default: &'static str) -> Self {
         self.default = Some(default);
@@ -56,7 +110,124 @@ impl ArgSpec {
         self
     }
 
+    /// Updates the value of [`ArgSpec::choices`].
+    pub const fn choices(mut self, values: &'static [&'static str]) -> Self {
+        self.choices = values;
+        self
+    }
+
+    /// Sets [`ArgSpec::ignore_case`] to `true`.
+    pub const fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// Updates the value of [`ArgSpec::after_index`].
+    pub const fn after_index(mut self, index: usize) -> Self {
+        self.after_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`ArgSpec::before_index`].
+    pub const fn before_index(mut self, index: usize) -> Self {
+        self.before_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`ArgSpec::stable_id`].
+    pub const fn stable_id(mut self, id: &'static str) -> Self {
+        self.stable_id = Some(id);
+        self
+    }
+
+    /// Sets [`ArgSpec::hidden`] to `true`.
+    pub const fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Sets [`ArgSpec::sensitive`] to `true`.
+    pub const fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    /// Updates the value of [`ArgSpec::position`].
+    ///
+    /// Positions are counted over the raw arguments that are not consumed by options, flags or subcommands
+    /// (i.e., the remaining ones and the ones taken by positional arguments),
+    /// so the result does not depend on the order in which positional arguments are taken.
+    /// Therefore, options, flags and subcommands should be taken before such arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["cp", "a.txt", "b.txt"].iter().map(|a| a.to_string()));
+    /// let dst = noargs::arg("<DST>").at(1).take(&mut args);
+    /// let src = noargs::arg("<SRC>").at(0).take(&mut args);
+    /// assert_eq!(src.value(), "a.txt");
+    /// assert_eq!(dst.value(), "b.txt");
+    /// ```
+    pub const fn at(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Returns the identifier of this argument, which is [`ArgSpec::stable_id`] if set, or [`ArgSpec::name`] otherwise
+    /// (see [`OptSpec::id()`](crate::OptSpec::id)).
+    pub const fn id(&self) -> &'static str {
+        match self.stable_id {
+            Some(id) => id,
+            None => self.name,
+        }
+    }
+
+    /// Builds raw arguments that specify `value` for this argument (e.g., `["foo"]`).
+    ///
+    /// This is useful to construct a command line of an application that uses the same specifications.
+    /// If `value` starts with `-`, it is preceded by `--`, which is skipped by [`ArgSpec::take()`]
+    /// (as long as [`Metadata::terminator`] is the default one).
+    /// Note that options and flags only stop at `--` if [`Metadata::posix_ordering`] is `true`,
+    /// so the result should be placed after the arguments built for the options and flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(noargs::arg("<NAME>").to_args("foo"), ["foo"]);
+    /// assert_eq!(noargs::arg("<PATTERN>").to_args("-x"), ["--", "-x"]);
+    /// ```
+    pub fn to_args(self, value: &str) -> Vec<String> {
+        if value.len() > 1 && value.starts_with('-') {
+            vec!["--".to_owned(), value.to_owned()]
+        } else {
+            vec![value.to_owned()]
+        }
+    }
+
+    /// Takes all [`Arg`] instances that satisfy this specification from the raw arguments.
+    ///
+    /// Unlike calling [`ArgSpec::take()`] in a `while` loop, this method always terminates even if
+    /// the result is present without consuming any raw argument (e.g., [`Arg::Default`]).
+    /// Such a result is included only if nothing is found in the raw arguments.
+    pub fn take_all(self, args: &mut RawArgs) -> Vec<Arg> {
+        let mut taken = Vec::new();
+        loop {
+            let x = self.take(args);
+            if x.index().is_some() {
+                taken.push(x);
+                continue;
+            }
+            if taken.is_empty() && x.is_present() {
+                taken.push(x);
+            }
+            return taken;
+        }
+    }
+
     /// Takes the first [`Arg`] instance that satisfies this specification from the raw arguments.
+    ///
+    /// The first unconsumed `--` ([`Metadata::terminator`]) is not taken as a value.
+    /// It is consumed when an argument after it is taken (e.g., `-x` in `grep -- -x`).
     pub fn take(self, args: &mut RawArgs) -> Arg {
         let metadata = args.metadata();
         args.with_record_arg(|args| {
@@ -76,27 +247,85 @@ impl ArgSpec {
                 };
             }
 
-            for (index, raw_arg) in args.raw_args_mut().iter_mut().enumerate() {
-                if let Some(value) = raw_arg.value.take() {
-                    return Arg::Positional {
-                        spec: self,
-                        metadata,
-                        index,
-                        value,
-                    };
+            let terminator = args.terminator_index();
+            let mut window =
+                args.index_window(self.after_index, self.before_index, args.raw_args().len())
+                    .filter(|i| Some(*i) != terminator);
+            let index = match self.position {
+                Some(position) => {
+                    let taken = args
+                        .log()
+                        .iter()
+                        .filter_map(|taken| match taken {
+                            Taken::Arg(arg) => arg.index(),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    window
+                        .filter(|i| args.raw_args()[*i].value.is_some() || taken.contains(i))
+                        .nth(position)
+                }
+                None => window.find(|i| args.raw_args()[*i].value.is_some()),
+            };
+            if let Some((index, value)) =
+                index.and_then(|i| Some((i, args.raw_args_mut()[i].value.take()?)))
+            {
+                if let Some(i) = terminator.filter(|i| *i < index) {
+                    args.raw_args_mut()[i].value = None;
+                }
+                args.consume_detached_values(index);
+                return Arg::Positional {
+                    spec: self,
+                    metadata,
+                    index,
+                    value: self.canonicalize(value),
+                    source: args.arg_source(index).map(|s| s.to_owned()),
                 };
             }
 
-            if self.default.is_some() {
+            if let Some(value) = self.env.and_then(|name| args.env_var(name)) {
+                Arg::Env {
+                    spec: self,
+                    metadata,
+                    value: self.canonicalize(value),
+                }
+            } else if self.default.is_some() {
                 Arg::Default {
                     spec: self,
                     metadata,
                 }
             } else {
+                let detached = self
+                    .looks_required()
+                    .then(|| args.pop_detached_value())
+                    .flatten();
+                if let Some(detached) = detached {
+                    args.push_warning(format!(
+                        "'{}' was used as the value of '{}', but '{}' is missing; did you mean '{} <{}> {}'?",
+                        detached.value,
+                        detached.opt_name,
+                        self.name,
+                        detached.opt_name,
+                        detached.opt_ty,
+                        detached.value
+                    ));
+                }
                 Arg::None { spec: self }
             }
         })
     }
+
+    fn canonicalize(self, value: String) -> String {
+        match canonical_choice(self.choices, &value).filter(|_| self.ignore_case) {
+            Some(choice) => choice.to_owned(),
+            None => value,
+        }
+    }
+
+    fn looks_required(self) -> bool {
+        // See the naming convention described in the documentation of `noargs::arg()`.
+        self.example.is_some() || self.name.starts_with('<')
+    }
 }
 
 impl Default for ArgSpec {
@@ -106,33 +335,93 @@ impl Default for ArgSpec {
 }
 
 /// A positional argument.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The [`Debug`](std::fmt::Debug) implementation shows sensitive values as `[redacted]` (see [`Arg::is_sensitive()`]).
+///
+/// The enum and its variants are `#[non_exhaustive]`, so that new fields can be added in a compatible way.
+/// Use the accessor methods (e.g., [`Arg::value()`] and [`Arg::index()`]) or `..` in patterns to read them.
+#[derive(Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
+#[non_exhaustive]
 pub enum Arg {
+    #[non_exhaustive]
     Positional {
         spec: ArgSpec,
         metadata: Metadata,
         index: usize,
         value: String,
+        source: Option<String>,
     },
-    Default {
-        spec: ArgSpec,
-        metadata: Metadata,
-    },
-    Example {
+    #[non_exhaustive]
+    Env {
         spec: ArgSpec,
         metadata: Metadata,
+        value: String,
     },
-    None {
-        spec: ArgSpec,
-    },
+    #[non_exhaustive]
+    Default { spec: ArgSpec, metadata: Metadata },
+    #[non_exhaustive]
+    Example { spec: ArgSpec, metadata: Metadata },
+    #[non_exhaustive]
+    None { spec: ArgSpec },
+}
+
+impl std::fmt::Debug for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sensitive = self.is_sensitive();
+        match self {
+            Arg::Positional {
+                spec,
+                metadata,
+                index,
+                value: v,
+                source,
+            } => f
+                .debug_struct("Positional")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("index", index)
+                .field("value", redact(v, sensitive))
+                .field("source", source)
+                .finish(),
+            Arg::Env {
+                spec,
+                metadata,
+                value: v,
+            } => f
+                .debug_struct("Env")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("value", redact(v, sensitive))
+                .finish(),
+            Arg::Default { spec, metadata } => f
+                .debug_struct("Default")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .finish(),
+            Arg::Example { spec, metadata } => f
+                .debug_struct("Example")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .finish(),
+            Arg::None { spec } => f.debug_struct("None").field("spec", spec).finish(),
+        }
+    }
 }
 
+#[allow(clippy::result_large_err)]
 impl Arg {
+    /// Returns `true` if this argument was taken by `spec`, comparing [`ArgSpec::id()`] instead of the whole specification
+    /// (see [`Opt::is()`](crate::Opt::is)).
+    pub fn is(&self, spec: ArgSpec) -> bool {
+        self.spec().id() == spec.id()
+    }
+
     /// Returns the specification of this argument.
     pub fn spec(&self) -> ArgSpec {
         match self {
             Arg::Positional { spec, .. }
+            | Arg::Env { spec, .. }
             | Arg::Default { spec, .. }
             | Arg::Example { spec, .. }
             | Arg::None { spec } => *spec,
@@ -144,6 +433,21 @@ impl Arg {
         !matches!(self, Self::None { .. })
     }
 
+    /// Returns `true` if this argument is given on the command line.
+    pub fn is_from_cli(&self) -> bool {
+        matches!(self, Self::Positional { .. })
+    }
+
+    /// Returns `true` if the value of this argument comes from the environment variable.
+    pub fn is_env(&self) -> bool {
+        matches!(self, Self::Env { .. })
+    }
+
+    /// Returns `true` if the value of this argument is the default value.
+    pub fn is_default(&self) -> bool {
+        matches!(self, Self::Default { .. })
+    }
+
     /// Returns `Some(self)` if this argument is present.
     pub fn present(self) -> Option<Self> {
         self.is_present().then_some(self)
@@ -174,6 +478,7 @@ impl Arg {
     /// # Errors
     ///
     /// - Returns [`Error::MissingArg`] if `self.is_present()` is `false` (argument is missing)
+    /// - Returns [`Error::InvalidArg`] if the value is not one of [`ArgSpec::choices`]
     /// - Returns [`Error::InvalidArg`] if `f(self)` returns `Err(_)` (validation or conversion failed)
     pub fn then<F, T, E>(self, f: F) -> Result<T, Error>
     where
@@ -185,6 +490,13 @@ impl Arg {
                 arg: Box::new(self),
             });
         }
+        let choices = self.spec().choices;
+        if !choices.is_empty() && !choices.contains(&self.value()) {
+            return Err(Error::InvalidArg {
+                arg: Box::new(self),
+                reason: format!("expected one of: {}", choices.join(", ")),
+            });
+        }
         f(self.clone()).map_err(|e| Error::InvalidArg {
             arg: Box::new(self),
             reason: e.to_string(),
@@ -200,16 +512,51 @@ impl Arg {
         self.present().map(|arg| arg.then(f)).transpose()
     }
 
+    /// Returns the raw value of this argument, or `default` if not present.
+    ///
+    /// This is useful when the fallback value is computed at runtime (i.e., [`ArgSpec::default`] is not applicable).
+    pub fn present_or(&self, default: &str) -> String {
+        self.present_or_else(|| default.to_owned())
+    }
+
+    /// Returns the raw value of this argument, or the result of `f()` if not present.
+    pub fn present_or_else<F>(&self, f: F) -> String
+    where
+        F: FnOnce() -> String,
+    {
+        if self.is_present() {
+            self.value().to_owned()
+        } else {
+            f()
+        }
+    }
+
     /// Returns the raw value of this argument, or an empty string if not present.
     pub fn value(&self) -> &str {
         match self {
-            Arg::Positional { value, .. } => value.as_str(),
+            Arg::Positional { value, .. } | Arg::Env { value, .. } => value.as_str(),
             Arg::Default { spec, .. } => spec.default.unwrap_or(""),
             Arg::Example { spec, .. } => spec.example.unwrap_or(""),
             Arg::None { .. } => "",
         }
     }
 
+    /// Consumes this argument and returns the owned raw value, or an empty string if not present.
+    pub fn into_value(self) -> String {
+        match self {
+            Arg::Positional { value, .. } | Arg::Env { value, .. } => value,
+            _ => self.value().to_owned(),
+        }
+    }
+
+    /// Returns `true` if the value of this argument is sensitive.
+    ///
+    /// This is the case if [`ArgSpec::sensitive`] is `true`, or the value is resolved by [`Metadata::env_resolver`].
+    pub fn is_sensitive(&self) -> bool {
+        self.spec().sensitive
+            || matches!(self, Arg::Env { spec: ArgSpec { env: Some(name), .. }, .. } if is_env_reference(name))
+    }
+
     /// Returns the index at which the raw value of this argument was located in [`RawArgs`].
     pub fn index(&self) -> Option<usize> {
         if let Arg::Positional { index, .. } = self {
@@ -219,9 +566,19 @@ impl Arg {
         }
     }
 
+    /// Returns the label of the source that injected the raw argument of this argument
+    /// (see [`RawArgs::push_injected()`]), or `None` if it was given by the user.
+    pub fn injected_by(&self) -> Option<&str> {
+        match self {
+            Arg::Positional { source, .. } => source.as_deref(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn metadata(&self) -> Option<Metadata> {
         match self {
             Arg::Positional { metadata, .. }
+            | Arg::Env { metadata, .. }
             | Arg::Default { metadata, .. }
             | Arg::Example { metadata, .. } => Some(*metadata),
             Arg::None { .. } => None,
@@ -294,6 +651,288 @@ mod tests {
         );
     }
 
+    #[test]
+    fn present_or_fallback() {
+        let mut args = test_args(&["test", "foo"]);
+        let arg = crate::arg("[NAME]");
+        assert_eq!(arg.take(&mut args).present_or("bar"), "foo");
+        assert_eq!(arg.take(&mut args).present_or("bar"), "bar");
+        assert_eq!(
+            arg.take(&mut args).present_or_else(|| "baz".to_owned()),
+            "baz"
+        );
+    }
+
+    #[test]
+    fn take_all() {
+        let mut args = test_args(&["test", "a", "b"]);
+        let arg = crate::arg("[FILE]...").default("-");
+        let values = arg.take_all(&mut args);
+        assert_eq!(
+            values.iter().map(|a| a.value()).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert!(matches!(arg.take_all(&mut args)[..], [Arg::Default { .. }]));
+    }
+
+    #[test]
+    fn into_value() {
+        let mut args = test_args(&["test", "foo"]);
+        let arg = crate::arg("[NAME]").default("bar");
+        assert_eq!(arg.take(&mut args).into_value(), "foo");
+        assert_eq!(arg.take(&mut args).into_value(), "bar");
+        assert_eq!(crate::arg("[NAME]").take(&mut args).into_value(), "");
+    }
+
+    #[test]
+    fn swapped_opt_value_warning() {
+        let mut args = test_args(&["test", "--output", "in.txt"]);
+        crate::opt("output").ty("PATH").take(&mut args);
+        crate::arg("[EXTRA]").take(&mut args);
+        assert!(args.warnings().is_empty());
+
+        crate::arg("<INPUT>").take(&mut args);
+        assert_eq!(
+            args.warnings(),
+            [
+                "'in.txt' was used as the value of '--output', but '<INPUT>' is missing; did you mean '--output <PATH> in.txt'?"
+            ]
+        );
+
+        // The positional argument following the option value has been taken.
+        let mut args = test_args(&["test", "--output", "x.txt", "in.txt"]);
+        crate::opt("output").take(&mut args);
+        assert_eq!(crate::arg("<IN>").take(&mut args).value(), "in.txt");
+        crate::arg("<OTHER>").take(&mut args);
+        assert!(args.warnings().is_empty());
+    }
+
+    #[test]
+    fn arg_index_window() {
+        let mut args = test_args(&["test", "foo", "bar", "baz"]);
+        let arg = crate::arg("[NAME]").after_index(1).before_index(3);
+        assert!(matches!(
+            arg.take(&mut args),
+            Arg::Positional { index: 2, .. }
+        ));
+        assert!(matches!(arg.take(&mut args), Arg::None { .. }));
+    }
+
+    #[test]
+    fn bare_dash_arg() {
+        let mut args = test_args(&["test", "-", "-x", "-o", "out", "--", "-"]);
+        args.metadata_mut().posix_ordering = true;
+        assert!(!crate::flag("").short('x').take(&mut args).is_present());
+        assert!(
+            !crate::opt("")
+                .short('o')
+                .digits_shorthand()
+                .take(&mut args)
+                .is_present()
+        );
+
+        let mut args = test_args(&["test", "-", "-x", "-o", "-"]);
+        assert!(crate::flag("").short('x').take(&mut args).is_present());
+        assert_eq!(crate::opt("").short('o').take(&mut args).value(), "-");
+        let arg = crate::arg("<FILE>").take(&mut args);
+        assert!(matches!(arg, Arg::Positional { index: 1, .. }));
+        assert_eq!(arg.value(), "-");
+    }
+
+    #[test]
+    fn env_arg() {
+        let mut args = test_args(&["test", "foo"]);
+        let arg = crate::arg("<NAME>").env("TEST_ENV_ARG_NAME").default("baz");
+        assert!(matches!(arg.take(&mut args), Arg::Positional { .. }));
+        assert!(matches!(arg.take(&mut args), Arg::Default { .. }));
+
+        unsafe {
+            std::env::set_var("TEST_ENV_ARG_NAME", "bar");
+        }
+        let arg = arg.take(&mut args);
+        assert!(matches!(arg, Arg::Env { .. }));
+        assert!(arg.is_env() && !arg.is_from_cli() && !arg.is_default());
+        assert_eq!(arg.value(), "bar");
+
+        let e = arg.then(|a| a.value().parse::<usize>()).expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"environment variable 'TEST_ENV_ARG_NAME' for '<NAME>' has an invalid value "bar": invalid digit found in string
+
+Try '--help' for more information."#
+        );
+    }
+
+    #[test]
+    fn take_rest() {
+        let mut args = test_args(&["test", "foo", "--", "bar", "--", "-x"]);
+        crate::arg("<NAME>").take(&mut args);
+        assert_eq!(args.take_rest(), ["bar", "--", "-x"]);
+        assert_eq!(args.remaining_args().next(), None);
+
+        let mut args = test_args(&["test", "foo", "-x"]);
+        assert_eq!(args.take_rest(), ["foo", "-x"]);
+        assert!(args.take_rest().is_empty());
+    }
+
+    #[cfg(feature = "timings")]
+    #[test]
+    fn timings() {
+        let mut args = test_args(&["test", "foo"]);
+        assert_eq!(args.timings(), crate::Timings::default());
+        crate::arg("<NAME>").take(&mut args);
+        let timings = args.timings();
+        assert!(timings.take > std::time::Duration::ZERO);
+        assert_eq!(timings.help, std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn argv_spill_warning() {
+        let args = test_args(&["test", "foo", "bar"]);
+        assert!(args.warnings().is_empty());
+
+        let long = "x".repeat(128 * 1024);
+        let args = test_args(&["test", "foo", &long]);
+        assert_eq!(
+            args.warnings(),
+            [format!(
+                "the command line is very long (2 arguments, {} bytes) and may have been truncated or split; \
+                 consider passing the arguments via a file (e.g., '@args.txt')",
+                128 * 1024 + 5
+            )]
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let mut args = test_args(&["test", "foo", "--", "a", "--", "b", "--", "c"]);
+        crate::arg("<NAME>").take(&mut args);
+        assert_eq!(
+            args.sections(&["X", "Y"]),
+            [vec!["a"], vec!["b", "--", "c"]]
+        );
+        assert!(args.finish().is_ok());
+
+        let mut args = test_args(&["test", "--", "a"]);
+        assert_eq!(args.sections(&["X", "Y"]), [vec!["a"], vec![]]);
+
+        let mut args = test_args(&["test", "a"]);
+        assert_eq!(args.sections(&["X"]), [Vec::<String>::new()]);
+        assert!(args.finish().is_err());
+    }
+
+    #[test]
+    fn custom_terminator() {
+        let raw_args = ["test", "foo", ";;", "-x", "--", ";;", "bar"];
+        let mut args = test_args(&raw_args);
+        args.metadata_mut().terminator = ";;";
+        assert_eq!(args.terminator_index(), Some(2));
+        assert_eq!(args.sections(&["X", "Y"]), [vec!["-x", "--"], vec!["bar"]]);
+        assert_eq!(args.terminator_index(), None);
+
+        let mut args = test_args(&raw_args);
+        args.metadata_mut().terminator = ";;";
+        assert_eq!(args.take_rest(), ["-x", "--", ";;", "bar"]);
+        assert_eq!(crate::arg("<NAME>").take(&mut args).value(), "foo");
+    }
+
+    #[test]
+    fn positional_at() {
+        let mut args = test_args(&["test", "--foo", "a", "b", "c"]);
+        crate::flag("foo").take(&mut args);
+        let c = crate::arg("<C>").at(2).take(&mut args);
+        let a = crate::arg("<A>").at(0).take(&mut args);
+        let b = crate::arg("<B>").take(&mut args);
+        assert_eq!((a.value(), b.value(), c.value()), ("a", "b", "c"));
+
+        // Already taken.
+        assert!(!crate::arg("<X>").at(1).take(&mut args).is_present());
+        assert!(!crate::arg("<Y>").at(3).take(&mut args).is_present());
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn sensitive_arg() {
+        let mut args = test_args(&["test", "hunter2"]);
+        let arg = crate::arg("<PASSWORD>").sensitive().take(&mut args);
+        assert!(arg.is_sensitive());
+        assert!(format!("{arg:?}").contains("value: [redacted]"));
+        assert!(!format!("{arg:?}").contains("hunter2"));
+
+        let e = arg.then(|a| a.value().parse::<u32>()).expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "argument '<PASSWORD>' has an invalid value [redacted]: invalid digit found in string\n\nTry '--help' for more information."
+        );
+
+        let mut args = test_args(&["test", "--help"]);
+        args.metadata_mut().help_mode = true;
+        crate::arg("<PASSWORD>")
+            .sensitive()
+            .example("hunter2")
+            .take(&mut args);
+        crate::opt("token")
+            .sensitive()
+            .example("s3cr3t")
+            .take(&mut args);
+        let help = args.finish().expect("help").expect("help");
+        assert!(
+            help.contains("$ <APP_NAME> [redacted] --token [redacted]"),
+            "{help}"
+        );
+    }
+
+    #[test]
+    fn argfile_expansion() {
+        let path = std::env::temp_dir().join(format!("noargs-argfile-{}", std::process::id()));
+        std::fs::write(&path, "bar\n  'baz qux'\tquux\n").expect("write");
+        let argfile = format!("@{}", path.display());
+
+        let mut args = test_args(&["test", "foo", &argfile, "@", "--", &argfile]);
+        args.expand_argfiles().expect("expand");
+        let values = std::iter::from_fn(|| crate::arg("<ARG>").take(&mut args).present())
+            .map(|a| a.value().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["foo", "bar", "baz qux", "quux", "@", &argfile]);
+
+        std::fs::write(&path, "'unterminated").expect("write");
+        let mut args = test_args(&["test", &argfile]);
+        let e = args.expand_argfiles().expect_err("unterminated quote");
+        let _ = std::fs::remove_file(&path);
+        assert!(e.to_string(false).contains("unterminated"), "{e:?}");
+
+        let mut args = test_args(&["test", "@/nonexistent/noargs-argfile"]);
+        let e = args.expand_argfiles().expect_err("missing file");
+        assert!(
+            e.to_string(false)
+                .starts_with("failed to read argument file '/nonexistent/noargs-argfile': ")
+        );
+        assert!(crate::arg("<ARG>").take(&mut args).is_present());
+    }
+
+    #[test]
+    fn choices_arg() {
+        let mut args = test_args(&["test", "xml", "yaml"]);
+        let arg = crate::arg("<FORMAT>").choices(&["json", "yaml"]);
+        let e = arg
+            .take(&mut args)
+            .then(|a| Ok::<_, String>(a.value().to_owned()))
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '<FORMAT>' has an invalid value "xml": expected one of: json, yaml
+
+Try '--help' for more information."#
+        );
+        let value = arg
+            .take(&mut args)
+            .then(|a| Ok::<_, String>(a.value().to_owned()));
+        assert_eq!(value.ok().as_deref(), Some("yaml"));
+
+        let mut args = test_args(&["test", "YAML"]);
+        assert_eq!(arg.ignore_case().take(&mut args).value(), "yaml");
+    }
+
     fn test_args(raw_args: &[&str]) -> RawArgs {
         RawArgs::new(raw_args.iter().map(|a| a.to_string()))
     }
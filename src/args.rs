@@ -1,17 +1,37 @@
-use std::{borrow::Cow, io::IsTerminal};
+use std::{
+    borrow::Cow,
+    io::IsTerminal,
+    ops::{Bound, ControlFlow, Deref, DerefMut, Range, RangeBounds},
+    process::ExitCode,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
-use crate::{Arg, Cmd, Error, Flag, Opt, help::HelpBuilder};
-#[expect(unused_imports)]
+use crate::{
+    Arg, ArgGroup, Cmd, CmdSpec, Error, Flag, Opt, Output,
+    help::{self, HelpBuilder, HelpCacheKey},
+};
+#[allow(unused_imports)]
 use crate::{ArgSpec, OptSpec};
 
 /// Raw arguments that will be converted into [`Arg`], [`Opt`], [`Flag`] and [`Cmd`] instances.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RawArgs {
     metadata: Metadata,
     raw_args: Vec<RawArg>,
     log: Vec<Taken>,
+    warnings: Vec<String>,
+    detached_values: Vec<DetachedValue>,
+    repeated_opts: Vec<Opt>,
+    scope: Range<usize>,
+    generation: u64,
+    #[cfg(feature = "timings")]
+    timings: std::cell::Cell<Timings>,
 }
 
+/// Source of [`RawArgs::generation()`] values (unique per process).
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[allow(clippy::result_large_err)]
 impl RawArgs {
     /// Makes an [`RawArgs`] instance with the given raw arguments.
     pub fn new<I>(args: I) -> Self
@@ -22,15 +42,53 @@ impl RawArgs {
             .enumerate()
             .map(|(i, value)| RawArg {
                 value: (i != 0).then_some(value),
+                abbreviation: None,
+                external_name: None,
+                source: None,
             })
-            .collect();
+            .collect::<Vec<RawArg>>();
+        let warnings = argv_spill_warning(&raw_args).into_iter().collect();
         Self {
             metadata: Metadata::default(),
             raw_args,
             log: Vec::new(),
+            warnings,
+            detached_values: Vec::new(),
+            repeated_opts: Vec::new(),
+            scope: 0..usize::MAX,
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "timings")]
+            timings: std::cell::Cell::default(),
         }
     }
 
+    /// Makes an [`RawArgs`] instance by splitting `command_line` into raw arguments.
+    ///
+    /// `command_line` does not include the program name (e.g., a line entered in a REPL).
+    /// Arguments are separated by whitespace, and single quotes, double quotes and backslashes
+    /// can be used as in POSIX shells (no other expansions are performed).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Other`] if `command_line` has an unterminated quote or a trailing backslash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::from_command_line("run --port 8080 'some file'")?;
+    /// assert!(noargs::cmd("run").take(&mut args).is_present());
+    /// assert_eq!(noargs::opt("port").take(&mut args).value(), "8080");
+    /// assert_eq!(noargs::arg("<FILE>").take(&mut args).value(), "some file");
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn from_command_line(command_line: &str) -> Result<Self, Error> {
+        let args = split_command_line(command_line).map_err(|reason| Error::Other {
+            metadata: None,
+            error: format!("failed to parse command line: {reason}"),
+        })?;
+        Ok(Self::new(std::iter::once(String::new()).chain(args)))
+    }
+
     /// Returns the metadata.
     pub fn metadata(&self) -> Metadata {
         self.metadata
@@ -49,20 +107,731 @@ impl RawArgs {
             .filter_map(|(i, a)| a.value.as_ref().map(|v| (i, v.as_str())))
     }
 
+    /// Returns the token identifying the raw arguments of this instance.
+    ///
+    /// Every [`RawArgs::new()`] (and [`RawArgs::reparse()`]) call produces a distinct value,
+    /// which is recorded in the values taken from the instance (e.g., [`Cmd`]) to detect mixing instances.
+    /// Clones share the same value.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Makes a new [`RawArgs`] instance with the given raw arguments and the metadata of this instance.
+    ///
+    /// The metadata updated while taking arguments (i.e., [`Metadata::help_mode`], [`Metadata::full_help`]
+    /// and [`Metadata::dry_run`]) are reset to the default values.
+    /// This is useful to re-parse a new argument list at runtime with the same specifications (see [`ReparseDiff`](crate::ReparseDiff)).
+    pub fn reparse<I>(&self, args: I) -> Self
+    where
+        I: Iterator<Item = String>,
+    {
+        let default = Metadata::default();
+        let mut this = Self::new(args);
+        this.metadata = Metadata {
+            help_mode: default.help_mode,
+            full_help: default.full_help,
+            dry_run: default.dry_run,
+            ..self.metadata
+        };
+        this
+    }
+
+    /// Restricts the raw arguments considered by subsequent `take()` calls to `range` (indices of raw arguments)
+    /// until the returned guard is dropped.
+    ///
+    /// Nested scopes are intersected with the outer ones.
+    /// See also [`Cmd::scope()`], which restricts the arguments to those after a subcommand.
+    pub fn scope<R>(&mut self, range: R) -> Scope<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i.saturating_add(1),
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => usize::MAX,
+        };
+        let prev = self.scope.clone();
+        let start = start.max(prev.start);
+        self.scope = start..end.min(prev.end).max(start);
+        Scope { args: self, prev }
+    }
+
+    /// Saves the current parsing state (consumed raw arguments, taken specs, warnings and metadata)
+    /// so that it can be rolled back by [`RawArgs::restore()`].
+    ///
+    /// This is useful to try one interpretation of the arguments speculatively
+    /// (e.g., "is the first token a subcommand or a file?") and fall back to another if it fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "notes.txt"].iter().map(|a| a.to_string()));
+    /// let snapshot = args.snapshot();
+    ///
+    /// let cmd = noargs::cmd("open").take(&mut args);
+    /// let file = noargs::arg("<FILE>").take(&mut args);
+    /// assert!(!cmd.is_present());
+    ///
+    /// // Not a subcommand: parse the arguments again without it.
+    /// args.restore(snapshot);
+    /// let file = noargs::arg("<FILE>").take(&mut args);
+    /// assert_eq!(file.value(), "notes.txt");
+    /// assert!(args.finish()?.is_none());
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            metadata: self.metadata,
+            raw_args: self.raw_args.clone(),
+            log: self.log.clone(),
+            warnings: self.warnings.clone(),
+            detached_values: self.detached_values.clone(),
+            repeated_opts: self.repeated_opts.clone(),
+        }
+    }
+
+    /// Rolls back the parsing state to `snapshot` taken by [`RawArgs::snapshot()`].
+    ///
+    /// The current scope (see [`RawArgs::scope()`]) is not changed.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.metadata = snapshot.metadata;
+        self.raw_args = snapshot.raw_args;
+        self.log = snapshot.log;
+        self.warnings = snapshot.warnings;
+        self.detached_values = snapshot.detached_values;
+        self.repeated_opts = snapshot.repeated_opts;
+    }
+
+    /// Replaces each raw argument of the form `@path` with the arguments read from the file at `path`
+    /// (also known as response files).
+    ///
+    /// The file contents are split as in [`RawArgs::from_command_line()`] (i.e., whitespace-separated, with quotes),
+    /// so an argument containing whitespace can be written as `'some file'`.
+    /// The program name and the arguments after [`Metadata::terminator`] are not expanded.
+    ///
+    /// This should be called before taking any arguments.
+    /// Arguments read from a file are not expanded recursively.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Other`] if a file cannot be read or has an unterminated quote or a trailing backslash.
+    pub fn expand_argfiles(&mut self) -> Result<(), Error> {
+        let mut expanded = Vec::with_capacity(self.raw_args.len());
+        let mut raw_args = std::mem::take(&mut self.raw_args).into_iter();
+        // The program name.
+        expanded.extend(raw_args.next());
+        while let Some(raw_arg) = raw_args.next() {
+            if raw_arg.value.as_deref() == Some(self.metadata.terminator) {
+                expanded.push(raw_arg);
+                expanded.extend(raw_args);
+                break;
+            }
+            let Some(path) = raw_arg
+                .value
+                .as_deref()
+                .and_then(|v| v.strip_prefix('@'))
+                .filter(|path| !path.is_empty())
+            else {
+                expanded.push(raw_arg);
+                continue;
+            };
+            let values = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| split_command_line(&text).map_err(|e| e.to_owned()));
+            let values = match values {
+                Ok(values) => values,
+                Err(e) => {
+                    let error = format!("failed to read argument file '{path}': {e}");
+                    expanded.push(raw_arg);
+                    expanded.extend(raw_args);
+                    self.raw_args = expanded;
+                    return Err(Error::other(self, error));
+                }
+            };
+            expanded.extend(values.into_iter().map(|value| RawArg {
+                value: Some(value),
+                abbreviation: None,
+                external_name: None,
+                source: raw_arg.source.clone(),
+            }));
+        }
+        self.raw_args = expanded;
+        Ok(())
+    }
+
+    /// Appends raw arguments that were not given by the user but injected by some other source (e.g., a wrapper script).
+    ///
+    /// `source` is a label describing where the arguments came from.
+    /// If an error is caused by an injected argument (e.g., it is left unconsumed or has an invalid value),
+    /// the error message names `source` so that users are not blamed for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["myapp"].iter().map(|a| a.to_string()));
+    /// args.push_injected(["--colour".to_owned()].into_iter(), "wrapper");
+    /// noargs::flag("color").take(&mut args);
+    ///
+    /// assert_eq!(args.arg_source(1), Some("wrapper"));
+    /// assert!(args.finish().is_err()); // "unexpected argument '--colour' found (injected by wrapper)"
+    /// ```
+    pub fn push_injected<I>(&mut self, args: I, source: &str)
+    where
+        I: Iterator<Item = String>,
+    {
+        self.raw_args.extend(args.map(|value| RawArg {
+            value: Some(value),
+            abbreviation: None,
+            external_name: None,
+            source: Some(source.to_owned()),
+        }));
+    }
+
+    /// Inserts raw arguments that were not given by the user (e.g., defaults read from a profile or an expanded alias)
+    /// before or after the user-supplied arguments.
+    ///
+    /// With [`ArgsPlacement::BeforeUserArgs`], the arguments are inserted right after the program name.
+    /// With [`ArgsPlacement::AfterUserArgs`], they are inserted before the first `--` (or appended if there is no `--`).
+    /// As options and flags are matched in the order of the raw arguments, the placement decides precedence
+    /// when the same option is given by both (e.g., the user-supplied value wins with [`ArgsPlacement::AfterUserArgs`]
+    /// unless [`RepeatPolicy::LastWins`](crate::RepeatPolicy::LastWins) is used).
+    /// The arguments are treated as injected ones labeled with `source` (see [`RawArgs::push_injected()`]).
+    ///
+    /// This should be called before taking any arguments, as it shifts the indices of the user-supplied arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use noargs::{ArgsPlacement, RepeatPolicy};
+    ///
+    /// let mut args = noargs::RawArgs::new(["myapp", "--color=auto"].iter().map(|a| a.to_string()));
+    /// let profile = ["--color=never".to_owned(), "--jobs=4".to_owned()];
+    /// args.prepend_args(profile.into_iter(), "profile", ArgsPlacement::BeforeUserArgs);
+    ///
+    /// let color = noargs::opt("color").repeat(RepeatPolicy::LastWins).take(&mut args);
+    /// assert_eq!(color.value(), "auto");
+    /// assert_eq!(noargs::opt("jobs").take(&mut args).value(), "4");
+    /// assert_eq!(args.arg_source(2), Some("profile"));
+    /// ```
+    pub fn prepend_args<I>(&mut self, args: I, source: &str, placement: ArgsPlacement)
+    where
+        I: Iterator<Item = String>,
+    {
+        let position = match placement {
+            ArgsPlacement::BeforeUserArgs => self.raw_args.len().min(1),
+            ArgsPlacement::AfterUserArgs => self.terminator_position(),
+        };
+        self.insert_injected(position, args, source);
+    }
+
+    /// Inserts the arguments given by the environment variable `name` (e.g., `MYAPP_FLAGS`),
+    /// similar to `RUSTFLAGS`, so that users can set persistent defaults.
+    ///
+    /// The value is split into arguments by whitespace, and single quotes, double quotes and backslashes
+    /// can be used as in POSIX shells (e.g., `--name 'John Doe'`).
+    /// The arguments are inserted before the first `--` (or appended if there is no `--`),
+    /// so options given on the command line take precedence over them.
+    /// The arguments are treated as injected ones labeled with `name` (see [`RawArgs::push_injected()`]).
+    ///
+    /// Nothing is inserted if the variable is not set, is empty or [`Metadata::ignore_env`] is `true`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Other`] if the value has an unterminated quote or a trailing backslash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::set_var("MYAPP_FLAGS", "--color=never --name 'John Doe'");
+    /// // MYAPP_FLAGS="--color=never --name 'John Doe'"
+    /// let mut args = noargs::RawArgs::new(["myapp", "--color=auto"].iter().map(|a| a.to_string()));
+    /// args.extend_from_env("MYAPP_FLAGS")?;
+    ///
+    /// let color = noargs::opt("color").take(&mut args);
+    /// assert_eq!(color.value(), "auto");
+    /// let name = noargs::opt("name").take(&mut args);
+    /// assert_eq!(name.value(), "John Doe");
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn extend_from_env(&mut self, name: &str) -> Result<(), Error> {
+        let Some(value) = self.env_var(name) else {
+            return Ok(());
+        };
+        let values = split_command_line(&value).map_err(|reason| {
+            Error::other(
+                self,
+                format!("failed to parse environment variable '{name}': {reason}"),
+            )
+        })?;
+        let position = self.terminator_position();
+        self.insert_injected(position, values.into_iter(), name);
+        Ok(())
+    }
+
+    fn terminator_position(&self) -> usize {
+        self.terminator_index().unwrap_or(self.raw_args.len())
+    }
+
+    fn insert_injected<I>(&mut self, position: usize, args: I, source: &str)
+    where
+        I: Iterator<Item = String>,
+    {
+        self.raw_args.splice(
+            position..position,
+            args.map(|value| RawArg {
+                value: Some(value),
+                abbreviation: None,
+                external_name: None,
+                source: Some(source.to_owned()),
+            }),
+        );
+    }
+
+    /// Returns the source label of the raw argument at `index` if it was added by [`RawArgs::push_injected()`].
+    pub fn arg_source(&self, index: usize) -> Option<&str> {
+        self.raw_args.get(index)?.source.as_deref()
+    }
+
+    /// Returns the index of the first unconsumed terminator ([`Metadata::terminator`], `--` by default) if any.
+    ///
+    /// This is useful to restrict specifications to the arguments before or after the terminator
+    /// (e.g., [`OptSpec::before_index()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--name", "a", ";;", "--name", "b"].iter().map(|a| a.to_string()));
+    /// args.metadata_mut().terminator = ";;";
+    /// let end = args.terminator_index().unwrap_or(usize::MAX);
+    /// assert_eq!(end, 3);
+    ///
+    /// let name = noargs::opt("name").before_index(end).take(&mut args);
+    /// assert_eq!(name.value(), "a");
+    /// assert_eq!(args.take_rest(), ["--name", "b"]);
+    /// ```
+    pub fn terminator_index(&self) -> Option<usize> {
+        let terminator = self.metadata.terminator;
+        self.raw_args
+            .iter()
+            .position(|a| a.value.as_deref() == Some(terminator))
+    }
+
+    /// Takes all the remaining raw arguments after the first unconsumed `--` ([`Metadata::terminator`]),
+    /// or all the remaining raw arguments if there is no `--`.
+    ///
+    /// The `--` itself is also consumed but not included in the result.
+    /// If an external subcommand ([`CmdSpec::external`]) has been taken, all the remaining raw arguments after it
+    /// (including `--`) are taken instead.
+    /// This is useful to pass trailing arguments to a child process (e.g., `myapp exec -- cmd --its --flags`).
+    ///
+    /// Note that [`OptSpec::take()`] and [`FlagSpec::take()`](crate::FlagSpec::take) do not stop at `--`,
+    /// so this method should be called before them if the trailing arguments may contain the same names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["myapp", "exec", "--", "cmd", "--verbose"].iter().map(|a| a.to_string()));
+    /// noargs::cmd("exec").take(&mut args);
+    /// let rest = args.take_rest();
+    /// assert!(!noargs::flag("verbose").take(&mut args).is_present());
+    ///
+    /// assert_eq!(rest, ["cmd", "--verbose"]);
+    /// ```
+    pub fn take_rest(&mut self) -> Vec<String> {
+        let external_cmd_index = self.log.iter().rev().find_map(|taken| match taken {
+            Taken::Cmd(cmd) if cmd.spec().external => cmd.index(),
+            _ => None,
+        });
+        let start = if let Some(i) = external_cmd_index {
+            i + 1
+        } else {
+            self.terminator_index()
+                .map(|i| {
+                    self.raw_args[i].value = None;
+                    i + 1
+                })
+                .unwrap_or(0)
+        };
+        self.raw_args[start..]
+            .iter_mut()
+            .filter_map(|a| a.value.take())
+            .collect()
+    }
+
+    /// Takes the remaining raw arguments after the first unconsumed `--` ([`Metadata::terminator`]) as `--`-separated sections
+    /// (e.g., `tool -- BUILD_ARGS -- RUN_ARGS`).
+    ///
+    /// The result always has `names.len()` sections (missing sections are empty),
+    /// and the last section contains the rest of the arguments including any further `--`.
+    /// `names` are shown in the usage line of the help text (e.g., `[-- BUILD_ARGS] [-- RUN_ARGS]`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(
+    ///     ["tool", "--release", "--", "--features=foo", "--", "--port", "8080"].iter().map(|a| a.to_string()),
+    /// );
+    /// let release = noargs::flag("release").take(&mut args);
+    /// let [build_args, run_args] = args.sections(&["BUILD_ARGS", "RUN_ARGS"]).try_into().expect("infallible");
+    /// assert!(release.is_present());
+    /// assert_eq!(build_args, ["--features=foo"]);
+    /// assert_eq!(run_args, ["--port", "8080"]);
+    /// ```
+    pub fn sections(&mut self, names: &'static [&'static str]) -> Vec<Vec<String>> {
+        self.log.push(Taken::Sections(names));
+        let mut sections = vec![Vec::new(); names.len()];
+        let Some(start) = self.terminator_index().filter(|_| !names.is_empty()) else {
+            return sections;
+        };
+        let mut i = 0;
+        for raw_arg in &mut self.raw_args[start..] {
+            let Some(value) = raw_arg.value.take() else {
+                continue;
+            };
+            if value == self.metadata.terminator && i < names.len() {
+                i += 1;
+                continue;
+            }
+            sections[i - 1].push(value);
+        }
+        sections
+    }
+
+    /// Returns the version of the innermost present subcommand that has [`CmdSpec::version`].
+    ///
+    /// This is useful to report the version of a subcommand (e.g., `myapp plugin --version`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["myapp", "plugin", "--version"].iter().map(|a| a.to_string()));
+    /// noargs::cmd("plugin").version("1.2.0").take(&mut args);
+    /// if noargs::VERSION_FLAG.take(&mut args).is_present() {
+    ///     let version = args.cmd_version().unwrap_or(env!("CARGO_PKG_VERSION"));
+    ///     assert_eq!(version, "1.2.0");
+    /// }
+    /// ```
+    ///
+    /// See also: [`FlagSpec::take_version()`](crate::FlagSpec::take_version)
+    pub fn cmd_version(&self) -> Option<&'static str> {
+        self.log.iter().rev().find_map(|taken| match taken {
+            Taken::Cmd(cmd) if cmd.is_present() => cmd.spec().version,
+            _ => None,
+        })
+    }
+
+    /// Returns the time spent in each parsing phase so far.
+    ///
+    /// This is only available if the `timings` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--foo"].iter().map(|a| a.to_string()));
+    /// noargs::flag("foo").take(&mut args);
+    /// # #[cfg(feature = "timings")]
+    /// eprintln!("spent {:?} in taking arguments", args.timings().take);
+    /// ```
+    #[cfg(feature = "timings")]
+    pub fn timings(&self) -> Timings {
+        self.timings.get()
+    }
+
+    #[cfg(feature = "timings")]
+    fn record_timing(
+        &self,
+        start: std::time::Instant,
+        phase: fn(&mut Timings) -> &mut std::time::Duration,
+    ) {
+        let mut timings = self.timings.get();
+        *phase(&mut timings) += start.elapsed();
+        self.timings.set(timings);
+    }
+
+    /// Returns the warnings recorded while taking arguments.
+    ///
+    /// Warnings do not prevent parsing from succeeding, but may help users to notice mistakes
+    /// (e.g., an option that consumed a value which was likely intended for a positional argument).
+    ///
+    /// A warning is also recorded when the raw arguments are so long that they are likely to have been
+    /// truncated or split by the OS or the shell (e.g., a huge file list expanded from a glob).
+    /// Note that [`RawArgs::finish()`] consumes `self`, so warnings need to be retrieved before calling it.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns the example command line synthesized from the example values of the taken specs
+    /// (the same one shown in the "Example" section of the help text), including the application name.
+    ///
+    /// Returns `None` if no taken argument or option has an example value.
+    /// The result can be passed to [`RawArgs::new()`] so that integration tests can execute the documented example
+    /// and notice when it drifts from the actual grammar.
+    /// Unlike the help text, the example values of sensitive specs are not redacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn parse(args: &mut noargs::RawArgs) -> noargs::Result<(u16, String)> {
+    ///     let port = noargs::opt("port").example("8080").take(args).then(|o| o.value().parse())?;
+    ///     let file = noargs::arg("<FILE>").example("my file.txt").take(args).then(|a| a.value().parse())?;
+    ///     Ok((port, file))
+    /// }
+    ///
+    /// let mut args = noargs::RawArgs::new(["app", "--help"].iter().map(|a| a.to_string()));
+    /// args.metadata_mut().app_name = "app";
+    /// noargs::HELP_FLAG.take_help(&mut args);
+    /// parse(&mut args)?;
+    ///
+    /// let argv = args.example_invocation().expect("example");
+    /// assert_eq!(argv, ["app", "--port", "8080", "my file.txt"]);
+    ///
+    /// let mut args = noargs::RawArgs::new(argv.into_iter());
+    /// assert_eq!(parse(&mut args)?, (8080, "my file.txt".to_owned()));
+    /// assert!(args.finish()?.is_none());
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn example_invocation(&self) -> Option<Vec<String>> {
+        let entries = self.log.iter().filter(|entry| !entry.is_hidden());
+        if !entries.clone().any(|entry| entry.has_example()) {
+            return None;
+        }
+
+        let mut argv = vec![self.metadata.app_name.to_owned()];
+        for entry in entries {
+            argv.extend(entry.example_args());
+        }
+        Some(argv)
+    }
+
+    /// Enables [`Metadata::help_mode`] in advance if `--help` or `-h` ([`HELP_FLAG`](crate::HELP_FLAG)) is given.
+    ///
+    /// In help mode, options and positional arguments only yield default and example values,
+    /// so calling this method before any other takes ensures that help is displayed even if
+    /// some arguments are invalid or subcommand branches take [`HELP_FLAG`](crate::HELP_FLAG) late
+    /// (users typing `--help` expect help rather than a validation error).
+    /// [`FlagSpec::take_help()`](crate::FlagSpec::take_help) still needs to be called to consume the flag.
+    ///
+    /// The help flag is looked up by [`Metadata::help_flag_name`] (`-h` is also recognized only if it is `Some("help")`),
+    /// and this method does nothing if it is `None`.
+    /// Arguments after `--` are not considered.
+    /// Note that [`raw_args()`](crate::raw_args) does not call this method automatically,
+    /// as `-h` may have another meaning in some applications.
+    ///
+    /// Returns `true` if help mode has been enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--port=foo", "--help"].iter().map(|a| a.to_string()));
+    /// assert!(args.detect_help_early());
+    ///
+    /// // The default value is used instead of the invalid one.
+    /// let port: u16 = noargs::opt("port").default("80").take(&mut args).then(|o| o.value().parse())?;
+    /// assert_eq!(port, 80);
+    ///
+    /// noargs::HELP_FLAG.take_help(&mut args);
+    /// assert!(args.finish()?.is_some());
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn detect_help_early(&mut self) -> bool {
+        let Some(name) = self.metadata.help_flag_name else {
+            return false;
+        };
+        let help = crate::HELP_FLAG;
+        let long = format!("--{name}");
+        let short = help
+            .short
+            .filter(|_| name == help.name)
+            .map(|c| format!("-{c}"));
+        let found = self.raw_args[..self.option_search_len()]
+            .iter()
+            .filter_map(|raw_arg| raw_arg.value.as_deref())
+            .take_while(|value| *value != self.metadata.terminator)
+            .find(|value| *value == long || Some(*value) == short.as_deref());
+        let Some(value) = found else {
+            return false;
+        };
+        let full_help = value == long;
+        self.metadata.help_mode = true;
+        self.metadata.full_help = full_help;
+        true
+    }
+
     /// Completes the parsing process and checks for any errors.
     ///
     /// If successful and [`Metadata::help_mode`] is `true`, this method returns `Ok(Some(help_text))`.
     pub fn finish(self) -> Result<Option<String>, Error> {
         if self.metadata.help_mode {
-            let help = HelpBuilder::new(&self, std::io::stdout().is_terminal()).build();
+            let help = self.build_help(self.is_help_styled(|| std::io::stdout().is_terminal()));
             Ok(Some(help))
         } else {
-            Error::check_command_error(&self)?;
-            Error::check_unexpected_arg(&self)?;
+            self.check_errors()?;
             Ok(None)
         }
     }
 
+    /// Completes the parsing process and writes the help text or the error to `output`.
+    ///
+    /// This is a shorthand for passing the result of [`RawArgs::finish()`] to [`Output::report()`],
+    /// except that the help text is styled according to [`Output::is_stdout_terminal()`].
+    /// Returns [`ControlFlow::Break`] with the exit code if the application should exit.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::ops::ControlFlow;
+    ///
+    /// fn main() -> std::process::ExitCode {
+    ///     let mut args = noargs::raw_args();
+    ///     noargs::HELP_FLAG.take_help(&mut args);
+    ///     let verbose = noargs::flag("verbose").take(&mut args).is_present();
+    ///     if let ControlFlow::Break(exit_code) = args.finish_with(&mut noargs::StdOutput) {
+    ///         return exit_code;
+    ///     }
+    ///
+    ///     // Do application logic
+    ///
+    ///     std::process::ExitCode::SUCCESS
+    /// }
+    /// ```
+    pub fn finish_with<O: Output + ?Sized>(self, output: &mut O) -> ControlFlow<ExitCode> {
+        let result = if self.metadata.help_mode {
+            Ok(Some(self.build_help(
+                self.is_help_styled(|| output.is_stdout_terminal()),
+            )))
+        } else {
+            self.check_errors().map(|()| None)
+        };
+        match output.report(result) {
+            Some(exit_code) => ControlFlow::Break(exit_code),
+            None => ControlFlow::Continue(()),
+        }
+    }
+
+    fn check_errors(&self) -> Result<(), Error> {
+        self.check_errors_except_unexpected()?;
+        Error::check_unexpected_arg(self)?;
+        Ok(())
+    }
+
+    /// Checks all errors except unexpected arguments, which [`RawArgs::finish_lenient()`] returns instead.
+    fn check_errors_except_unexpected(&self) -> Result<(), Error> {
+        Error::check_ambiguous_opt(self)?;
+        Error::check_repeated_opt(self)?;
+        Error::check_conflicting_args(self)?;
+        Error::check_required_args(self)?;
+        Error::check_required_envs(self)?;
+        Error::check_arg_groups(self)?;
+        Error::check_command_error(self)?;
+        Ok(())
+    }
+
+    /// Same as [`RawArgs::finish()`], but returns unconsumed raw arguments instead of reporting them as an error.
+    ///
+    /// This is useful for wrapper applications that forward unrecognized arguments to an underlying tool.
+    /// Other errors (e.g., a missing subcommand) are still reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["wrapper", "--verbose", "--release", "build"].iter().map(|a| a.to_string()));
+    /// let verbose = noargs::flag("verbose").take(&mut args).is_present();
+    /// let (help, rest) = args.finish_lenient()?;
+    ///
+    /// assert!(verbose);
+    /// assert_eq!(help, None);
+    /// assert_eq!(rest, ["--release", "build"]);
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn finish_lenient(self) -> Result<(Option<String>, Vec<String>), Error> {
+        if self.metadata.help_mode {
+            let help = self.build_help(self.is_help_styled(|| std::io::stdout().is_terminal()));
+            Ok((Some(help), Vec::new()))
+        } else {
+            self.check_errors_except_unexpected()?;
+            let rest = self.remaining_args().map(|(_, a)| a.to_owned()).collect();
+            Ok((None, rest))
+        }
+    }
+
+    /// Builds a man page (in roff format) from the specs taken so far.
+    ///
+    /// Like the help text, the page describes the innermost subcommand taken from the command line.
+    /// The app authors, homepage and license in [`Metadata`] are rendered as their own sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app"].iter().map(|a| a.to_string()));
+    /// args.metadata_mut().app_name = "app";
+    /// args.metadata_mut().app_description = "Does things";
+    /// args.metadata_mut().app_license = "MIT";
+    /// noargs::flag("verbose").doc("Print more").take(&mut args);
+    ///
+    /// let man = args.build_man_page();
+    /// assert!(man.starts_with(".TH \"APP\" \"1\""));
+    /// assert!(man.contains(".SH NAME\napp \\- Does things\n"));
+    /// assert!(man.contains(".SH LICENSE\nMIT\n"));
+    /// ```
+    pub fn build_man_page(&self) -> String {
+        HelpBuilder::new(self, false).build_man()
+    }
+
+    fn is_help_styled(&self, is_stdout_terminal: impl FnOnce() -> bool) -> bool {
+        self.metadata.style_help.unwrap_or_else(is_stdout_terminal)
+    }
+
+    fn build_help(&self, is_terminal: bool) -> String {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
+        let help = self.build_help_with_cache(is_terminal);
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.help);
+        help
+    }
+
+    fn build_help_with_cache(&self, is_terminal: bool) -> String {
+        if !self.metadata.cache_help {
+            return HelpBuilder::new(self, is_terminal).build();
+        }
+
+        let key = HelpCacheKey::new(self, is_terminal);
+        help::cached(key, || HelpBuilder::new(self, is_terminal).build())
+    }
+
+    /// Returns the raw arguments that re-specify the options and flags named `names` taken from the command line.
+    pub(crate) fn forwarded_args(&self, names: &[&str]) -> Vec<String> {
+        let is_target = |name: &str, display_name: String| {
+            names.iter().any(|n| *n == name || *n == display_name)
+        };
+        let mut forwarded = Vec::new();
+        for taken in &self.log {
+            match taken {
+                Taken::Opt(opt @ (Opt::Long { .. } | Opt::Short { .. }))
+                    if is_target(opt.spec().name, opt.spec().display_name()) =>
+                {
+                    forwarded.extend(opt.spec().to_args(&opt.unsplit_values()));
+                }
+                Taken::Flag(flag @ (Flag::Long { .. } | Flag::Short { .. }))
+                    if is_target(flag.spec().name, flag.spec().display_name()) =>
+                {
+                    forwarded.extend(flag.spec().to_args());
+                }
+                _ => {}
+            }
+        }
+        forwarded
+    }
+
+    pub(crate) fn raw_args(&self) -> &[RawArg] {
+        &self.raw_args
+    }
+
     pub(crate) fn raw_args_mut(&mut self) -> &mut [RawArg] {
         &mut self.raw_args
     }
@@ -75,8 +844,13 @@ impl RawArgs {
     where
         F: FnOnce(&mut Self) -> Arg,
     {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
         let arg = f(self);
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.take);
         self.log.push(Taken::Arg(arg.clone()));
+        self.check_infinite_loop();
         arg
     }
 
@@ -84,8 +858,20 @@ impl RawArgs {
     where
         F: FnOnce(&mut Self) -> Opt,
     {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
         let opt = f(self);
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.take);
+        if let Some(note) = opt.spec().deprecated.filter(|_| opt.index().is_some()) {
+            self.push_warning(format!(
+                "'{}' is deprecated: {note}",
+                opt.spec().display_name()
+            ));
+        }
         self.log.push(Taken::Opt(opt.clone()));
+        self.check_strict_take();
+        self.check_infinite_loop();
         opt
     }
 
@@ -93,8 +879,20 @@ impl RawArgs {
     where
         F: FnOnce(&mut Self) -> Flag,
     {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
         let flag = f(self);
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.take);
+        if let Some(note) = flag.spec().deprecated.filter(|_| flag.index().is_some()) {
+            self.push_warning(format!(
+                "'{}' is deprecated: {note}",
+                flag.spec().display_name()
+            ));
+        }
         self.log.push(Taken::Flag(flag));
+        self.check_strict_take();
+        self.check_infinite_loop();
         flag
     }
 
@@ -102,19 +900,571 @@ impl RawArgs {
     where
         F: FnOnce(&mut Self) -> Cmd,
     {
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
         let cmd = f(self);
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.take);
+        if let Some(note) = cmd.spec().deprecated.filter(|_| cmd.is_present()) {
+            self.push_warning(format!(
+                "'{}' command is deprecated: {note}",
+                cmd.spec().name
+            ));
+        }
         self.log.push(Taken::Cmd(cmd));
         cmd
     }
 
-    pub(crate) fn next_raw_arg_value(&self) -> Option<&str> {
-        self.raw_args.iter().find_map(|a| a.value.as_deref())
+    /// Panics (only in debug builds) if the same spec has been taken many times in a row without
+    /// consuming any raw argument, which usually indicates an infinite loop such as
+    /// `while spec.take(&mut args).is_present() { .. }` with a default value or an environment variable.
+    fn check_infinite_loop(&self) {
+        const LIMIT: usize = 1000;
+
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let Some(last) = self.log.last() else {
+            return;
+        };
+        // Present but not consuming any raw argument (e.g., `Opt::Default`).
+        let is_suspicious = match last {
+            Taken::Arg(arg) => arg.is_present() && arg.index().is_none(),
+            Taken::Opt(opt) => opt.is_present() && opt.index().is_none(),
+            Taken::Flag(flag) => flag.is_present() && flag.index().is_none(),
+            Taken::Cmd(_) | Taken::Sections(_) | Taken::Group(_) => false,
+        };
+        if !is_suspicious {
+            return;
+        }
+        let count = self
+            .log
+            .iter()
+            .rev()
+            .take(LIMIT)
+            .take_while(|taken| *taken == last)
+            .count();
+        if count == LIMIT {
+            let name = match last {
+                Taken::Arg(arg) => arg.spec().name.to_owned(),
+                Taken::Opt(opt) => opt.spec().display_name(),
+                Taken::Flag(flag) => flag.spec().display_name(),
+                Taken::Cmd(cmd) => cmd.spec().name.to_owned(),
+                Taken::Sections(names) | Taken::Group(ArgGroup { names, .. }) => names.join(" "),
+            };
+            panic!(
+                "'{name}' has been taken {LIMIT} times in a row without consuming any argument; \
+                 use `take_all()` instead of calling `take()` in a loop"
+            );
+        }
+    }
+
+    fn check_strict_take(&mut self) {
+        let Some((taken, earlier)) = self.log.split_last() else {
+            return;
+        };
+        let (name, repeatable) = match taken {
+            Taken::Opt(opt) => (opt.spec().display_name(), opt.spec().repeatable),
+            Taken::Flag(flag) => (flag.spec().display_name(), flag.spec().repeatable),
+            _ => return,
+        };
+        if !self.metadata.strict_takes || repeatable {
+            return;
+        }
+        let same_spec = |x: &Taken| match (x, taken) {
+            (Taken::Opt(x), Taken::Opt(y)) => x.spec() == y.spec(),
+            (Taken::Flag(x), Taken::Flag(y)) => x.spec() == y.spec(),
+            _ => false,
+        };
+        if earlier.iter().any(same_spec) {
+            self.push_warning(format!(
+                "'{name}' is taken more than once (use `repeatable()` if this is intended)"
+            ));
+            return;
+        }
+
+        // Different specifications sharing a name (the later one never matches).
+        let Some(names) = taken.defined_names() else {
+            return;
+        };
+        let mut conflicts: Vec<(String, String)> = Vec::new();
+        for x in earlier {
+            let Some(other_names) = x.defined_names() else {
+                continue;
+            };
+            let other = other_names[0].clone();
+            let same_kind = std::mem::discriminant(x) == std::mem::discriminant(taken);
+            if (same_kind && other == name) || conflicts.iter().any(|(_, o)| *o == other) {
+                continue;
+            }
+            if let Some(shared) = names.iter().find(|n| other_names.contains(n)) {
+                conflicts.push((shared.clone(), other));
+            }
+        }
+        for (shared, other) in conflicts {
+            self.push_warning(format!(
+                "'{shared}' is defined by both '{other}' and '{name}'"
+            ));
+        }
+    }
+
+    pub(crate) fn push_taken(&mut self, taken: Taken) {
+        self.log.push(taken);
+    }
+
+    pub(crate) fn push_warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    pub(crate) fn push_detached_value(&mut self, value: DetachedValue) {
+        self.detached_values.push(value);
+    }
+
+    pub(crate) fn push_repeated_opt(&mut self, opt: Opt) {
+        self.repeated_opts.push(opt);
+    }
+
+    pub(crate) fn repeated_opts(&self) -> &[Opt] {
+        &self.repeated_opts
+    }
+
+    pub(crate) fn pop_detached_value(&mut self) -> Option<DetachedValue> {
+        self.detached_values.pop()
+    }
+
+    /// Discards the detached values whose positions were filled by the positional argument taken at `index`.
+    pub(crate) fn consume_detached_values(&mut self, index: usize) {
+        self.detached_values.retain(|v| v.index > index);
+    }
+
+    /// Returns the long names of the options and flags taken so far (grouped by spec).
+    pub(crate) fn taken_long_names(&self) -> Vec<Vec<&'static str>> {
+        let mut groups = Vec::new();
+        for taken in &self.log {
+            let names: Vec<_> = match taken {
+                Taken::Opt(opt) => opt.spec().long_names().collect(),
+                Taken::Flag(flag) => flag.spec().long_names().collect(),
+                _ => continue,
+            };
+            if !names.is_empty() && !groups.contains(&names) {
+                groups.push(names);
+            }
+        }
+        groups
+    }
+
+    /// Returns the long names that need to be considered when an abbreviated name is given for a spec having `own_names`.
+    ///
+    /// If [`Metadata::allow_abbrev`] is `false`, this method returns `None`.
+    pub(crate) fn abbrev_rivals<I>(&self, own_names: I) -> Option<Vec<&'static str>>
+    where
+        I: Iterator<Item = &'static str>,
+    {
+        if !self.metadata.allow_abbrev {
+            return None;
+        }
+        let own_names = own_names.collect::<Vec<_>>();
+        let rivals = self
+            .taken_long_names()
+            .into_iter()
+            .filter(|names| *names != own_names)
+            .flatten()
+            .collect();
+        Some(rivals)
+    }
+
+    /// Returns the subcommands taken after the last present one (i.e., the subcommands at the current level).
+    pub(crate) fn sibling_cmds(&self) -> Vec<CmdSpec> {
+        let level = self.cmd_levels().pop().unwrap_or_default();
+        level.into_iter().map(|cmd| cmd.spec()).collect()
+    }
+
+    /// Returns the taken subcommands grouped by level.
+    ///
+    /// Each level ends with a present subcommand, except for the last one.
+    pub(crate) fn cmd_levels(&self) -> Vec<Vec<Cmd>> {
+        let mut levels = vec![Vec::new()];
+        for taken in &self.log {
+            let Taken::Cmd(cmd) = taken else {
+                continue;
+            };
+            let level = levels.last_mut().expect("infallible");
+            level.push(*cmd);
+            if cmd.is_present() {
+                levels.push(Vec::new());
+            }
+        }
+        levels
+    }
+
+    /// Returns the number of raw arguments to be searched for options and flags (see [`Metadata::posix_ordering`]).
+    pub(crate) fn option_search_len(&self) -> usize {
+        if !self.metadata.posix_ordering {
+            return self.raw_args.len();
+        }
+
+        for (index, raw_arg) in self.raw_args.iter().enumerate() {
+            let Some(value) = &raw_arg.value else {
+                continue;
+            };
+            let is_positional = value == "-"
+                || !value.starts_with('-')
+                || (self.metadata.negative_numbers_as_args && is_negative_number(value));
+            if value == self.metadata.terminator || is_positional {
+                return index;
+            }
+        }
+        self.raw_args.len()
+    }
+
+    /// Returns the range of raw argument indices within the current scope and the window specified by
+    /// `after_index` and `before_index` (both exclusive).
+    pub(crate) fn index_window(
+        &self,
+        after_index: Option<usize>,
+        before_index: Option<usize>,
+        len: usize,
+    ) -> Range<usize> {
+        let start = after_index
+            .map_or(0, |i| i.saturating_add(1))
+            .max(self.scope.start)
+            .min(len);
+        let end = before_index
+            .unwrap_or(len)
+            .min(self.scope.end)
+            .min(len)
+            .max(start);
+        start..end
+    }
+
+    /// Returns the non-empty value of the environment variable `name` (see [`Metadata::ignore_env`]).
+    pub(crate) fn env_var(&self, name: &str) -> Option<String> {
+        if self.metadata.ignore_env {
+            return None;
+        }
+        #[cfg(feature = "timings")]
+        let start = std::time::Instant::now();
+        let value = if is_env_reference(name) {
+            self.metadata.env_resolver.and_then(|resolve| resolve(name))
+        } else {
+            std::env::var(name).ok()
+        };
+        #[cfg(feature = "timings")]
+        self.record_timing(start, |t| &mut t.env);
+        value.filter(|v| !v.is_empty())
+    }
+
+    pub(crate) fn next_raw_arg(&self) -> Option<&RawArg> {
+        self.raw_args.iter().find(|a| a.value.is_some())
+    }
+}
+
+/// Returns a warning if the raw arguments reach the command-line length limits of common OSes.
+fn argv_spill_warning(raw_args: &[RawArg]) -> Option<String> {
+    // Linux limits the length of a single argument to 128 KiB (`MAX_ARG_STRLEN`),
+    // and Windows limits the length of the whole command line to 32767 characters.
+    const MAX_ARG_LEN: usize = 128 * 1024 - 1;
+    const MAX_TOTAL_LEN: usize = if cfg!(windows) {
+        32767
+    } else {
+        2 * 1024 * 1024
+    };
+
+    let values = raw_args.iter().filter_map(|a| a.value.as_ref());
+    let total_len = values.clone().map(|v| v.len() + 1).sum::<usize>();
+    let longest = values.map(|v| v.len()).max().unwrap_or(0);
+    if total_len < MAX_TOTAL_LEN && longest < MAX_ARG_LEN {
+        return None;
+    }
+    Some(format!(
+        "the command line is very long ({} arguments, {total_len} bytes) and may have been truncated or split; \
+         consider passing the arguments via a file (e.g., '@args.txt')",
+        raw_args.len().saturating_sub(1)
+    ))
+}
+
+/// Text shown instead of sensitive values.
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// Returns the choice that matches `value` ignoring ASCII case.
+pub(crate) fn canonical_choice(choices: &[&'static str], value: &str) -> Option<&'static str> {
+    choices
+        .iter()
+        .copied()
+        .find(|choice| choice.eq_ignore_ascii_case(value))
+}
+
+/// Returns `value`, or a placeholder showing [`REDACTED`] if `sensitive` is `true`.
+pub(crate) fn redact(value: &dyn std::fmt::Debug, sensitive: bool) -> &dyn std::fmt::Debug {
+    struct Redacted;
+
+    impl std::fmt::Debug for Redacted {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(REDACTED)
+        }
+    }
+
+    if sensitive { &Redacted } else { value }
+}
+
+/// Splits `s` into arguments like POSIX shells (without expansions).
+pub(crate) fn split_command_line(s: &str) -> Result<Vec<String>, &'static str> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                args.extend(current.take());
+            }
+            '\\' => {
+                let c = chars.next().ok_or("trailing backslash")?;
+                current.get_or_insert_with(String::new).push(c);
+            }
+            '\'' => {
+                let current = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated single quote")? {
+                        '\'' => break,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let current = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated double quote")? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or("unterminated double quote")? {
+                            c @ ('"' | '\\' | '$' | '`') => current.push(c),
+                            c => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                        },
+                        c => current.push(c),
+                    }
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
     }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Joins `args` into a command line that is split back into the same arguments by `CommandLineToArgvW`
+/// (and the Microsoft C runtime) on Windows.
+pub(crate) fn join_windows_command_line<I>(args: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut line = String::new();
+    for arg in args {
+        let arg = arg.as_ref();
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\x0B', '"']) {
+            line.push_str(arg);
+            continue;
+        }
+
+        // Backslashes are literal unless they precede a double quote (including the closing one),
+        // in which case they must be doubled.
+        line.push('"');
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => backslashes += 1,
+                '"' => {
+                    line.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+                    line.push('"');
+                    backslashes = 0;
+                }
+                c => {
+                    line.extend(std::iter::repeat('\\').take(backslashes));
+                    line.push(c);
+                    backslashes = 0;
+                }
+            }
+        }
+        line.extend(std::iter::repeat('\\').take(backslashes * 2));
+        line.push('"');
+    }
+    line
+}
+
+/// Time spent in each parsing phase (see [`RawArgs::timings()`]).
+#[cfg(feature = "timings")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timings {
+    /// Time spent in taking arguments, options, flags and subcommands (including environment variable lookups).
+    pub take: std::time::Duration,
+
+    /// Time spent in looking up environment variables.
+    pub env: std::time::Duration,
+
+    /// Time spent in building help texts.
+    pub help: std::time::Duration,
+}
+
+/// Guard returned by [`RawArgs::scope()`] and [`Cmd::scope()`].
+///
+/// This dereferences to the [`RawArgs`] instance, and restores the previous scope when dropped.
+#[derive(Debug)]
+pub struct Scope<'a> {
+    args: &'a mut RawArgs,
+    prev: Range<usize>,
+}
+
+impl Deref for Scope<'_> {
+    type Target = RawArgs;
+
+    fn deref(&self) -> &Self::Target {
+        self.args
+    }
+}
+
+impl DerefMut for Scope<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.args
+    }
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        self.args.scope = self.prev.clone();
+    }
+}
+
+/// Where [`RawArgs::prepend_args()`] inserts the given arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgsPlacement {
+    /// Inserts the arguments before the user-supplied ones (right after the program name).
+    BeforeUserArgs,
+
+    /// Inserts the arguments after the user-supplied ones (but before the first `--`).
+    AfterUserArgs,
+}
+
+/// Parsing state of [`RawArgs`] saved by [`RawArgs::snapshot()`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    metadata: Metadata,
+    raw_args: Vec<RawArg>,
+    log: Vec<Taken>,
+    warnings: Vec<String>,
+    detached_values: Vec<DetachedValue>,
+    repeated_opts: Vec<Opt>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RawArg {
     pub value: Option<String>,
+
+    /// Abbreviated long name used when this argument was taken (see [`Metadata::allow_abbrev`]).
+    pub abbreviation: Option<String>,
+
+    /// Label of the source that injected this argument (see [`RawArgs::push_injected()`]).
+    pub source: Option<String>,
+
+    /// Subcommand name matched by an external subcommand (see [`CmdSpec::external`]).
+    pub external_name: Option<String>,
+}
+
+/// Returns `Some(true)` if `name` (without the leading `/`) is one of `long_names`,
+/// `Some(false)` if it is one of `short_names`, and `None` otherwise.
+pub(crate) fn match_slash_name<I, J>(
+    name: &str,
+    mut long_names: I,
+    mut short_names: J,
+) -> Option<bool>
+where
+    I: Iterator<Item = &'static str>,
+    J: Iterator<Item = char>,
+{
+    let mut chars = name.chars();
+    if long_names.any(|n| n == name) {
+        Some(true)
+    } else if let (Some(c), None) = (chars.next(), chars.next()) {
+        short_names.any(|s| s == c).then_some(false)
+    } else {
+        None
+    }
+}
+
+/// Converts a name (e.g., `db-port`) into the corresponding environment variable name (e.g., `DB_PORT`).
+pub(crate) fn env_case(name: &str) -> String {
+    name.to_ascii_uppercase().replace('-', "_")
+}
+
+/// Returns `env`, or the name derived from `name` and [`Metadata::env_prefix`] if `env_auto` is `true`.
+pub(crate) fn env_name(
+    env: Option<&'static str>,
+    env_auto: bool,
+    name: &str,
+    metadata: &Metadata,
+) -> Option<Cow<'static, str>> {
+    match env {
+        Some(env) => Some(Cow::Borrowed(env)),
+        None if env_auto && !name.is_empty() => Some(Cow::Owned(format!(
+            "{}{}",
+            metadata.env_prefix,
+            env_case(name)
+        ))),
+        None => None,
+    }
+}
+
+/// Returns `true` if `name` is a reference resolved by [`Metadata::env_resolver`].
+pub(crate) fn is_env_reference(name: &str) -> bool {
+    name.contains(':')
+}
+
+/// Returns `true` if `arg` looks like a negative number (e.g., `-5` or `-3.14`).
+pub(crate) fn is_negative_number(arg: &str) -> bool {
+    arg.strip_prefix('-').is_some_and(|n| {
+        n.starts_with(|c: char| c.is_ascii_digit() || c == '.') && n.parse::<f64>().is_ok()
+    })
+}
+
+/// Strips one of `names` from `arg` (a long name argument without the leading `--`).
+///
+/// The first element of the result is the rest of `arg`, which is either empty or starts with `=`.
+/// If `rivals` is `Some(_)`, an abbreviated name is also accepted unless it is a prefix of one of `rivals`.
+/// In that case, the second element of the result is the abbreviated name.
+pub(crate) fn strip_long_name<'a, I>(
+    arg: &'a str,
+    mut names: I,
+    rivals: Option<&[&str]>,
+) -> Option<(&'a str, Option<&'a str>)>
+where
+    I: Iterator<Item = &'static str> + Clone,
+{
+    let (name, rest) = arg.split_at(arg.find('=').unwrap_or(arg.len()));
+    if names.clone().any(|n| n == name) {
+        return Some((rest, None));
+    }
+
+    let rivals = rivals?;
+    if name.is_empty() || rivals.iter().any(|n| n.starts_with(name)) {
+        return None;
+    }
+    names
+        .any(|n| n.starts_with(name))
+        .then_some((rest, Some(name)))
+}
+
+/// An option value that was given as a separate argument (e.g., `--output out.txt`).
+#[derive(Debug, Clone)]
+pub struct DetachedValue {
+    /// Index of the raw argument (i.e., the position a positional argument would have taken).
+    pub index: usize,
+    pub opt_name: String,
+    pub opt_ty: &'static str,
+    pub value: String,
 }
 
 /// Metadata of [`RawArgs`].
@@ -126,9 +1476,186 @@ pub struct Metadata {
     /// Application description (e.g., `env!("CARGO_PKG_DESCRIPTION")`).
     pub app_description: &'static str,
 
+    /// Application version (e.g., `env!("CARGO_PKG_VERSION")`).
+    ///
+    /// This is used by [`FlagSpec::take_version()`](crate::FlagSpec::take_version).
+    pub app_version: &'static str,
+
+    /// Application authors (e.g., `env!("CARGO_PKG_AUTHORS")`).
+    ///
+    /// Multiple authors can be separated by `:` as in `CARGO_PKG_AUTHORS`.
+    /// This is shown only in the full help text.
+    pub app_authors: &'static str,
+
+    /// Application homepage (e.g., `env!("CARGO_PKG_HOMEPAGE")`).
+    ///
+    /// This is shown only in the full help text.
+    pub app_homepage: &'static str,
+
+    /// Application license (e.g., `env!("CARGO_PKG_LICENSE")`).
+    ///
+    /// This is shown only in the full help text.
+    pub app_license: &'static str,
+
+    /// Section number of the man page built by [`RawArgs::build_man_page()`] (default: `"1"`).
+    pub man_section: &'static str,
+
+    /// Date shown in the footer of the man page built by [`RawArgs::build_man_page()`] (default: `""`).
+    ///
+    /// The date is not derived from the system clock, so that the page is reproducible (e.g., `"2025-01-31"`).
+    pub man_date: &'static str,
+
+    /// Entries of the SEE ALSO section of the man page built by [`RawArgs::build_man_page()`] (default: `&[]`).
+    ///
+    /// Each entry is a reference to another man page (e.g., `"git(1)"`).
+    /// The pages of the subcommands having [`CmdSpec::man_page`] are listed before these entries.
+    pub man_see_also: &'static [&'static str],
+
+    /// If `true`, long names of options and flags can be abbreviated (e.g., `--verb` for `--verbose`)
+    /// as long as the abbreviation is unambiguous (default: `false`).
+    ///
+    /// As specs are taken one by one, an abbreviation is resolved against the specs taken so far,
+    /// and [`RawArgs::finish()`] reports an error if it turns out to be ambiguous among all taken specs.
+    pub allow_abbrev: bool,
+
+    /// If `true`, arguments that look like negative numbers (e.g., `-5` or `-3.14`) are not considered
+    /// to be flags or options, and are left for positional arguments (default: `false`).
+    ///
+    /// Note that this does not affect option values given as separate arguments (e.g., `--offset -5`).
+    pub negative_numbers_as_args: bool,
+
+    /// If `true`, options and flags can also be specified in the Windows style (default: `false`).
+    ///
+    /// In this style, `/name` (or `/c` for a short name) corresponds to `--name` (`-c`),
+    /// and `/name:value` corresponds to `--name=value`.
+    /// Note that a positional argument starting with `/` (e.g., an absolute path) is taken by
+    /// a flag or an option if it happens to match the name.
+    pub slash_syntax: bool,
+
+    /// If `true`, a cluster of short flags may end with a short option like `getopt` (default: `false`).
+    ///
+    /// For example, `tar -xzf file.tar` and `tar -xzffile.tar` are parsed as `-x -z -f file.tar`.
+    /// When an option is taken, the characters preceding its short name in a cluster must satisfy
+    /// [`Metadata::is_valid_flag_chars`], and they are left to be taken as flags.
+    /// Note that this introduces ambiguity with concatenated values (e.g., `-ofoo` can be taken as `-o -f oo`),
+    /// so options whose values are concatenated should be taken before the others.
+    pub short_option_clusters: bool,
+
+    /// If `true`, options and flags are only searched for before the first positional argument
+    /// like POSIX `getopt` (default: `false`).
+    ///
+    /// This prevents arguments intended for another program from being taken
+    /// (e.g., `--flag-for-target` in `mytool build target --flag-for-target`).
+    /// The search stops at the first unconsumed argument that does not look like an option,
+    /// including an option value given as a separate argument (e.g., `out.txt` in `--output out.txt`).
+    /// Such a value is still taken by its option, after which the options and flags following it can be found,
+    /// so options should be taken before flags.
+    pub posix_ordering: bool,
+
+    /// Token that separates options from the trailing arguments (default: `"--"`).
+    ///
+    /// Some CLIs use another token (e.g., `";;"` for find-style expressions).
+    /// This is used by [`RawArgs::take_rest()`], [`RawArgs::sections()`], [`Metadata::posix_ordering`]
+    /// and [`RawArgs::terminator_index()`].
+    pub terminator: &'static str,
+
+    /// If `true`, the application is running in dry-run mode (default: `false`).
+    ///
+    /// This is set by [`take_standard()`](crate::take_standard) when [`DRY_RUN_FLAG`](crate::DRY_RUN_FLAG) is present,
+    /// and application-level errors made by [`Error::other()`] are prefixed with `(dry-run)`.
+    pub dry_run: bool,
+
+    /// If `true`, environment variables are not read when taking options and flags (default: `false`).
+    ///
+    /// This is set by [`lint()`](crate::lint) so that the result does not depend on the environment.
+    pub ignore_env: bool,
+
+    /// If `true`, environment variables take precedence over the command line (default: `false`).
+    ///
+    /// By default, an option or a flag given on the command line wins over its environment variable,
+    /// which is only consulted when the option or flag is absent.
+    /// If this is `true` and the environment variable is set, the occurrences on the command line are
+    /// consumed but ignored (e.g., for container images where the environment is the source of truth).
+    /// Note that a flag whose environment variable has one of [`FlagSpec::falsey_env_values`](crate::FlagSpec::falsey_env_values) is not present
+    /// in this case even if it is given on the command line.
+    pub env_overrides_cli: bool,
+
+    /// Prefix of the environment variable names derived by [`OptSpec::env_auto()`] and
+    /// [`FlagSpec::env_auto()`](crate::FlagSpec::env_auto) (default: `""`).
+    ///
+    /// For example, if this is `"MYAPP_"`, `noargs::opt("port").env_auto()` reads `MYAPP_PORT`.
+    pub env_prefix: &'static str,
+
+    /// If `true`, help texts built by [`RawArgs::finish()`] are cached within the process (default: `false`).
+    ///
+    /// The cache is keyed by the whole metadata, the specifications taken so far (and the present subcommands) and
+    /// whether the output is styled for a terminal.
+    /// This is useful for applications that generate help many times in a long-running process.
+    /// If the help text depends on values computed at runtime (e.g., [`OptSpec::default_with()`]),
+    /// call [`clear_help_cache()`](crate::clear_help_cache) to invalidate the cache when they change.
+    pub cache_help: bool,
+
+    /// If `true`, the [`Debug`](std::fmt::Debug) implementation of [`Error`] shows the conventional structural
+    /// representation instead of the user-facing text (default: `false`).
+    ///
+    /// The default is kept for backward compatibility as `fn main() -> noargs::Result<()>` reports errors via `Debug`.
+    /// Use [`Error::display()`] to get the user-facing text regardless of this setting.
+    pub structured_debug: bool,
+
+    /// If `true`, a warning is recorded when the same option or flag specification is taken more than once,
+    /// or when different specifications share a long name or a short name (default: `false`).
+    ///
+    /// Taking an identical specification twice (e.g., by copy-and-paste) silently consumes another occurrence
+    /// or yields a non-present result, which often hides bugs.
+    /// Specifications intended to be taken repeatedly should be marked with [`OptSpec::repeatable()`] or
+    /// [`FlagSpec::repeatable()`](crate::FlagSpec::repeatable) ([`OptSpec::take_all()`] does this automatically).
+    ///
+    /// Similarly, if two specifications share a name (e.g., `-o` for both `--output` and `--overwrite`),
+    /// the later one silently loses. Such conflicts are reported with both names
+    /// (e.g., "'-o' is defined by both '--output' and '--overwrite'").
+    /// The warnings can be retrieved via [`RawArgs::warnings()`].
+    pub strict_takes: bool,
+
+    /// Whether help texts are styled (default: `None`).
+    ///
+    /// If `None`, help texts are styled if the standard output is a terminal
+    /// (or [`Output::is_stdout_terminal()`] returns `true` for [`RawArgs::finish_with()`]).
+    /// [`take_standard()`](crate::take_standard) sets this to `Some(false)` when [`NO_COLOR_FLAG`](crate::NO_COLOR_FLAG) is present.
+    pub style_help: Option<bool>,
+
+    /// Whether error texts are styled (default: `None`).
+    ///
+    /// If `None`, error texts are styled if the standard error is a terminal
+    /// (or [`Output::is_stderr_terminal()`] returns `true` for [`Output::report()`]).
+    /// As help and errors are written to different streams, they are styled independently
+    /// (e.g., with `2> err.log`, help is styled but errors are not).
+    /// [`take_standard()`](crate::take_standard) sets this to `Some(false)` when [`NO_COLOR_FLAG`](crate::NO_COLOR_FLAG) is present.
+    pub style_errors: Option<bool>,
+
+    /// Example command lines (without the application name) shown in the "Example" section of the help text
+    /// (default: `&[]`).
+    ///
+    /// Each example is written as a shell command line (e.g., `"run --port 8080 'my file'"`).
+    /// In the help text of a subcommand, only the examples containing the subcommand path are shown.
+    /// Use [`testing::check_examples()`](crate::testing::check_examples) in tests to verify that
+    /// these examples (and any other known-good invocations) still parse as the CLI evolves.
+    pub examples: &'static [&'static str],
+
     /// Flag name for help (default: `Some("help")`).
     pub help_flag_name: Option<&'static str>,
 
+    /// Flag name for version (default: `Some("version")`).
+    ///
+    /// [`FlagSpec::take_version()`](crate::FlagSpec::take_version) uses this as the long name of the flag,
+    /// and `None` disables the version flag.
+    /// If set and a version is available, the full help text shows it in the footer.
+    pub version_flag_name: Option<&'static str>,
+
+    /// Text shown at the bottom of the help text in both summary and full modes (default: `""`).
+    ///
+    /// This is useful for pointers to further documentation (e.g., `"See https://example.com/docs for more"`).
+    pub after_help: &'static str,
+
     /// When enabled, the following help mode behaviors apply:
     ///
     /// - [`RawArgs::finish()`] will return `Ok(Some(help_text))` if successful
@@ -138,16 +1665,24 @@ pub struct Metadata {
     /// If `true`, a full help text will be displayed.
     pub full_help: bool,
 
+    /// Maximum number of lines of the summary help text (`-h`) (default: `None`).
+    ///
+    /// If set (e.g., to the terminal height), options that do not fit are omitted from the summary
+    /// and a line such as `... 12 more options (run '--help' to see all)` is shown instead,
+    /// so that the quick reference of an application with many options stays within one screen.
+    /// The full help text (`--help`) is not affected.
+    pub summary_max_lines: Option<usize>,
+
     /// Predicate function to determine if a string contains only valid flag characters.
     ///
     /// This function is used when parsing short flags to distinguish between:
     /// - Multiple flags (e.g., `-abc` where each character is a flag)
     /// - Options with concatenated values (e.g., `-khello` where 'k' is an option and "hello" is its value)
     ///
-    /// The default implementation accepts only ASCII alphabetic characters, which prevents
-    /// ambiguity in parsing. For example, with `-khello world`, the presence of space and
-    /// non-alphabetic characters indicates this is an option with a concatenated value rather
-    /// than multiple flags.
+    /// The default implementation accepts only ASCII alphanumeric characters (digits are included for
+    /// numeric short flags such as `ls -1`), which prevents ambiguity in parsing.
+    /// For example, with `-khello world`, the presence of space and non-alphanumeric characters
+    /// indicates this is an option with a concatenated value rather than multiple flags.
     ///
     /// # Example: Only accept flags actually defined by the app
     ///
@@ -172,6 +1707,28 @@ pub struct Metadata {
     /// let debug_flag = flag("debug").short('d').take(&mut args);
     /// ```
     pub is_valid_flag_chars: fn(&str) -> bool,
+
+    /// Resolver of environment variable references (default: `None`).
+    ///
+    /// An environment variable name containing `:` (e.g., `VAULT:secret/path#key` in `noargs::opt("token").env(...)`)
+    /// is considered as a reference, and its value is resolved by calling this function instead of reading
+    /// the environment variable (if this is `None`, references are never resolved).
+    /// Values resolved in this way are considered sensitive (see [`Opt::is_sensitive()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app"].iter().map(|a| a.to_string()));
+    /// args.metadata_mut().env_resolver = Some(|reference| {
+    ///     // Look up a secret store here.
+    ///     (reference == "VAULT:secret/db#password").then(|| "hunter2".to_owned())
+    /// });
+    ///
+    /// let password = noargs::opt("password").env("VAULT:secret/db#password").take(&mut args);
+    /// assert_eq!(password.value(), "hunter2");
+    /// assert!(password.is_sensitive());
+    /// ```
+    pub env_resolver: Option<fn(&str) -> Option<String>>,
 }
 
 impl Default for Metadata {
@@ -179,10 +1736,37 @@ impl Default for Metadata {
         Self {
             app_name: "<APP_NAME>",
             app_description: "",
+            app_version: "",
+            app_authors: "",
+            app_homepage: "",
+            app_license: "",
+            man_section: "1",
+            man_date: "",
+            man_see_also: &[],
+            allow_abbrev: false,
+            negative_numbers_as_args: false,
+            slash_syntax: false,
+            short_option_clusters: false,
+            posix_ordering: false,
+            terminator: "--",
+            dry_run: false,
+            ignore_env: false,
+            env_overrides_cli: false,
+            env_prefix: "",
+            cache_help: false,
+            structured_debug: false,
+            examples: &[],
+            style_help: None,
+            style_errors: None,
+            strict_takes: false,
             help_flag_name: Some("help"),
+            version_flag_name: Some("version"),
+            after_help: "",
             help_mode: false,
             full_help: false,
-            is_valid_flag_chars: |chars| chars.chars().all(|c| c.is_ascii_alphabetic()),
+            summary_max_lines: None,
+            is_valid_flag_chars: |chars| chars.chars().all(|c| c.is_ascii_alphanumeric()),
+            env_resolver: None,
         }
     }
 }
@@ -190,7 +1774,7 @@ impl Default for Metadata {
 // [NOTE]
 // PartialEq, Eq, Hash are manually implemented to avoid
 // the `unpredictable_function_pointer_comparisons` warning.
-// (`is_valid_flag_chars` should not be compared)
+// (`is_valid_flag_chars` and `env_resolver` should not be compared)
 //
 // TODO: Remove `is_valid_flag_chars` from `Metadata`
 
@@ -198,9 +1782,35 @@ impl PartialEq for Metadata {
     fn eq(&self, other: &Self) -> bool {
         self.app_name == other.app_name
             && self.app_description == other.app_description
+            && self.app_version == other.app_version
+            && self.app_authors == other.app_authors
+            && self.app_homepage == other.app_homepage
+            && self.app_license == other.app_license
+            && self.man_section == other.man_section
+            && self.man_date == other.man_date
+            && self.man_see_also == other.man_see_also
+            && self.allow_abbrev == other.allow_abbrev
+            && self.negative_numbers_as_args == other.negative_numbers_as_args
+            && self.slash_syntax == other.slash_syntax
+            && self.short_option_clusters == other.short_option_clusters
+            && self.posix_ordering == other.posix_ordering
+            && self.terminator == other.terminator
+            && self.dry_run == other.dry_run
+            && self.ignore_env == other.ignore_env
+            && self.env_overrides_cli == other.env_overrides_cli
+            && self.env_prefix == other.env_prefix
+            && self.cache_help == other.cache_help
+            && self.structured_debug == other.structured_debug
+            && self.examples == other.examples
+            && self.style_help == other.style_help
+            && self.style_errors == other.style_errors
+            && self.strict_takes == other.strict_takes
             && self.help_flag_name == other.help_flag_name
+            && self.version_flag_name == other.version_flag_name
+            && self.after_help == other.after_help
             && self.help_mode == other.help_mode
             && self.full_help == other.full_help
+            && self.summary_max_lines == other.summary_max_lines
     }
 }
 
@@ -210,9 +1820,35 @@ impl std::hash::Hash for Metadata {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.app_name.hash(state);
         self.app_description.hash(state);
+        self.app_version.hash(state);
+        self.app_authors.hash(state);
+        self.app_homepage.hash(state);
+        self.app_license.hash(state);
+        self.man_section.hash(state);
+        self.man_date.hash(state);
+        self.man_see_also.hash(state);
+        self.allow_abbrev.hash(state);
+        self.negative_numbers_as_args.hash(state);
+        self.slash_syntax.hash(state);
+        self.short_option_clusters.hash(state);
+        self.posix_ordering.hash(state);
+        self.terminator.hash(state);
+        self.dry_run.hash(state);
+        self.ignore_env.hash(state);
+        self.env_overrides_cli.hash(state);
+        self.env_prefix.hash(state);
+        self.cache_help.hash(state);
+        self.structured_debug.hash(state);
+        self.examples.hash(state);
+        self.style_help.hash(state);
+        self.style_errors.hash(state);
+        self.strict_takes.hash(state);
         self.help_flag_name.hash(state);
+        self.version_flag_name.hash(state);
+        self.after_help.hash(state);
         self.help_mode.hash(state);
         self.full_help.hash(state);
+        self.summary_max_lines.hash(state);
     }
 }
 
@@ -222,30 +1858,104 @@ pub enum Taken {
     Opt(Opt),
     Flag(Flag),
     Cmd(Cmd),
+    Sections(&'static [&'static str]),
+    Group(ArgGroup),
 }
 
 impl Taken {
-    pub fn name(&self) -> &'static str {
+    /// Returns `true` if this entry should not be shown in the help text.
+    ///
+    /// A hidden subcommand is still shown if it is present (e.g., `myapp hidden-cmd --help`).
+    pub fn is_hidden(&self) -> bool {
         match self {
-            Taken::Arg(arg) => arg.spec().name,
-            Taken::Opt(opt) => opt.spec().name,
-            Taken::Flag(flag) => flag.spec().name,
-            Taken::Cmd(cmd) => cmd.spec().name,
+            Taken::Arg(arg) => arg.spec().hidden,
+            Taken::Opt(opt) => opt.spec().hidden,
+            Taken::Flag(flag) => flag.spec().hidden,
+            Taken::Cmd(cmd) => cmd.spec().hidden && !cmd.is_present(),
+            Taken::Sections(_) | Taken::Group(_) => false,
         }
     }
 
     pub fn example(&self) -> Option<Cow<'static, str>> {
         match self {
+            Taken::Arg(arg) if arg.spec().sensitive => arg.spec().example.map(|_| REDACTED.into()),
             Taken::Arg(arg) => arg.spec().example.map(Self::quote_if_need),
-            Taken::Opt(opt) => opt
-                .spec()
-                .example
-                .map(|v| Cow::Owned(format!("--{} {}", opt.spec().name, Self::quote_if_need(v)))),
+            Taken::Opt(opt) => opt.spec().example.map(|v| {
+                let name = opt.spec().display_name();
+                if opt.spec().sensitive {
+                    return Cow::Owned(format!("{name} {REDACTED}"));
+                }
+                if opt.spec().num_values > 1 {
+                    // Multiple values are separated by whitespace in the example.
+                    return Cow::Owned(format!("{name} {v}"));
+                }
+                Cow::Owned(format!("{name} {}", Self::quote_if_need(v)))
+            }),
             Taken::Cmd(cmd) if cmd.is_present() => Some(Cow::Borrowed(cmd.spec().name)),
             _ => None,
         }
     }
 
+    pub fn has_example(&self) -> bool {
+        match self {
+            Taken::Arg(arg) => arg.spec().example.is_some(),
+            Taken::Opt(opt) => opt.spec().example.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Same as [`Taken::example()`], but returns the raw arguments without quoting or redaction.
+    pub fn example_args(&self) -> Vec<String> {
+        match self {
+            Taken::Arg(arg) => arg.spec().example.into_iter().map(str::to_owned).collect(),
+            Taken::Opt(opt) => opt.spec().example.map_or_else(Vec::new, |v| {
+                let values: Vec<&str> = if opt.spec().num_values > 1 {
+                    v.split_whitespace().collect()
+                } else {
+                    vec![v]
+                };
+                std::iter::once(opt.spec().display_name())
+                    .chain(values.into_iter().map(str::to_owned))
+                    .collect()
+            }),
+            Taken::Cmd(cmd) if cmd.is_present() => vec![cmd.spec().name.to_owned()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the display name followed by all the names (e.g., `--output`, `-o`) of an option or a flag.
+    fn defined_names(&self) -> Option<Vec<String>> {
+        let (display_name, long_names, short_names): (_, Vec<_>, Vec<_>) = match self {
+            Taken::Opt(opt) => {
+                let spec = opt.spec();
+                let long_names = spec.long_names().collect();
+                (
+                    spec.display_name(),
+                    long_names,
+                    spec.short_names().collect(),
+                )
+            }
+            Taken::Flag(flag) => {
+                let spec = flag.spec();
+                let long_names = spec.long_names().collect();
+                (
+                    spec.display_name(),
+                    long_names,
+                    spec.short_names().collect(),
+                )
+            }
+            _ => return None,
+        };
+        let names = long_names.iter().map(|name| format!("--{name}"));
+        let shorts = short_names.iter().map(|c| format!("-{c}"));
+        Some(
+            std::iter::once(display_name)
+                .chain(names)
+                .chain(shorts)
+                .collect(),
+        )
+    }
+
     fn quote_if_need(s: &'static str) -> Cow<'static, str> {
         if s.contains('"') && !s.contains('\'') {
             Cow::Owned(format!("'{}'", s))
@@ -256,3 +1966,121 @@ impl Taken {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_command_line() {
+        let mut args = RawArgs::from_command_line(r#"run --name "John Doe" -- 'a b'"#)
+            .expect("valid command line");
+        assert!(crate::cmd("run").take(&mut args).is_present());
+        assert_eq!(crate::opt("name").take(&mut args).value(), "John Doe");
+        assert_eq!(args.take_rest(), ["a b"]);
+
+        let e = RawArgs::from_command_line("run 'foo").expect_err("invalid command line");
+        assert_eq!(
+            e.to_string(false),
+            "failed to parse command line: unterminated single quote"
+        );
+    }
+
+    #[test]
+    fn detect_help_early() {
+        let mut args = test_args(&["test", "run", "--port", "foo", "-h"]);
+        assert!(args.detect_help_early());
+        if crate::cmd("run").take(&mut args).is_present() {
+            let port: Result<u16, _> = crate::opt("port")
+                .default("80")
+                .take(&mut args)
+                .then(|o| o.value().parse());
+            assert_eq!(port.ok(), Some(80));
+            crate::HELP_FLAG.take_help(&mut args);
+        }
+        let help = args.finish().expect("ok").expect("help");
+        assert!(help.starts_with("Usage: <APP_NAME> run [OPTIONS]"));
+        assert!(help.contains("--port <VALUE> [default: 80]"));
+
+        let mut args = test_args(&["test", "run", "--", "--help"]);
+        assert!(!args.detect_help_early());
+        assert!(!args.metadata().help_mode);
+
+        let mut args = test_args(&["test", "-h", "--usage"]);
+        args.metadata_mut().help_flag_name = Some("usage");
+        assert!(args.detect_help_early());
+        assert!(args.metadata().full_help);
+
+        let mut args = test_args(&["test", "-h"]);
+        args.metadata_mut().help_flag_name = Some("usage");
+        assert!(!args.detect_help_early());
+
+        let mut args = test_args(&["test", "--help"]);
+        args.metadata_mut().help_flag_name = None;
+        assert!(!args.detect_help_early());
+        assert!(!args.metadata().help_mode);
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut args = test_args(&["test", "build", "--release"]);
+        let snapshot = args.snapshot();
+        assert_eq!(crate::arg("<FILE>").take(&mut args).value(), "build");
+        assert!(crate::flag("release").take(&mut args).is_present());
+
+        args.restore(snapshot);
+        assert!(crate::cmd("build").take(&mut args).is_present());
+        assert!(crate::flag("release").take(&mut args).is_present());
+        args.metadata_mut().help_mode = true;
+        let help = args.finish().expect("ok").expect("help");
+        assert!(help.contains("build"));
+        assert!(!help.contains("<FILE>"));
+    }
+
+    #[test]
+    fn global_flag() {
+        let mut args = test_args(&["test", "run", "--verbose", "--output=a", "-h"]);
+        args.metadata_mut().posix_ordering = true;
+        let verbose = crate::flag("verbose")
+            .doc("Verbose")
+            .global()
+            .take(&mut args);
+        let output = crate::opt("output").global().take(&mut args);
+        crate::HELP_FLAG.global().take_help(&mut args);
+        assert!(verbose.is_present());
+        assert_eq!(output.value(), "a");
+
+        assert!(crate::cmd("run").take(&mut args).is_present());
+        crate::flag("force").doc("Force").take(&mut args);
+        let help = args.finish().expect("ok").expect("help");
+        assert!(help.contains("--verbose"));
+        assert!(help.contains("--force"));
+    }
+
+    #[test]
+    fn foreign_cmd_scope() {
+        let mut args = test_args(&["test", "run", "--force"]);
+        let run = crate::cmd("run").take(&mut args);
+        assert!(run.scope(&mut args).is_ok());
+
+        // Structurally identical, but taken from another instance.
+        let mut other = args.reparse(["test", "run", "--force"].iter().map(|a| a.to_string()));
+        crate::cmd("run").take(&mut other);
+        let e = run.scope(&mut other).map(|_| ()).expect_err("foreign");
+        assert!(
+            e.to_string(false)
+                .contains("subcommand 'run' was not taken from these arguments")
+        );
+
+        // Rolled back.
+        let mut args = test_args(&["test", "stop"]);
+        let snapshot = args.snapshot();
+        let stop = crate::cmd("stop").take(&mut args);
+        args.restore(snapshot);
+        assert!(stop.scope(&mut args).is_err());
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
@@ -1,4 +1,7 @@
-use crate::args::RawArgs;
+use crate::{
+    Error,
+    args::{RawArgs, Scope, Taken},
+};
 
 /// Specification for [`Cmd`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -8,11 +11,61 @@ pub struct CmdSpec {
 
     /// Documentation.
     pub doc: &'static str,
+
+    /// Version of this subcommand (e.g., for plugins versioned independently of the application).
+    ///
+    /// See also: [`RawArgs::cmd_version()`]
+    pub version: Option<&'static str>,
+
+    /// If `true`, an unambiguous prefix of [`CmdSpec::name`] also matches this subcommand (e.g., `st` for `status`).
+    ///
+    /// The prefix is not accepted if it is also a prefix of another subcommand taken before this one at the same level.
+    /// In that case, [`RawArgs::finish()`] reports an ambiguity error listing the candidates.
+    /// Note that subcommands taken after this one are unknown at this point,
+    /// so the first matching subcommand wins if multiple subcommands sharing the prefix enable this.
+    pub prefix_match: bool,
+
+    /// If `true`, [`CmdSpec::name`] is matched ignoring ASCII case (e.g., `START` for `start`).
+    pub case_insensitive: bool,
+
+    /// If `true`, any unconsumed argument not starting with `-` matches this subcommand (e.g., for git-style external subcommands).
+    ///
+    /// In that case, the matched argument can be retrieved via [`Cmd::name()`],
+    /// and the arguments following it can be retrieved via [`RawArgs::take_rest()`].
+    /// This subcommand should be taken after all the other subcommands at the same level.
+    pub external: bool,
+
+    /// Stable identifier of this subcommand (see [`CmdSpec::id()`]).
+    pub stable_id: Option<&'static str>,
+
+    /// If `true`, this subcommand is not shown in the help text (but is parsed as usual).
+    pub hidden: bool,
+
+    /// If set, this subcommand is deprecated with the given note (e.g., `"use --new-name instead"`).
+    ///
+    /// A deprecated subcommand is parsed as usual, but a warning is recorded when it is given on the command line
+    /// (see [`RawArgs::warnings()`]), and the note is shown in the help text.
+    pub deprecated: Option<&'static str>,
+
+    /// If `true`, [`man_pages()`](crate::man_pages) emits a separate man page for this subcommand (e.g., `myapp-start.1`),
+    /// and the man page of the parent command refers to it in its SEE ALSO section.
+    pub man_page: bool,
 }
 
 impl CmdSpec {
     /// The default specification.
-    pub const DEFAULT: Self = Self { name: "", doc: "" };
+    pub const DEFAULT: Self = Self {
+        name: "",
+        doc: "",
+        version: None,
+        prefix_match: false,
+        case_insensitive: false,
+        external: false,
+        stable_id: None,
+        hidden: false,
+        deprecated: None,
+        man_page: false,
+    };
 
     /// Makes an [`CmdSpec`] instance with a specified name (equivalent to `noargs::cmd(name)`).
     pub const fn new(name: &'static str) -> Self {
@@ -28,17 +81,125 @@ impl CmdSpec {
         self
     }
 
+    /// Updates the value of [`CmdSpec::version`].
+    pub const fn version(mut self, version: &'static str) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets [`CmdSpec::prefix_match`] to `true`.
+    pub const fn prefix_match(mut self) -> Self {
+        self.prefix_match = true;
+        self
+    }
+
+    /// Sets [`CmdSpec::case_insensitive`] to `true`.
+    pub const fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Sets [`CmdSpec::external`] to `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["git", "lfs", "pull", "--all"].iter().map(|a| a.to_string()));
+    /// noargs::cmd("clone").take(&mut args);
+    /// let cmd = noargs::any_cmd().take(&mut args);
+    ///
+    /// assert_eq!(cmd.name(&args), "lfs");
+    /// assert_eq!(args.take_rest(), ["pull", "--all"]);
+    /// ```
+    pub const fn external(mut self) -> Self {
+        self.external = true;
+        self
+    }
+
+    /// Updates the value of [`CmdSpec::stable_id`].
+    pub const fn stable_id(mut self, id: &'static str) -> Self {
+        self.stable_id = Some(id);
+        self
+    }
+
+    /// Sets [`CmdSpec::man_page`] to `true`.
+    pub const fn man_page(mut self) -> Self {
+        self.man_page = true;
+        self
+    }
+
+    /// Sets [`CmdSpec::hidden`] to `true`.
+    pub const fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Updates the value of [`CmdSpec::deprecated`].
+    pub const fn deprecated(mut self, note: &'static str) -> Self {
+        self.deprecated = Some(note);
+        self
+    }
+
+    /// Returns the identifier of this subcommand, which is [`CmdSpec::stable_id`] if set, or [`CmdSpec::name`] otherwise
+    /// (see [`OptSpec::id()`](crate::OptSpec::id)).
+    pub const fn id(&self) -> &'static str {
+        match self.stable_id {
+            Some(id) => id,
+            None => self.name,
+        }
+    }
+
     /// Takes the first [`Cmd`] instance that satisfies this specification from the raw arguments.
+    ///
+    /// Nested subcommands (e.g., `app remote add`) can be defined by taking the inner specifications
+    /// after the outer one is found to be present (optionally within [`Cmd::scope()`]).
+    /// In that case, the usage line of the help text contains the full path of the subcommands.
     pub fn take(self, args: &mut RawArgs) -> Cmd {
         args.with_record_cmd(|args| {
+            let generation = args.generation();
+            let siblings = args.sibling_cmds();
+            let window = args.index_window(None, None, args.raw_args().len());
             for (index, raw_arg) in args.raw_args_mut().iter_mut().enumerate() {
+                if !window.contains(&index) {
+                    continue;
+                }
                 let Some(value) = &raw_arg.value else {
                     continue;
                 };
 
-                if value == self.name {
+                if self.external && !value.starts_with('-') {
+                    raw_arg.external_name = raw_arg.value.take();
+                    return Cmd::Some {
+                        spec: self,
+                        index,
+                        generation,
+                    };
+                }
+
+                let value = self.normalize(value);
+                let name = self.normalize(self.name);
+                if value == name {
                     raw_arg.value = None;
-                    return Cmd::Some { spec: self, index };
+                    return Cmd::Some {
+                        spec: self,
+                        index,
+                        generation,
+                    };
+                }
+
+                if self.prefix_match
+                    && !value.is_empty()
+                    && name.starts_with(value.as_str())
+                    && !siblings
+                        .iter()
+                        .any(|s| self.normalize(s.name).starts_with(value.as_str()))
+                {
+                    raw_arg.value = None;
+                    return Cmd::Some {
+                        spec: self,
+                        index,
+                        generation,
+                    };
                 }
 
                 // Ensure only the next unconsumed argument is processed as a subcommand.
@@ -50,6 +211,16 @@ impl CmdSpec {
     }
 }
 
+impl CmdSpec {
+    pub(crate) fn normalize(&self, s: &str) -> String {
+        if self.case_insensitive {
+            s.to_ascii_lowercase()
+        } else {
+            s.to_owned()
+        }
+    }
+}
+
 impl Default for CmdSpec {
     fn default() -> Self {
         Self::DEFAULT
@@ -57,14 +228,32 @@ impl Default for CmdSpec {
 }
 
 /// A subcommand.
+///
+/// The enum and its variants are `#[non_exhaustive]`, so that new fields can be added in a compatible way.
+/// Use the accessor methods (e.g., [`Cmd::is_present()`] and [`Cmd::index()`]) or `..` in patterns to read them.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
+#[non_exhaustive]
 pub enum Cmd {
-    Some { spec: CmdSpec, index: usize },
+    #[non_exhaustive]
+    Some {
+        spec: CmdSpec,
+        index: usize,
+        /// Identifies the [`RawArgs`] instance this subcommand was taken from.
+        generation: u64,
+    },
+    #[non_exhaustive]
     None { spec: CmdSpec },
 }
 
+#[allow(clippy::result_large_err)]
 impl Cmd {
+    /// Returns `true` if this subcommand was taken by `spec`, comparing [`CmdSpec::id()`] instead of the whole specification
+    /// (see [`Opt::is()`](crate::Opt::is)).
+    pub fn is(self, spec: CmdSpec) -> bool {
+        self.spec().id() == spec.id()
+    }
+
     /// Returns the specification of this subcommand.
     pub fn spec(self) -> CmdSpec {
         match self {
@@ -72,6 +261,17 @@ impl Cmd {
         }
     }
 
+    /// Returns the name of this subcommand given on the command line.
+    ///
+    /// This is [`CmdSpec::name`] except for external subcommands ([`CmdSpec::external`]),
+    /// for which the matched argument is returned.
+    pub fn name(self, args: &RawArgs) -> &str {
+        self.index()
+            .and_then(|i| args.raw_args().get(i))
+            .and_then(|raw_arg| raw_arg.external_name.as_deref())
+            .unwrap_or(self.spec().name)
+    }
+
     /// Returns `true` if this subcommand is present.
     pub fn is_present(self) -> bool {
         matches!(self, Self::Some { .. })
@@ -90,6 +290,49 @@ impl Cmd {
             None
         }
     }
+
+    /// Restricts the raw arguments considered by subsequent `take()` calls to those after this subcommand
+    /// until the returned guard is dropped (see [`RawArgs::scope()`]).
+    ///
+    /// If this subcommand is not present, no raw arguments are considered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Other`] if this subcommand was not taken from `args`
+    /// (e.g., taken from another [`RawArgs`] instance in a multi-phase application, or rolled back by [`RawArgs::restore()`]),
+    /// as its index is meaningless for `args`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "run", "--force"].iter().map(|a| a.to_string()));
+    /// let run = noargs::cmd("run").take(&mut args);
+    /// let stop = noargs::cmd("stop").take(&mut args);
+    ///
+    /// {
+    ///     let mut args = stop.scope(&mut args)?;
+    ///     assert!(!noargs::flag("force").take(&mut args).is_present());
+    /// }
+    /// let mut args = run.scope(&mut args)?;
+    /// assert!(noargs::flag("force").take(&mut args).is_present());
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn scope(self, args: &mut RawArgs) -> Result<Scope<'_>, Error> {
+        if let Self::Some { generation, .. } = self {
+            if generation != args.generation() || !args.log().contains(&Taken::Cmd(self)) {
+                return Err(Error::other(
+                    args,
+                    format!(
+                        "subcommand '{}' was not taken from these arguments; \
+                         values taken from different instances cannot be mixed",
+                        self.spec().name
+                    ),
+                ));
+            }
+        }
+        let start = self.index().map_or(usize::MAX, |i| i + 1);
+        Ok(args.scope(start..))
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +372,49 @@ mod tests {
         assert_eq!(cmd.index(), Some(2));
     }
 
+    #[test]
+    fn scoped_take() {
+        let mut args = test_args(&["test", "-x", "run", "-x", "--", "-x"]);
+        let run = crate::cmd("run").take(&mut args);
+        assert!(!run.is_present());
+
+        let flag = crate::flag("").short('x');
+        {
+            let mut args = args.scope(2..4);
+            let run = crate::cmd("run").take(&mut args);
+            assert_eq!(run.index(), Some(2));
+
+            let mut args = run.scope(&mut args).expect("taken from args");
+            assert!(matches!(flag.take(&mut args), Flag::Short { index: 3, .. }));
+            assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+        }
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 1, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 5, .. }));
+    }
+
+    #[test]
+    fn external_cmd() {
+        let mut args = test_args(&["test", "--foo", "bar", "baz"]);
+        let external = crate::any_cmd();
+        assert!(!external.take(&mut args).is_present());
+
+        crate::flag("foo").take(&mut args);
+        crate::cmd("run").take(&mut args);
+        let cmd = external.take(&mut args);
+        assert_eq!(cmd.index(), Some(2));
+        assert_eq!(cmd.name(&args), "bar");
+        assert_eq!(cmd.spec().name, "<COMMAND>");
+        assert_eq!(args.take_rest(), ["baz"]);
+        assert!(args.finish().is_ok());
+
+        // Arguments before `--` are not left behind.
+        let mut args = test_args(&["git", "lfs", "pull", "--", "x"]);
+        let cmd = external.take(&mut args);
+        assert_eq!(cmd.name(&args), "lfs");
+        assert_eq!(args.take_rest(), ["pull", "--", "x"]);
+        assert!(args.finish().is_ok());
+    }
+
     #[test]
     fn cmd_not_found() {
         let mut args = test_args(&["test", "--foo", "run", "--foo"]);
@@ -209,6 +495,72 @@ mod tests {
         assert_eq!(cmd.index(), None);
     }
 
+    #[test]
+    fn cmd_prefix_match() {
+        let mut args = test_args(&["test", "st"]);
+        assert!(!crate::cmd("status").take(&mut args).is_present());
+
+        let mut args = test_args(&["test", "st"]);
+        let cmd = crate::cmd("status").prefix_match().take(&mut args);
+        assert!(cmd.is_present());
+        assert_eq!(cmd.index(), Some(1));
+        assert!(args.finish().is_ok());
+
+        // `st` is also a prefix of the already taken `start`.
+        let mut args = test_args(&["test", "st"]);
+        assert!(!crate::cmd("start").take(&mut args).is_present());
+        assert!(
+            !crate::cmd("status")
+                .prefix_match()
+                .take(&mut args)
+                .is_present()
+        );
+        assert_eq!(args.remaining_args().next(), Some((1, "st")));
+
+        // A matched command prefix is not an abbreviated option.
+        let mut args = test_args(&["test", "st", "--stdin"]);
+        args.metadata_mut().allow_abbrev = true;
+        assert!(
+            crate::cmd("status")
+                .prefix_match()
+                .take(&mut args)
+                .is_present()
+        );
+        assert!(crate::flag("stdin").take(&mut args).is_present());
+        assert!(!crate::flag("strict").take(&mut args).is_present());
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn cmd_version() {
+        let mut args = test_args(&["test", "plugin", "sub", "--version"]);
+        assert_eq!(args.cmd_version(), None);
+
+        crate::cmd("other").version("2.0.0").take(&mut args);
+        crate::cmd("plugin").version("1.2.0").take(&mut args);
+        assert_eq!(args.cmd_version(), Some("1.2.0"));
+
+        crate::cmd("sub").take(&mut args);
+        assert_eq!(args.cmd_version(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn cmd_case_insensitive() {
+        let mut args = test_args(&["test", "START"]);
+        assert!(!crate::cmd("start").take(&mut args).is_present());
+
+        let cmd = crate::cmd("start").case_insensitive().take(&mut args);
+        assert!(cmd.is_present());
+        assert_eq!(cmd.spec().name, "start");
+
+        let mut args = test_args(&["test", "StA"]);
+        let cmd = crate::cmd("start")
+            .case_insensitive()
+            .prefix_match()
+            .take(&mut args);
+        assert!(cmd.is_present());
+    }
+
     fn test_args(raw_args: &[&str]) -> RawArgs {
         RawArgs::new(raw_args.iter().map(|a| a.to_string()))
     }
@@ -1,11 +1,19 @@
 use std::io::IsTerminal;
 
-use crate::{Arg, Metadata, Opt, OptSpec, RawArgs, args::Taken, formatter::Formatter};
+use crate::{
+    Arg, ArgSpec, Cmd, Flag, Metadata, Opt, OptSpec, RawArgs,
+    args::{REDACTED, Taken},
+    formatter::Formatter,
+};
 
 /// Possible errors.
 ///
 /// Note that this enum is intended to be used only as a top-level error and
 /// deliberately does not implement the [`std::error::Error`] and [`std::fmt::Display`] traits.
+/// Its [`Debug`](std::fmt::Debug) implementation shows the user-facing text (styled if stderr is a terminal)
+/// so that `fn main() -> noargs::Result<()>` reports errors nicely,
+/// unless [`Metadata::structured_debug`] is `true`.
+/// Use [`Error::display()`] to get the user-facing text as a [`std::fmt::Display`] value.
 ///
 /// Additionally, any external errors that implement [`std::fmt::Display`] can be converted into this error.
 #[allow(missing_docs)]
@@ -14,14 +22,26 @@ pub enum Error {
     UnexpectedArg {
         metadata: Metadata,
         raw_arg: String,
+        source: Option<String>,
     },
     UndefinedCommand {
         metadata: Metadata,
         raw_arg: String,
+        source: Option<String>,
     },
     MissingCommand {
         metadata: Metadata,
     },
+    AmbiguousCommand {
+        metadata: Metadata,
+        raw_arg: String,
+        candidates: Vec<String>,
+    },
+    AmbiguousOpt {
+        metadata: Metadata,
+        raw_arg: String,
+        candidates: Vec<String>,
+    },
     InvalidArg {
         arg: Box<Arg>,
         reason: String,
@@ -36,13 +56,39 @@ pub enum Error {
     MissingOpt {
         opt: Box<Opt>,
     },
+    RepeatedOpt {
+        opt: Box<Opt>,
+    },
+    ConflictingArgs {
+        metadata: Metadata,
+        arg: String,
+        other: String,
+        source: Option<String>,
+    },
+    MissingRequirement {
+        metadata: Metadata,
+        arg: String,
+        required: String,
+        source: Option<String>,
+    },
+    MissingGroup {
+        metadata: Metadata,
+        names: Vec<String>,
+    },
     Other {
         metadata: Option<Metadata>,
         error: String,
     },
 }
 
+// `Error` carries the `Metadata` it was produced with by value, which exceeds clippy's size threshold.
+#[allow(clippy::result_large_err)]
 impl Error {
+    /// Exit status conventionally used when a command-line application fails due to a usage error.
+    ///
+    /// See also: [`Output::report()`](crate::Output::report)
+    pub const EXIT_CODE: u8 = 2;
+
     /// Makes an application specific error.
     pub fn other<E>(args: &RawArgs, error: E) -> Self
     where
@@ -54,6 +100,56 @@ impl Error {
         }
     }
 
+    pub(crate) fn check_ambiguous_opt(args: &RawArgs) -> Result<(), Error> {
+        if !args.metadata().allow_abbrev {
+            return Ok(());
+        }
+
+        let groups = args.taken_long_names();
+        for raw_arg in args.raw_args() {
+            let name = if let Some(name) = &raw_arg.abbreviation {
+                name.as_str()
+            } else if let Some(name) = raw_arg.value.as_deref().and_then(|v| v.strip_prefix("--")) {
+                let name = name.split('=').next().unwrap_or(name);
+                if name.is_empty() || groups.iter().flatten().any(|n| *n == name) {
+                    continue;
+                }
+                name
+            } else {
+                continue;
+            };
+
+            let candidates = groups
+                .iter()
+                .filter_map(|names| names.iter().find(|n| n.starts_with(name)))
+                .map(|n| format!("--{n}"))
+                .collect::<Vec<_>>();
+            if candidates.len() > 1 {
+                return Err(Self::AmbiguousOpt {
+                    metadata: args.metadata(),
+                    raw_arg: format!("--{name}"),
+                    candidates,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn cmd_candidates(level: &[Cmd], name: &str) -> Vec<String> {
+        let mut candidates = Vec::new();
+        for cmd in level {
+            let candidate = cmd.spec().name;
+            let matched = cmd
+                .spec()
+                .normalize(candidate)
+                .starts_with(&cmd.spec().normalize(name));
+            if matched && !candidates.iter().any(|c| c == candidate) {
+                candidates.push(candidate.to_owned());
+            }
+        }
+        candidates
+    }
+
     pub(crate) fn check_command_error(args: &RawArgs) -> Result<(), Error> {
         let Some(Taken::Cmd(cmd)) = args.log().last() else {
             return Ok(());
@@ -61,10 +157,20 @@ impl Error {
         if cmd.is_present() {
             return Ok(());
         }
-        if let Some((_, raw_arg)) = args.remaining_args().next() {
+        if let Some((index, raw_arg)) = args.remaining_args().next() {
+            let level = args.cmd_levels().pop().unwrap_or_default();
+            let candidates = Self::cmd_candidates(&level, raw_arg);
+            if candidates.len() > 1 && level.iter().any(|c| c.spec().prefix_match) {
+                return Err(Self::AmbiguousCommand {
+                    metadata: args.metadata(),
+                    raw_arg: raw_arg.to_owned(),
+                    candidates,
+                });
+            }
             Err(Self::UndefinedCommand {
                 metadata: args.metadata(),
                 raw_arg: raw_arg.to_owned(),
+                source: args.arg_source(index).map(|s| s.to_owned()),
             })
         } else {
             Err(Self::MissingCommand {
@@ -73,54 +179,336 @@ impl Error {
         }
     }
 
+    pub(crate) fn check_repeated_opt(args: &RawArgs) -> Result<(), Error> {
+        if let Some(opt) = args.repeated_opts().first() {
+            Err(Error::RepeatedOpt {
+                opt: Box::new(opt.clone()),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn check_conflicting_args(args: &RawArgs) -> Result<(), Error> {
+        let given = args
+            .log()
+            .iter()
+            .filter_map(|taken| match taken {
+                Taken::Opt(opt) => {
+                    let spec = opt.spec();
+                    let index = opt.index()?;
+                    Some((
+                        spec.name,
+                        spec.short,
+                        spec.display_name(),
+                        spec.conflicts_with,
+                        index,
+                    ))
+                }
+                Taken::Flag(flag) => {
+                    let spec = flag.spec();
+                    let index = flag.index()?;
+                    Some((
+                        spec.name,
+                        spec.short,
+                        spec.display_name(),
+                        spec.conflicts_with,
+                        index,
+                    ))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (i, (name, short, display_name, conflicts_with, index)) in given.iter().enumerate() {
+            let conflicting =
+                given[..i]
+                    .iter()
+                    .find(|(other_name, other_short, _, other_conflicts_with, _)| {
+                        conflicts_with
+                            .iter()
+                            .any(|n| is_referred(n, other_name, *other_short))
+                            || other_conflicts_with
+                                .iter()
+                                .any(|n| is_referred(n, name, *short))
+                    });
+            if let Some((_, _, other_display_name, _, _)) = conflicting {
+                return Err(Error::ConflictingArgs {
+                    metadata: args.metadata(),
+                    arg: display_name.clone(),
+                    other: other_display_name.clone(),
+                    source: args.arg_source(*index).map(|s| s.to_owned()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the names, short names, display names and raw argument indices of the options and flags given on
+    /// the command line or via environment variables.
+    fn given_names(args: &RawArgs) -> Vec<(&'static str, Option<char>, String, Option<usize>)> {
+        args.log()
+            .iter()
+            .filter_map(|taken| match taken {
+                Taken::Opt(opt) if opt.index().is_some() || matches!(opt, Opt::Env { .. }) => {
+                    let spec = opt.spec();
+                    Some((spec.name, spec.short, spec.display_name(), opt.index()))
+                }
+                Taken::Flag(flag) if flag.index().is_some() || matches!(flag, Flag::Env { .. }) => {
+                    let spec = flag.spec();
+                    Some((spec.name, spec.short, spec.display_name(), flag.index()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the display name of the taken option or flag referred to by `name` (e.g., in [`OptSpec::requires`]).
+    fn referred_display_name(args: &RawArgs, name: &str) -> String {
+        args.log()
+            .iter()
+            .find_map(|taken| match taken {
+                Taken::Opt(opt) if is_referred(name, opt.spec().name, opt.spec().short) => {
+                    Some(opt.spec().display_name())
+                }
+                Taken::Flag(flag) if is_referred(name, flag.spec().name, flag.spec().short) => {
+                    Some(flag.spec().display_name())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| format!("--{name}"))
+    }
+
+    pub(crate) fn check_required_args(args: &RawArgs) -> Result<(), Error> {
+        let given = Self::given_names(args);
+        for taken in args.log() {
+            let (display_name, requires, index) = match taken {
+                Taken::Opt(opt) if opt.index().is_some() => {
+                    (opt.spec().display_name(), opt.spec().requires, opt.index())
+                }
+                Taken::Flag(flag) if flag.index().is_some() => (
+                    flag.spec().display_name(),
+                    flag.spec().requires,
+                    flag.index(),
+                ),
+                _ => continue,
+            };
+            let is_given = |name: &&str| {
+                given
+                    .iter()
+                    .any(|(n, short, _, _)| is_referred(name, n, *short))
+            };
+            if let Some(required) = requires.iter().find(|name| !is_given(name)) {
+                return Err(Error::MissingRequirement {
+                    metadata: args.metadata(),
+                    arg: display_name,
+                    required: Self::referred_display_name(args, required),
+                    source: index.and_then(|i| args.arg_source(i)).map(|s| s.to_owned()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_required_envs(args: &RawArgs) -> Result<(), Error> {
+        let missing = args.log().iter().find_map(|taken| match taken {
+            Taken::Opt(opt) if opt.spec().env_required && !opt.is_present() => Some(opt),
+            _ => None,
+        });
+        if let Some(opt) = missing {
+            Err(Error::MissingOpt {
+                opt: Box::new(opt.clone()),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn check_arg_groups(args: &RawArgs) -> Result<(), Error> {
+        let given = Self::given_names(args);
+        for taken in args.log() {
+            let Taken::Group(group) = taken else {
+                continue;
+            };
+            let members = given
+                .iter()
+                .filter(|(name, short, _, _)| {
+                    group.names.iter().any(|n| is_referred(n, name, *short))
+                })
+                .collect::<Vec<_>>();
+            if let [(_, _, first, _), (_, _, second, index), ..] = members[..] {
+                if !group.multiple {
+                    return Err(Error::ConflictingArgs {
+                        metadata: args.metadata(),
+                        arg: second.clone(),
+                        other: first.clone(),
+                        source: index.and_then(|i| args.arg_source(i)).map(|s| s.to_owned()),
+                    });
+                }
+            }
+            if members.is_empty() && group.required && !group.names.is_empty() {
+                return Err(Error::MissingGroup {
+                    metadata: args.metadata(),
+                    names: group
+                        .names
+                        .iter()
+                        .map(|name| Self::referred_display_name(args, name))
+                        .collect(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn check_unexpected_arg(args: &RawArgs) -> Result<(), Error> {
-        if let Some(unexpected_arg) = args.next_raw_arg_value() {
+        if let Some(unexpected_arg) = args.next_raw_arg() {
             Err(Error::UnexpectedArg {
                 metadata: args.metadata(),
-                raw_arg: unexpected_arg.to_owned(),
+                raw_arg: unexpected_arg.value.clone().unwrap_or_default(),
+                source: unexpected_arg.source.clone(),
             })
         } else {
             Ok(())
         }
     }
 
-    fn to_string(&self, is_terminal: bool) -> String {
+    /// Returns a wrapper that implements [`std::fmt::Display`] to show the user-facing text of this error.
+    ///
+    /// Unlike the [`Debug`](std::fmt::Debug) implementation, the text is never styled and stderr is not probed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let args = noargs::RawArgs::new(["noargs", "--foo"].iter().map(|a| a.to_string()));
+    /// let e = args.finish().expect_err("error");
+    /// assert_eq!(
+    ///     e.display().to_string(),
+    ///     "unexpected argument '--foo' found\n\nTry '--help' for more information."
+    /// );
+    /// ```
+    pub fn display(&self) -> ErrorDisplay<'_> {
+        ErrorDisplay(self)
+    }
+
+    /// Renders this error using the given metadata instead of the one captured when the error was made.
+    ///
+    /// This is useful when several applications share a process and the metadata captured at error time
+    /// might belong to another application.
+    /// Note that errors converted from external errors (via `From`) are also rendered with `metadata`
+    /// (e.g., the help hint line is added).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let args = noargs::RawArgs::new(["noargs", "--foo"].iter().map(|a| a.to_string()));
+    /// let mut metadata = args.metadata();
+    /// let e = args.finish().expect_err("error");
+    ///
+    /// metadata.help_flag_name = Some("usage");
+    /// assert_eq!(
+    ///     e.render_with(metadata),
+    ///     "unexpected argument '--foo' found\n\nTry '--usage' for more information."
+    /// );
+    /// ```
+    pub fn render_with(&self, metadata: Metadata) -> String {
+        let is_terminal = metadata
+            .style_errors
+            .unwrap_or_else(|| std::io::stderr().is_terminal());
+        self.render(is_terminal, Some(metadata))
+    }
+
+    pub(crate) fn to_string(&self, is_terminal: bool) -> String {
+        self.render(is_terminal, None)
+    }
+
+    pub(crate) fn render(&self, is_terminal: bool, metadata_override: Option<Metadata>) -> String {
         let mut fmt = Formatter::new(is_terminal);
         let metadata = match self {
-            Error::UnexpectedArg { metadata, raw_arg } => {
+            Error::UnexpectedArg {
+                metadata,
+                raw_arg,
+                source,
+            } => {
                 fmt.write(&format!(
                     "unexpected argument '{}' found",
                     fmt.bold(raw_arg)
                 ));
-                *metadata
+                Self::write_source(&mut fmt, source.as_deref());
+                Some(*metadata)
             }
-            Error::UndefinedCommand { metadata, raw_arg } => {
+            Error::UndefinedCommand {
+                metadata,
+                raw_arg,
+                source,
+            } => {
                 fmt.write(&format!("'{}' command is not defined", fmt.bold(raw_arg)));
-                *metadata
+                Self::write_source(&mut fmt, source.as_deref());
+                Some(*metadata)
             }
             Error::MissingCommand { metadata } => {
                 fmt.write("command is not specified");
-                *metadata
+                Some(*metadata)
             }
-            Error::InvalidArg { arg, reason } => {
+            Error::AmbiguousCommand {
+                metadata,
+                raw_arg,
+                candidates,
+            } => {
+                let candidates = candidates
+                    .iter()
+                    .map(|c| format!("'{}'", fmt.bold(c)))
+                    .collect::<Vec<_>>();
+                fmt.write(&format!(
+                    "command '{}' is ambiguous; possibilities: {}",
+                    fmt.bold(raw_arg),
+                    candidates.join(", ")
+                ));
+                Some(*metadata)
+            }
+            Error::AmbiguousOpt {
+                metadata,
+                raw_arg,
+                candidates,
+            } => {
+                let candidates = candidates
+                    .iter()
+                    .map(|c| format!("'{}'", fmt.bold(c)))
+                    .collect::<Vec<_>>();
                 fmt.write(&format!(
-                    "argument '{}' has an invalid value {:?}: {reason}",
-                    fmt.bold(arg.spec().name),
-                    arg.value()
+                    "option '{}' is ambiguous; possibilities: {}",
+                    fmt.bold(raw_arg),
+                    candidates.join(", ")
                 ));
-                if let Some(metadata) = arg.metadata() {
-                    metadata
+                Some(*metadata)
+            }
+            Error::InvalidArg { arg, reason } => {
+                let name = match &**arg {
+                    Arg::Env {
+                        spec: ArgSpec { env: Some(env), .. },
+                        ..
+                    } => format!(
+                        "environment variable '{}' for '{}'",
+                        fmt.bold(env),
+                        fmt.bold(arg.spec().name)
+                    ),
+                    _ => format!("argument '{}'", fmt.bold(arg.spec().name)),
+                };
+                let name = match arg.injected_by() {
+                    Some(source) => format!("{name} (injected by {source})"),
+                    None => name,
+                };
+                if arg.is_sensitive() {
+                    fmt.write(&format!("{name} has an invalid value {REDACTED}: {reason}"));
                 } else {
-                    return fmt.finish();
+                    fmt.write(&format!(
+                        "{name} has an invalid value {:?}: {reason}",
+                        arg.value()
+                    ));
                 }
+                arg.metadata()
             }
             Error::MissingArg { arg } => {
                 fmt.write(&format!("missing argument '{}'", fmt.bold(arg.spec().name)));
-                if let Some(metadata) = arg.metadata() {
-                    metadata
-                } else {
-                    return fmt.finish();
-                }
+                arg.metadata()
             }
             Error::InvalidOpt { opt, reason } => {
                 let name = match &**opt {
@@ -128,75 +516,159 @@ impl Error {
                         spec: OptSpec { short: Some(c), .. },
                         ..
                     } => format!("argument '{}'", fmt.bold(&format!("-{c}"))),
-                    Opt::Env {
-                        spec:
-                            OptSpec {
-                                env: Some(name), ..
-                            },
-                        ..
-                    } => format!(
+                    Opt::Env { spec, metadata, .. } => format!(
                         "environment variable '{}' for '{}'",
-                        fmt.bold(name),
-                        fmt.bold(&format!("--{}", opt.spec().name))
+                        fmt.bold(&spec.env_name(metadata).unwrap_or_default()),
+                        fmt.bold(&spec.display_name())
                     ),
-                    _ => format!("argument '{}'", fmt.bold(&format!("--{}", opt.spec().name))),
+                    _ => format!("argument '{}'", fmt.bold(&opt.spec().display_name())),
                 };
-                fmt.write(&format!(
-                    "{name} has an invalid value {:?}: {reason}",
-                    opt.value()
-                ));
-                if let Some(metadata) = opt.metadata() {
-                    metadata
+                let name = match opt.injected_by() {
+                    Some(source) => format!("{name} (injected by {source})"),
+                    None => name,
+                };
+                if opt.is_sensitive() {
+                    fmt.write(&format!("{name} has an invalid value {REDACTED}: {reason}"));
                 } else {
-                    return fmt.finish();
+                    fmt.write(&format!(
+                        "{name} has an invalid value {:?}: {reason}",
+                        opt.value()
+                    ));
                 }
+                opt.metadata()
             }
             Error::MissingOpt { opt } => {
-                match **opt {
-                    Opt::MissingValue {
-                        spec:
-                            OptSpec {
-                                short: Some(name), ..
-                            },
-                        long: false,
-                    } => {
-                        let name = fmt.bold(&format!("-{name}")).into_owned();
-                        fmt.write(&format!("missing '{name}' value"));
+                match &**opt {
+                    Opt::MissingValue { index, raw_arg, .. } => {
+                        let name = fmt.bold(raw_arg).into_owned();
+                        fmt.write(&format!("missing '{name}' value (argument #{index})"));
+                        Self::write_source(&mut fmt, opt.injected_by());
+                    }
+                    _ if opt.spec().env_required && opt.spec().env.is_some() => {
+                        let name = fmt.bold(opt.spec().env.unwrap_or_default()).into_owned();
+                        fmt.write(&format!("environment variable '{name}' is not set"));
                     }
-                    Opt::MissingValue { spec, .. } => {
-                        let name = fmt.bold(&format!("--{}", spec.name)).into_owned();
-                        fmt.write(&format!("missing '{name}' value"));
+                    _ if opt.spec().env_required && opt.spec().env_auto => {
+                        // The derived name depends on the metadata, which a missing option does not have.
+                        let name = fmt.bold(&opt.spec().display_name()).into_owned();
+                        fmt.write(&format!("environment variable for '{name}' is not set"));
                     }
                     _ => {
-                        let name = fmt.bold(&format!("--{}", opt.spec().name)).into_owned();
+                        let name = fmt.bold(&opt.spec().display_name()).into_owned();
                         fmt.write(&format!("missing '{name}' option"));
                     }
                 };
-                if let Some(metadata) = opt.metadata() {
-                    metadata
-                } else {
-                    return fmt.finish();
+                if let Some(hint) = opt.spec().missing_hint {
+                    fmt.write(&format!(": {hint}"));
                 }
+                opt.metadata()
+            }
+            Error::RepeatedOpt { opt } => {
+                let name = match &**opt {
+                    Opt::Short {
+                        spec: OptSpec { short: Some(c), .. },
+                        ..
+                    } => format!("-{c}"),
+                    _ => opt.spec().display_name(),
+                };
+                fmt.write(&format!(
+                    "argument '{}' cannot be used multiple times (argument #{})",
+                    fmt.bold(&name),
+                    opt.index().unwrap_or_default()
+                ));
+                Self::write_source(&mut fmt, opt.injected_by());
+                opt.metadata()
             }
-            Error::Other {
-                metadata: Some(metadata),
-                error,
+            Error::ConflictingArgs {
+                metadata,
+                arg,
+                other,
+                source,
             } => {
-                fmt.write(error);
-                *metadata
+                fmt.write(&format!(
+                    "argument '{}' cannot be used with '{}'",
+                    fmt.bold(arg),
+                    fmt.bold(other)
+                ));
+                Self::write_source(&mut fmt, source.as_deref());
+                Some(*metadata)
             }
-            Error::Other {
-                metadata: None,
-                error,
+            Error::MissingRequirement {
+                metadata,
+                arg,
+                required,
+                source,
             } => {
+                fmt.write(&format!(
+                    "argument '{}' requires '{}'",
+                    fmt.bold(arg),
+                    fmt.bold(required)
+                ));
+                Self::write_source(&mut fmt, source.as_deref());
+                Some(*metadata)
+            }
+            Error::MissingGroup { metadata, names } => {
+                let names = names
+                    .iter()
+                    .map(|name| format!("'{}'", fmt.bold(name)))
+                    .collect::<Vec<_>>();
+                let (last, init) = names.split_last().expect("non-empty group");
+                if init.is_empty() {
+                    fmt.write(&format!("{last} must be given"));
+                } else {
+                    fmt.write(&format!(
+                        "one of {} or {last} must be given",
+                        init.join(", ")
+                    ));
+                }
+                Some(*metadata)
+            }
+            Error::Other { metadata, error } => {
+                let metadata = metadata_override.or(*metadata);
+                if metadata.is_some_and(|m| m.dry_run) {
+                    fmt.write("(dry-run) ");
+                }
                 fmt.write(error);
-                return fmt.finish();
+                metadata
             }
         };
-        Self::write_help_line(&mut fmt, metadata);
+        if let Some(metadata) = metadata_override.or(metadata) {
+            Self::write_help_line(&mut fmt, metadata);
+        }
         fmt.finish()
     }
 
+    /// Returns whether this error is styled according to [`Metadata::style_errors`].
+    pub(crate) fn is_styled(&self, is_stderr_terminal: impl FnOnce() -> bool) -> bool {
+        self.metadata()
+            .and_then(|m| m.style_errors)
+            .unwrap_or_else(is_stderr_terminal)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Error::UnexpectedArg { metadata, .. }
+            | Error::UndefinedCommand { metadata, .. }
+            | Error::MissingCommand { metadata }
+            | Error::AmbiguousCommand { metadata, .. }
+            | Error::AmbiguousOpt { metadata, .. }
+            | Error::ConflictingArgs { metadata, .. }
+            | Error::MissingRequirement { metadata, .. }
+            | Error::MissingGroup { metadata, .. } => Some(*metadata),
+            Error::InvalidArg { arg, .. } | Error::MissingArg { arg } => arg.metadata(),
+            Error::InvalidOpt { opt, .. }
+            | Error::MissingOpt { opt }
+            | Error::RepeatedOpt { opt } => opt.metadata(),
+            Error::Other { metadata, .. } => *metadata,
+        }
+    }
+
+    fn write_source(fmt: &mut Formatter, source: Option<&str>) {
+        if let Some(source) = source {
+            fmt.write(&format!(" (injected by {source})"));
+        }
+    }
+
     fn write_help_line(fmt: &mut Formatter, metadata: Metadata) {
         if let Some(help_flag_name) = metadata.help_flag_name {
             fmt.write(&format!(
@@ -207,6 +679,19 @@ impl Error {
     }
 }
 
+/// Returns `true` if `name` (e.g., an entry of [`OptSpec::requires`]) refers to the option or flag having
+/// the given long and short names.
+///
+/// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+fn is_referred(name: &str, long: &str, short: Option<char>) -> bool {
+    if long.is_empty() {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| Some(c) == short) && chars.next().is_none()
+    } else {
+        name == long
+    }
+}
+
 impl<T: std::fmt::Display> From<T> for Error {
     fn from(error: T) -> Self {
         Self::Other {
@@ -218,13 +703,119 @@ impl<T: std::fmt::Display> From<T> for Error {
 
 impl std::fmt::Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string(std::io::stderr().is_terminal()))
+        if !self.metadata().is_some_and(|m| m.structured_debug) {
+            let is_terminal = self.is_styled(|| std::io::stderr().is_terminal());
+            return write!(f, "{}", self.to_string(is_terminal));
+        }
+        match self {
+            Error::UnexpectedArg {
+                metadata,
+                raw_arg,
+                source,
+            } => f
+                .debug_struct("UnexpectedArg")
+                .field("metadata", metadata)
+                .field("raw_arg", raw_arg)
+                .field("source", source)
+                .finish(),
+            Error::UndefinedCommand {
+                metadata,
+                raw_arg,
+                source,
+            } => f
+                .debug_struct("UndefinedCommand")
+                .field("metadata", metadata)
+                .field("raw_arg", raw_arg)
+                .field("source", source)
+                .finish(),
+            Error::MissingCommand { metadata } => f
+                .debug_struct("MissingCommand")
+                .field("metadata", metadata)
+                .finish(),
+            Error::AmbiguousCommand {
+                metadata,
+                raw_arg,
+                candidates,
+            } => f
+                .debug_struct("AmbiguousCommand")
+                .field("metadata", metadata)
+                .field("raw_arg", raw_arg)
+                .field("candidates", candidates)
+                .finish(),
+            Error::AmbiguousOpt {
+                metadata,
+                raw_arg,
+                candidates,
+            } => f
+                .debug_struct("AmbiguousOpt")
+                .field("metadata", metadata)
+                .field("raw_arg", raw_arg)
+                .field("candidates", candidates)
+                .finish(),
+            Error::InvalidArg { arg, reason } => f
+                .debug_struct("InvalidArg")
+                .field("arg", arg)
+                .field("reason", reason)
+                .finish(),
+            Error::MissingArg { arg } => f.debug_struct("MissingArg").field("arg", arg).finish(),
+            Error::InvalidOpt { opt, reason } => f
+                .debug_struct("InvalidOpt")
+                .field("opt", opt)
+                .field("reason", reason)
+                .finish(),
+            Error::MissingOpt { opt } => f.debug_struct("MissingOpt").field("opt", opt).finish(),
+            Error::RepeatedOpt { opt } => f.debug_struct("RepeatedOpt").field("opt", opt).finish(),
+            Error::ConflictingArgs {
+                metadata,
+                arg,
+                other,
+                source,
+            } => f
+                .debug_struct("ConflictingArgs")
+                .field("metadata", metadata)
+                .field("arg", arg)
+                .field("other", other)
+                .field("source", source)
+                .finish(),
+            Error::MissingRequirement {
+                metadata,
+                arg,
+                required,
+                source,
+            } => f
+                .debug_struct("MissingRequirement")
+                .field("metadata", metadata)
+                .field("arg", arg)
+                .field("required", required)
+                .field("source", source)
+                .finish(),
+            Error::MissingGroup { metadata, names } => f
+                .debug_struct("MissingGroup")
+                .field("metadata", metadata)
+                .field("names", names)
+                .finish(),
+            Error::Other { metadata, error } => f
+                .debug_struct("Other")
+                .field("metadata", metadata)
+                .field("error", error)
+                .finish(),
+        }
+    }
+}
+
+/// Wrapper returned by [`Error::display()`].
+#[derive(Debug)]
+pub struct ErrorDisplay<'a>(&'a Error);
+
+impl std::fmt::Display for ErrorDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_string(false))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{arg, cmd, opt};
+    use crate::{arg, cmd, flag, opt};
 
     use super::*;
 
@@ -251,6 +842,188 @@ Try '--help' for more information."#
         );
     }
 
+    #[test]
+    fn structured_debug() {
+        let args = RawArgs::new(["noargs", "--foo"].iter().map(|a| a.to_string()));
+        let e = args.finish().expect_err("error");
+        assert!(format!("{e:?}").starts_with("unexpected argument '"));
+        assert_eq!(
+            e.display().to_string(),
+            "unexpected argument '--foo' found\n\nTry '--help' for more information."
+        );
+
+        let mut args = RawArgs::new(["noargs", "--foo"].iter().map(|a| a.to_string()));
+        args.metadata_mut().structured_debug = true;
+        let e = args.finish().expect_err("error");
+        let debug = format!("{e:?}");
+        assert!(debug.starts_with("UnexpectedArg { metadata: Metadata {"));
+        assert!(debug.ends_with(r#"raw_arg: "--foo", source: None }"#));
+
+        let mut args = RawArgs::new(["noargs", "--pin=abc"].iter().map(|a| a.to_string()));
+        args.metadata_mut().structured_debug = true;
+        let e = opt("pin")
+            .sensitive()
+            .take(&mut args)
+            .then(|o| o.value().parse::<u32>())
+            .expect_err("error");
+        let debug = format!("{e:?}");
+        assert!(debug.starts_with("InvalidOpt { opt: Long {"));
+        assert!(debug.ends_with(
+            r#"value: [redacted], extra_values: [redacted], source: None }, reason: "invalid digit found in string" }"#
+        ));
+    }
+
+    #[test]
+    fn conflicting_args_error() {
+        let json = flag("json").conflicts_with(&["yaml", "format"]);
+        let yaml = flag("yaml");
+        let format = opt("format").default("text");
+
+        // No error.
+        let mut args = RawArgs::new(["noargs", "--json"].iter().map(|a| a.to_string()));
+        json.take(&mut args);
+        yaml.take(&mut args);
+        format.take(&mut args);
+        assert!(args.finish().is_ok());
+
+        // Error.
+        let mut args = RawArgs::new(
+            ["noargs", "--yaml", "--format=xml", "--json"]
+                .iter()
+                .map(|a| a.to_string()),
+        );
+        args.metadata_mut().help_flag_name = None;
+        yaml.take(&mut args);
+        format.take(&mut args);
+        json.take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "argument '--json' cannot be used with '--yaml'"
+        );
+    }
+
+    #[test]
+    fn missing_requirement_error() {
+        let key = opt("key-file").requires(&["cert-file"]);
+        let cert = opt("cert-file").env("TEST_NOARGS_CERT_FILE");
+
+        // No error.
+        let mut args = RawArgs::new(
+            ["noargs", "--key-file=a", "--cert-file=b"]
+                .iter()
+                .map(|a| a.to_string()),
+        );
+        key.take(&mut args);
+        cert.take(&mut args);
+        assert!(args.finish().is_ok());
+
+        let mut args = RawArgs::new(["noargs", "--cert-file=b"].iter().map(|a| a.to_string()));
+        key.take(&mut args);
+        cert.take(&mut args);
+        assert!(args.finish().is_ok());
+
+        // Error.
+        let mut args = RawArgs::new(["noargs", "--key-file=a"].iter().map(|a| a.to_string()));
+        key.take(&mut args);
+        cert.take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '--key-file' requires '--cert-file'
+
+Try '--help' for more information."#
+        );
+
+        // Short-only prerequisite.
+        let key = opt("key-file").requires(&["c"]);
+        let cert = opt("").short('c');
+        let mut args = RawArgs::new(
+            ["noargs", "--key-file=a", "-c", "b"]
+                .iter()
+                .map(|a| a.to_string()),
+        );
+        key.take(&mut args);
+        cert.take(&mut args);
+        assert!(args.finish().is_ok());
+
+        let mut args = RawArgs::new(["noargs", "--key-file=a"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        key.take(&mut args);
+        cert.take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(e.to_string(false), "argument '--key-file' requires '-c'");
+    }
+
+    #[test]
+    fn injected_arg_error() {
+        let mut args = RawArgs::new(["noargs", "--foo"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        args.push_injected(["--bar".to_owned()].into_iter(), "wrapper");
+        flag("foo").take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "unexpected argument '--bar' found (injected by wrapper)"
+        );
+
+        let mut args = RawArgs::new(["noargs"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        args.push_injected(["baz".to_owned()].into_iter(), "wrapper");
+        cmd("foo").take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "'baz' command is not defined (injected by wrapper)"
+        );
+
+        let injected = |raw_args: &[&str]| {
+            let mut args = RawArgs::new(["noargs"].iter().map(|a| a.to_string()));
+            args.metadata_mut().help_flag_name = None;
+            args.push_injected(raw_args.iter().map(|a| a.to_string()), "wrapper");
+            args
+        };
+
+        let mut args = injected(&["--port=abc"]);
+        let e = opt("port")
+            .take(&mut args)
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '--port' (injected by wrapper) has an invalid value "abc": invalid digit found in string"#
+        );
+
+        let mut args = injected(&["--port"]);
+        let e = opt("port")
+            .take(&mut args)
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "missing '--port' value (argument #1) (injected by wrapper)"
+        );
+
+        let mut args = injected(&["abc"]);
+        let e = arg("<PORT>")
+            .take(&mut args)
+            .then(|a| a.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '<PORT>' (injected by wrapper) has an invalid value "abc": invalid digit found in string"#
+        );
+
+        let mut args = injected(&["--quiet", "--verbose"]);
+        flag("quiet").take(&mut args);
+        flag("verbose").conflicts_with(&["quiet"]).take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "argument '--verbose' cannot be used with '--quiet' (injected by wrapper)"
+        );
+    }
+
     #[test]
     fn undefined_command_error() {
         let mut args = RawArgs::new(["noargs", "baz"].iter().map(|a| a.to_string()));
@@ -320,6 +1093,111 @@ Try '--help' for more information."#
             .take(&mut args)
             .then(|o| o.value().parse::<usize>())
             .expect_err("error");
-        assert_eq!(e.to_string(false), "missing '-f' value");
+        assert_eq!(e.to_string(false), "missing '-f' value (argument #1)");
+
+        let mut args = RawArgs::new(["noargs", "a", "--old"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        let e = opt("foo")
+            .aliases(&["old"])
+            .take(&mut args)
+            .then(|o| o.value().parse::<usize>())
+            .expect_err("error");
+        assert_eq!(e.to_string(false), "missing '--old' value (argument #2)");
+    }
+
+    #[test]
+    fn short_only_opt_error() {
+        let mut args = RawArgs::new(["noargs"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        let e = opt("")
+            .short('n')
+            .take(&mut args)
+            .then(|o| o.value().parse::<usize>())
+            .expect_err("error");
+        assert_eq!(e.to_string(false), "missing '-n' option");
+    }
+
+    #[test]
+    fn ambiguous_opt_error() {
+        // Ambiguous among the specs taken before.
+        let mut args = RawArgs::new(["noargs", "--verb"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        args.metadata_mut().allow_abbrev = true;
+        flag("verbose").take(&mut args);
+        flag("verbatim").take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "option '--verb' is ambiguous; possibilities: '--verbose', '--verbatim'"
+        );
+
+        // Ambiguous among the specs taken after.
+        let mut args = RawArgs::new(["noargs", "--verb=1"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        args.metadata_mut().allow_abbrev = true;
+        assert!(opt("verbosity").take(&mut args).is_present());
+        flag("verbatim").take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "option '--verb' is ambiguous; possibilities: '--verbosity', '--verbatim'"
+        );
+    }
+
+    #[test]
+    fn ambiguous_command_error() {
+        let mut args = RawArgs::new(["noargs", "sta"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        cmd("stop").prefix_match().take(&mut args);
+        cmd("start").take(&mut args);
+        cmd("status").prefix_match().take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "command 'sta' is ambiguous; possibilities: 'start', 'status'"
+        );
+
+        // Without prefix matching, this is just an undefined command.
+        let mut args = RawArgs::new(["noargs", "sta"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        cmd("start").take(&mut args);
+        cmd("status").take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(e.to_string(false), "'sta' command is not defined");
+    }
+
+    #[test]
+    fn missing_opt_hint() {
+        let mut args = RawArgs::new(["noargs"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        let port = opt("port").missing_hint("provide a port number, e.g. --port 8080");
+        let e = port
+            .take(&mut args)
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "missing '--port' option: provide a port number, e.g. --port 8080"
+        );
+
+        let mut args = RawArgs::new(["noargs", "--port"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        let e = port
+            .take(&mut args)
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "missing '--port' value (argument #1): provide a port number, e.g. --port 8080"
+        );
+    }
+
+    #[test]
+    fn dry_run_other_error() {
+        let mut args = RawArgs::new(["noargs"].iter().map(|a| a.to_string()));
+        args.metadata_mut().help_flag_name = None;
+        args.metadata_mut().dry_run = true;
+        let e = Error::other(&args, "failed to write 'out.txt'");
+        assert_eq!(e.to_string(false), "(dry-run) failed to write 'out.txt'");
     }
 }
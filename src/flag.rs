@@ -1,11 +1,15 @@
-use crate::args::RawArgs;
+use std::borrow::Cow;
+
+use crate::args::{self, Metadata, RawArgs, is_negative_number, match_slash_name, strip_long_name};
 
 /// Specification for [`Flag`].
 ///
-/// Note that `noargs` does not support flags with only short names.
+/// If [`FlagSpec::name`] is empty, the flag only has a short name (e.g., `noargs::flag("").short('x')`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FlagSpec {
     /// Flag long name (usually kebab-case).
+    ///
+    /// An empty name means that this flag has no long name.
     pub name: &'static str,
 
     /// Flag short name.
@@ -18,6 +22,79 @@ pub struct FlagSpec {
     ///
     /// If a non-empty value is set to this variable, this flag is considered to be set.
     pub env: Option<&'static str>,
+
+    /// If `true` and [`FlagSpec::env`] is `None`, the environment variable name is derived from [`FlagSpec::name`]
+    /// and [`Metadata::env_prefix`](crate::Metadata::env_prefix) when taking this flag (e.g., `MYAPP_DB_PORT` for `db-port`).
+    pub env_auto: bool,
+
+    /// Environment variable values that are considered as "not set" for this flag (default: `&[]`).
+    ///
+    /// Values are compared ignoring ASCII case.
+    /// For example, with [`FALSEY_ENV_VALUES`](crate::FALSEY_ENV_VALUES), `MYAPP_DEBUG=0` does not enable
+    /// `noargs::flag("debug").env("MYAPP_DEBUG")`.
+    pub falsey_env_values: &'static [&'static str],
+
+    /// Alternative long names (e.g., old names kept for backward compatibility).
+    pub aliases: &'static [&'static str],
+
+    /// Alternative short names.
+    pub short_aliases: &'static [char],
+
+    /// If `true`, [`FlagSpec::aliases`] and [`FlagSpec::short_aliases`] are not shown in the help text.
+    pub hide_aliases: bool,
+
+    /// Long names of options and flags that cannot be used together with this flag (e.g., `--json` and `--yaml`).
+    ///
+    /// If this flag and any of them are given on the command line, [`RawArgs::finish()`] reports an error.
+    /// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+    pub conflicts_with: &'static [&'static str],
+
+    /// Long names of options and flags that must also be given when this flag is given (e.g., `--cert-file` for `--key-file`).
+    ///
+    /// If this flag is given on the command line and any of them is given neither on the command line nor
+    /// via its environment variable, [`RawArgs::finish()`] reports an error.
+    /// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+    pub requires: &'static [&'static str],
+
+    /// If set, only raw arguments whose indices are greater than this value are considered when taking this flag
+    /// (e.g., the index of a subcommand to ignore the arguments before it).
+    pub after_index: Option<usize>,
+
+    /// If set, only raw arguments whose indices are less than this value are considered when taking this flag
+    /// (e.g., the index of `--` to ignore the arguments after it).
+    pub before_index: Option<usize>,
+
+    /// Stable identifier of this flag (see [`FlagSpec::id()`]).
+    pub stable_id: Option<&'static str>,
+
+    /// If `true`, this flag belongs to every subcommand (e.g., `--verbose` taken before subcommand dispatch).
+    ///
+    /// A global flag is searched for in all the raw arguments even if [`Metadata::posix_ordering`](crate::Metadata::posix_ordering)
+    /// is `true`, so it matches even if written after the subcommand name (e.g., `myapp run --verbose`).
+    /// As flags taken before a subcommand are kept in the log, it is also shown in the help text of the subcommand.
+    pub global: bool,
+
+    /// If `true`, this flag is not shown in the help text (but is parsed as usual).
+    pub hidden: bool,
+
+    /// Importance of this flag in the summary help text (`-h`) (default: `128`).
+    ///
+    /// If `0`, this flag is omitted from the summary help text (but is still shown in the full help text).
+    /// If [`Metadata::summary_max_lines`](crate::Metadata::summary_max_lines) is set,
+    /// flags with lower priorities are omitted first.
+    pub help_priority: u8,
+
+    /// If `true`, this flag is expected to be taken more than once (e.g., in a loop).
+    ///
+    /// This suppresses the warning recorded when [`Metadata::strict_takes`](crate::Metadata::strict_takes)
+    /// is enabled and the same specification is taken again.
+    pub repeatable: bool,
+
+    /// If set, this flag is deprecated with the given note (e.g., `"use --new-name instead"`).
+    ///
+    /// A deprecated flag is parsed as usual, but a warning is recorded when it is given on the command line
+    /// (see [`RawArgs::warnings()`]), and the note is shown in the help text.
+    pub deprecated: Option<&'static str>,
 }
 
 impl FlagSpec {
@@ -27,6 +104,21 @@ impl FlagSpec {
         short: None,
         doc: "",
         env: None,
+        env_auto: false,
+        falsey_env_values: &[],
+        aliases: &[],
+        short_aliases: &[],
+        conflicts_with: &[],
+        requires: &[],
+        hide_aliases: false,
+        after_index: None,
+        before_index: None,
+        stable_id: None,
+        hidden: false,
+        help_priority: 128,
+        global: false,
+        repeatable: false,
+        deprecated: None,
     };
 
     /// Makes an [`FlagSpec`] instance with a specified name (equivalent to `noargs::flag(name)`).
@@ -55,46 +147,271 @@ impl FlagSpec {
         self
     }
 
+    /// Sets [`FlagSpec::env_auto`] to `true`.
+    pub const fn env_auto(mut self) -> Self {
+        self.env_auto = true;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::falsey_env_values`].
+    pub const fn falsey_env_values(mut self, values: &'static [&'static str]) -> Self {
+        self.falsey_env_values = values;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::aliases`].
+    pub const fn aliases(mut self, names: &'static [&'static str]) -> Self {
+        self.aliases = names;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::short_aliases`].
+    pub const fn short_aliases(mut self, names: &'static [char]) -> Self {
+        self.short_aliases = names;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::conflicts_with`].
+    pub const fn conflicts_with(mut self, names: &'static [&'static str]) -> Self {
+        self.conflicts_with = names;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::requires`].
+    pub const fn requires(mut self, names: &'static [&'static str]) -> Self {
+        self.requires = names;
+        self
+    }
+
+    /// Sets [`FlagSpec::hide_aliases`] to `true`.
+    pub const fn hide_aliases(mut self) -> Self {
+        self.hide_aliases = true;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::after_index`].
+    pub const fn after_index(mut self, index: usize) -> Self {
+        self.after_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::before_index`].
+    pub const fn before_index(mut self, index: usize) -> Self {
+        self.before_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::stable_id`].
+    pub const fn stable_id(mut self, id: &'static str) -> Self {
+        self.stable_id = Some(id);
+        self
+    }
+
+    /// Sets [`FlagSpec::hidden`] to `true`.
+    pub const fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::help_priority`].
+    pub const fn help_priority(mut self, priority: u8) -> Self {
+        self.help_priority = priority;
+        self
+    }
+
+    /// Sets [`FlagSpec::global`] to `true`.
+    pub const fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
+
+    /// Sets [`FlagSpec::repeatable`] to `true`.
+    pub const fn repeatable(mut self) -> Self {
+        self.repeatable = true;
+        self
+    }
+
+    /// Updates the value of [`FlagSpec::deprecated`].
+    pub const fn deprecated(mut self, note: &'static str) -> Self {
+        self.deprecated = Some(note);
+        self
+    }
+
+    /// Returns the identifier of this flag, which is [`FlagSpec::stable_id`] if set, or [`FlagSpec::name`] otherwise
+    /// (see [`OptSpec::id()`](crate::OptSpec::id)).
+    pub const fn id(&self) -> &'static str {
+        match self.stable_id {
+            Some(id) => id,
+            None => self.name,
+        }
+    }
+
+    /// Builds raw arguments that specify this flag (e.g., `["--verbose"]`).
+    ///
+    /// This is useful to construct a command line of an application that uses the same specifications.
+    pub fn to_args(self) -> Vec<String> {
+        vec![self.display_name()]
+    }
+
+    /// Takes all [`Flag`] instances that satisfy this specification from the raw arguments.
+    ///
+    /// Unlike calling [`FlagSpec::take()`] in a `while` loop, this method always terminates even if
+    /// the result is present without consuming any raw argument (e.g., [`Flag::Env`]).
+    /// Such a result is included only if nothing is found in the raw arguments.
+    pub fn take_all(self, args: &mut RawArgs) -> Vec<Flag> {
+        let this = self.repeatable();
+        let mut taken = Vec::new();
+        loop {
+            let x = this.take(args);
+            if x.index().is_some() {
+                taken.push(x);
+                continue;
+            }
+            if taken.is_empty() && x.is_present() {
+                taken.push(x);
+            }
+            return taken;
+        }
+    }
+
+    /// Looks up the first occurrence of this flag in the raw arguments without consuming them
+    /// or recording this flag in `args` (see [`OptSpec::peek()`](crate::OptSpec::peek)).
+    ///
+    /// Only the command line is looked up, so [`Flag::Env`] is never returned.
+    pub fn peek(self, args: &RawArgs) -> Flag {
+        let metadata = args.metadata();
+        let rivals = args.abbrev_rivals(self.long_names());
+        let window = self.search_window(args);
+        for index in window {
+            let Some(value) = &args.raw_args()[index].value else {
+                continue;
+            };
+            match self.match_raw_arg(&metadata, value, rivals.as_deref()) {
+                Some(FlagMatch::Long { .. }) => return Flag::Long { spec: self, index },
+                Some(FlagMatch::Short { .. }) => return Flag::Short { spec: self, index },
+                None => {}
+            }
+        }
+        Flag::None { spec: self }
+    }
+
     /// Takes the first [`Flag`] instance that satisfies this specification from the raw arguments.
     pub fn take(self, args: &mut RawArgs) -> Flag {
-        let is_valid_flag_chars = args.metadata().is_valid_flag_chars;
         args.with_record_flag(|args| {
-            for (index, raw_arg) in args.raw_args_mut().iter_mut().enumerate() {
-                let Some(value) = &mut raw_arg.value else {
-                    continue;
-                };
-                if !value.starts_with('-') {
-                    continue;
-                }
+            let flag = self.take_once(args);
+            if flag.index().is_none() || !args.metadata().env_overrides_cli {
+                return flag;
+            }
+            let Some(env_flag) = self.take_env(args) else {
+                return flag;
+            };
+            // The environment variable takes precedence, so the other occurrences are also ignored.
+            while self.take_once(args).index().is_some() {}
+            env_flag
+        })
+    }
 
-                if value.starts_with("--") {
-                    if &value[2..] == self.name {
-                        raw_arg.value = None;
-                        return Flag::Long { spec: self, index };
-                    }
-                } else if !(is_valid_flag_chars)(&value[1..]) {
-                } else if let Some(i) = value
-                    .char_indices()
-                    .skip(1)
-                    .find_map(|(i, c)| (Some(c) == self.short).then_some(i))
-                {
+    fn take_once(self, args: &mut RawArgs) -> Flag {
+        let metadata = args.metadata();
+        let rivals = args.abbrev_rivals(self.long_names());
+        let window = self.search_window(args);
+        for (index, raw_arg) in args.raw_args_mut()[..window.end].iter_mut().enumerate() {
+            if index < window.start {
+                continue;
+            }
+            let Some(value) = &mut raw_arg.value else {
+                continue;
+            };
+            match self.match_raw_arg(&metadata, value, rivals.as_deref()) {
+                Some(FlagMatch::Long { abbreviation }) => {
+                    raw_arg.abbreviation = abbreviation;
+                    raw_arg.value = None;
+                    return Flag::Long { spec: self, index };
+                }
+                Some(FlagMatch::Short { cluster_at: None }) => {
+                    raw_arg.value = None;
+                    return Flag::Short { spec: self, index };
+                }
+                Some(FlagMatch::Short {
+                    cluster_at: Some(i),
+                }) => {
                     value.remove(i);
                     if value.len() == 1 {
                         raw_arg.value = None;
                     }
                     return Flag::Short { spec: self, index };
                 }
+                None => {}
             }
+        }
+
+        self.take_env(args).unwrap_or(Flag::None { spec: self })
+    }
+
+    /// Returns the range of raw argument indices searched for this flag.
+    fn search_window(self, args: &RawArgs) -> std::ops::Range<usize> {
+        let search_len = if self.global {
+            args.raw_args().len()
+        } else {
+            args.option_search_len()
+        };
+        args.index_window(self.after_index, self.before_index, search_len)
+    }
 
-            if self
-                .env
-                .is_some_and(|name| std::env::var(name).is_ok_and(|v| !v.is_empty()))
-            {
-                Flag::Env { spec: self }
-            } else {
-                Flag::None { spec: self }
+    /// Returns how `value` (an unconsumed raw argument) names this flag, if it does.
+    fn match_raw_arg(
+        self,
+        metadata: &Metadata,
+        value: &str,
+        rivals: Option<&[&str]>,
+    ) -> Option<FlagMatch> {
+        if let Some(name) = value.strip_prefix('/').filter(|_| metadata.slash_syntax) {
+            return match match_slash_name(name, self.long_names(), self.short_names())? {
+                true => Some(FlagMatch::Long { abbreviation: None }),
+                false => Some(FlagMatch::Short { cluster_at: None }),
+            };
+        }
+        if !value.starts_with('-') {
+            return None;
+        }
+        if metadata.negative_numbers_as_args && is_negative_number(value) {
+            return None;
+        }
+
+        if let Some(name) = value.strip_prefix("--") {
+            match strip_long_name(name, self.long_names(), rivals)? {
+                ("", abbreviation) => Some(FlagMatch::Long {
+                    abbreviation: abbreviation.map(|name| name.to_owned()),
+                }),
+                _ => None,
             }
-        })
+        } else if !(metadata.is_valid_flag_chars)(&value[1..]) {
+            None
+        } else {
+            value
+                .char_indices()
+                .skip(1)
+                .find_map(|(i, c)| self.short_names().any(|s| s == c).then_some(i))
+                .map(|i| FlagMatch::Short {
+                    cluster_at: Some(i),
+                })
+        }
+    }
+
+    /// Returns `None` if the environment variable is not set.
+    fn take_env(self, args: &RawArgs) -> Option<Flag> {
+        let value = self
+            .env_name(&args.metadata())
+            .and_then(|name| args.env_var(&name))?;
+        if self
+            .falsey_env_values
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case(&value))
+        {
+            Some(Flag::None { spec: self })
+        } else {
+            Some(Flag::Env { spec: self })
+        }
     }
 
     /// Similar to [`FlagSpec::take()`], but updates the help-related metadata of `args` when the flag is present.
@@ -121,6 +438,64 @@ impl FlagSpec {
         }
         flag
     }
+
+    /// Similar to [`FlagSpec::take()`], but returns the version text to print when the flag is present.
+    ///
+    /// The text is `"{app_name} {version}\n"`, where `version` is [`RawArgs::cmd_version()`] if any,
+    /// or [`Metadata::app_version`](crate::Metadata::app_version) otherwise.
+    /// If the version is empty, only the application name is included.
+    ///
+    /// The long name of the flag is taken from [`Metadata::version_flag_name`](crate::Metadata::version_flag_name).
+    /// If it is `None`, the version flag is disabled and this method always returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["myapp", "--version"].iter().map(|a| a.to_string()));
+    /// args.metadata_mut().app_name = "myapp";
+    /// args.metadata_mut().app_version = "0.1.0";
+    /// let version = noargs::VERSION_FLAG.take_version(&mut args);
+    /// assert_eq!(version.as_deref(), Some("myapp 0.1.0\n"));
+    /// ```
+    pub fn take_version(self, args: &mut RawArgs) -> Option<String> {
+        let name = args.metadata().version_flag_name?;
+        let flag = Self { name, ..self };
+        if !flag.take(args).is_present() {
+            return None;
+        }
+        let metadata = args.metadata();
+        let version = args.cmd_version().unwrap_or(metadata.app_version);
+        if version.is_empty() {
+            Some(format!("{}\n", metadata.app_name))
+        } else {
+            Some(format!("{} {version}\n", metadata.app_name))
+        }
+    }
+}
+
+impl FlagSpec {
+    /// Returns [`FlagSpec::env`], or the name derived by [`FlagSpec::env_auto`].
+    pub(crate) fn env_name(&self, metadata: &Metadata) -> Option<Cow<'static, str>> {
+        args::env_name(self.env, self.env_auto, self.name, metadata)
+    }
+
+    pub(crate) fn long_names(&self) -> impl Clone + Iterator<Item = &'static str> {
+        let name = (!self.name.is_empty()).then_some(self.name);
+        name.into_iter().chain(self.aliases.iter().copied())
+    }
+
+    pub(crate) fn short_names(&self) -> impl Iterator<Item = char> {
+        self.short
+            .into_iter()
+            .chain(self.short_aliases.iter().copied())
+    }
+
+    pub(crate) fn display_name(&self) -> String {
+        match self.short {
+            Some(short) if self.name.is_empty() => format!("-{short}"),
+            _ => format!("--{}", self.name),
+        }
+    }
 }
 
 impl Default for FlagSpec {
@@ -139,7 +514,23 @@ pub enum Flag {
     None { spec: FlagSpec },
 }
 
+/// Where a present [`Flag`] comes from (see [`Flag::source()`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlagSource {
+    /// The flag is given on the command line.
+    CommandLine,
+
+    /// The flag is set via the environment variable.
+    Env,
+}
+
 impl Flag {
+    /// Returns `true` if this flag was taken by `spec`, comparing [`FlagSpec::id()`] instead of the whole specification
+    /// (see [`Opt::is()`](crate::Opt::is)).
+    pub fn is(self, spec: FlagSpec) -> bool {
+        self.spec().id() == spec.id()
+    }
+
     /// Returns the specification of this flag.
     pub fn spec(self) -> FlagSpec {
         match self {
@@ -155,11 +546,37 @@ impl Flag {
         !matches!(self, Flag::None { .. })
     }
 
+    /// Returns `true` if this flag is given on the command line.
+    ///
+    /// This is a shorthand for `self.source() == Some(FlagSource::CommandLine)`.
+    pub fn is_from_cli(self) -> bool {
+        matches!(self, Flag::Long { .. } | Flag::Short { .. })
+    }
+
+    /// Returns `true` if this flag is set via the environment variable.
+    ///
+    /// This is a shorthand for `self.source() == Some(FlagSource::Env)`.
+    pub fn is_env(self) -> bool {
+        matches!(self, Flag::Env { .. })
+    }
+
     /// Returns `Some(self)` if this flag is present.
     pub fn present(self) -> Option<Self> {
         self.is_present().then_some(self)
     }
 
+    /// Returns where this flag comes from, or `None` if this flag is not present.
+    ///
+    /// If a flag is given on the command line and its environment variable is also set,
+    /// the command line wins unless [`Metadata::env_overrides_cli`](crate::Metadata::env_overrides_cli) is `true`.
+    pub fn source(self) -> Option<FlagSource> {
+        match self {
+            Flag::Long { .. } | Flag::Short { .. } => Some(FlagSource::CommandLine),
+            Flag::Env { .. } => Some(FlagSource::Env),
+            Flag::None { .. } => None,
+        }
+    }
+
     /// Returns the index at which the raw value associated with this flag was located in [`RawArgs`].
     pub fn index(self) -> Option<usize> {
         match self {
@@ -169,6 +586,18 @@ impl Flag {
     }
 }
 
+/// How a raw argument names a flag (see `FlagSpec::match_raw_arg()`).
+#[derive(Debug)]
+enum FlagMatch {
+    Long {
+        abbreviation: Option<String>,
+    },
+    Short {
+        /// Byte offset of the short name in a cluster of short flags (e.g., `2` for `-xv`).
+        cluster_at: Option<usize>,
+    },
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::LazyLock;
@@ -198,6 +627,46 @@ mod tests {
     }
 
     #[test]
+    fn take_version() {
+        let mut args = test_args(&["test", "--version"]);
+        args.metadata_mut().app_name = "test";
+        assert_eq!(
+            crate::VERSION_FLAG.take_version(&mut args).as_deref(),
+            Some("test\n")
+        );
+        assert_eq!(crate::VERSION_FLAG.take_version(&mut args), None);
+
+        let mut args = test_args(&["test", "sub", "--version"]);
+        args.metadata_mut().app_name = "test";
+        args.metadata_mut().app_version = "0.1.0";
+        assert_eq!(
+            crate::VERSION_FLAG
+                .take_version(&mut args.clone())
+                .as_deref(),
+            Some("test 0.1.0\n")
+        );
+        crate::cmd("sub").version("1.2.0").take(&mut args);
+        assert_eq!(
+            crate::VERSION_FLAG.take_version(&mut args).as_deref(),
+            Some("test 1.2.0\n")
+        );
+
+        let mut args = test_args(&["test", "-V", "--show-version"]);
+        args.metadata_mut().app_name = "test";
+        args.metadata_mut().version_flag_name = Some("show-version");
+        let flag = crate::VERSION_FLAG.short('V');
+        assert_eq!(flag.take_version(&mut args).as_deref(), Some("test\n"));
+        assert_eq!(flag.take_version(&mut args).as_deref(), Some("test\n"));
+        assert_eq!(flag.take_version(&mut args), None);
+
+        let mut args = test_args(&["test", "--version"]);
+        args.metadata_mut().version_flag_name = None;
+        assert_eq!(crate::VERSION_FLAG.take_version(&mut args), None);
+        assert!(args.remaining_args().next().is_some());
+    }
+
+    #[test]
+    #[allow(clippy::explicit_auto_deref)]
     fn doc_with_format_macro() {
         crate::flag("test-flag").short('t').doc({
             static DOC_STRING: LazyLock<String> = LazyLock::new(|| {
@@ -224,6 +693,197 @@ mod tests {
         assert!(matches!(flag.take(&mut args), Flag::Env { .. }));
     }
 
+    #[test]
+    fn env_overrides_cli() {
+        let flag = crate::flag("foo").env("TEST_ENV_FLAG_OVERRIDE");
+        let opt = crate::opt("bar").env("TEST_ENV_OPT_OVERRIDE");
+        unsafe {
+            std::env::set_var("TEST_ENV_FLAG_OVERRIDE", "1");
+            std::env::set_var("TEST_ENV_OPT_OVERRIDE", "env");
+        }
+
+        let mut args = test_args(&["test", "--foo", "--bar=cli"]);
+        assert_eq!(flag.take(&mut args).source(), Some(FlagSource::CommandLine));
+        assert_eq!(opt.take(&mut args).value(), "cli");
+        assert!(flag.take(&mut args).is_env());
+        assert!(opt.take(&mut args).is_env());
+
+        let mut args = test_args(&["test", "--foo", "--bar=cli", "--foo", "--bar=cli"]);
+        args.metadata_mut().env_overrides_cli = true;
+        assert_eq!(flag.take(&mut args).source(), Some(FlagSource::Env));
+        assert_eq!(opt.take(&mut args).value(), "env");
+        assert!(args.finish().is_ok());
+
+        let mut args = test_args(&["test", "--foo"]);
+        args.metadata_mut().env_overrides_cli = true;
+        assert_eq!(
+            flag.falsey_env_values(&["1"]).take(&mut args).source(),
+            None
+        );
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn falsey_env_flag() {
+        let mut args = test_args(&["test"]);
+        let flag = crate::flag("foo").env("TEST_ENV_FLAG_FALSEY");
+        unsafe {
+            std::env::set_var("TEST_ENV_FLAG_FALSEY", "Off");
+        }
+        assert!(matches!(flag.take(&mut args), Flag::Env { .. }));
+
+        let flag = flag.falsey_env_values(crate::FALSEY_ENV_VALUES);
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+
+        unsafe {
+            std::env::set_var("TEST_ENV_FLAG_FALSEY", "on");
+        }
+        assert!(matches!(flag.take(&mut args), Flag::Env { .. }));
+    }
+
+    #[test]
+    fn flag_index_window() {
+        let mut args = test_args(&["test", "-x", "run", "-x", "--", "-x"]);
+        let flag = crate::flag("").short('x').after_index(2).before_index(4);
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 3, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+    }
+
+    #[test]
+    fn short_only_flag() {
+        let mut args = test_args(&["test", "--", "-x"]);
+        let flag = crate::flag("").short('x');
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 2, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+        assert_eq!(args.remaining_args().next(), Some((1, "--")));
+    }
+
+    #[test]
+    fn flag_aliases() {
+        let mut args = test_args(&["test", "--old-name", "-o", "--new-name"]);
+        let flag = crate::flag("new-name")
+            .short('n')
+            .aliases(&["old-name"])
+            .short_aliases(&['o']);
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 1, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 2, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 3, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+    }
+
+    #[test]
+    fn abbreviated_flag() {
+        let mut args = test_args(&["test", "--verb"]);
+        assert!(matches!(
+            crate::flag("verbose").take(&mut args),
+            Flag::None { .. }
+        ));
+
+        let mut args = test_args(&["test", "--verb", "--qu", "--qu"]);
+        args.metadata_mut().allow_abbrev = true;
+        let flag = crate::flag("verbose");
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 1, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+
+        // `--qu` is also a prefix of the already taken `--quux`.
+        let flag = crate::flag("quux");
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 2, .. }));
+        let flag = crate::flag("quiet");
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+        assert_eq!(args.remaining_args().next(), Some((3, "--qu")));
+    }
+
+    #[test]
+    fn take_all() {
+        let mut args = test_args(&["test", "-vv", "--verbose"]);
+        let flag = crate::flag("verbose").short('v');
+        assert_eq!(flag.take_all(&mut args).len(), 3);
+        assert!(flag.take_all(&mut args).is_empty());
+    }
+
+    #[test]
+    fn peek_flag() {
+        let mut args = test_args(&["test", "--foo", "-v"]);
+        args.metadata_mut().strict_takes = true;
+        let flag = crate::flag("foo");
+        assert!(matches!(flag.peek(&args), Flag::Long { index: 1, .. }));
+        assert!(matches!(flag.peek(&args), Flag::Long { index: 1, .. }));
+        assert!(!crate::flag("bar").peek(&args).is_present());
+        assert!(matches!(
+            crate::opt("").short('v').peek(&args),
+            crate::Opt::MissingValue { .. }
+        ));
+
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 1, .. }));
+        assert!(!flag.peek(&args).is_present());
+        unsafe {
+            std::env::set_var("TEST_ENV_FLAG_PEEK", "1");
+        }
+        assert!(!flag.env("TEST_ENV_FLAG_PEEK").peek(&args).is_present());
+        assert!(args.warnings().is_empty());
+        assert_eq!(args.remaining_args().count(), 1);
+    }
+
+    #[test]
+    fn strict_takes() {
+        let mut args = test_args(&["test", "-vv", "--quiet"]);
+        args.metadata_mut().strict_takes = true;
+        let quiet = crate::flag("quiet");
+        let verbose = crate::flag("verbose").short('v');
+        assert!(quiet.take(&mut args).is_present());
+        assert!(!quiet.take(&mut args).is_present());
+        assert_eq!(verbose.take_all(&mut args).len(), 2);
+        assert!(!verbose.repeatable().take(&mut args).is_present());
+        crate::opt("out").take(&mut args);
+        assert_eq!(
+            args.warnings(),
+            ["'--quiet' is taken more than once (use `repeatable()` if this is intended)"]
+        );
+
+        args.metadata_mut().strict_takes = false;
+        quiet.take(&mut args);
+        assert_eq!(args.warnings().len(), 1);
+
+        let mut args = test_args(&["test", "-o", "a.txt"]);
+        args.metadata_mut().strict_takes = true;
+        assert!(crate::opt("output").short('o').take(&mut args).is_present());
+        crate::opt("output").default("-").take(&mut args);
+        crate::flag("overwrite").short('o').take(&mut args);
+        crate::flag("overwrite").short('o').take(&mut args);
+        crate::flag("verbose").aliases(&["output"]).take(&mut args);
+        assert_eq!(
+            args.warnings(),
+            [
+                "'-o' is defined by both '--output' and '--overwrite'",
+                "'--overwrite' is taken more than once (use `repeatable()` if this is intended)",
+                "'--output' is defined by both '--output' and '--verbose'"
+            ]
+        );
+    }
+
+    #[test]
+    fn slash_syntax_flag() {
+        let mut args = test_args(&["test", "/verbose", "/v", "/usr"]);
+        let flag = crate::flag("verbose").short('v');
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+
+        args.metadata_mut().slash_syntax = true;
+        assert!(matches!(flag.take(&mut args), Flag::Long { index: 1, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 2, .. }));
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+    }
+
+    #[test]
+    fn negative_number_flag() {
+        let mut args = test_args(&["test", "-1", "-1"]);
+        let flag = crate::flag("").short('1');
+        assert!(matches!(flag.take(&mut args), Flag::Short { index: 1, .. }));
+
+        args.metadata_mut().negative_numbers_as_args = true;
+        assert!(matches!(flag.take(&mut args), Flag::None { .. }));
+        assert_eq!(crate::arg("<N>").take(&mut args).value(), "-1");
+    }
+
     fn test_args(raw_args: &[&str]) -> RawArgs {
         RawArgs::new(raw_args.iter().map(|a| a.to_string()))
     }
@@ -38,6 +38,10 @@ impl Formatter {
         }
     }
 
+    pub fn line_count(&self) -> usize {
+        self.text.matches('\n').count()
+    }
+
     pub fn finish(self) -> String {
         self.text
     }
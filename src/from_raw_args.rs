@@ -0,0 +1,101 @@
+use std::ops::ControlFlow;
+
+use crate::{Error, HELP_FLAG, RawArgs, Standard};
+
+/// Types that can be populated from [`RawArgs`] (see [`parse()`](crate::parse)).
+///
+/// # Examples
+///
+/// ```
+/// struct Args {
+///     port: u16,
+///     verbose: bool,
+/// }
+///
+/// impl noargs::FromRawArgs for Args {
+///     fn from_raw_args(args: &mut noargs::RawArgs) -> noargs::Result<Self> {
+///         Ok(Self {
+///             port: noargs::opt("port").default("8080").take(args).then(|o| o.value().parse())?,
+///             verbose: noargs::flag("verbose").take(args).is_present(),
+///         })
+///     }
+/// }
+/// ```
+#[allow(clippy::result_large_err)]
+pub trait FromRawArgs: Sized {
+    /// Takes the arguments of this type from `args`.
+    fn from_raw_args(args: &mut RawArgs) -> Result<Self, Error>;
+}
+
+impl FromRawArgs for Standard {
+    fn from_raw_args(args: &mut RawArgs) -> Result<Self, Error> {
+        Ok(Standard::take(args))
+    }
+}
+
+impl<A: FromRawArgs, B: FromRawArgs> FromRawArgs for (A, B) {
+    fn from_raw_args(args: &mut RawArgs) -> Result<Self, Error> {
+        Ok((A::from_raw_args(args)?, B::from_raw_args(args)?))
+    }
+}
+
+/// Returns `ControlFlow::Break(help_text)` if help is requested.
+#[allow(clippy::result_large_err)]
+pub(crate) fn parse_raw_args<T: FromRawArgs>(
+    mut args: RawArgs,
+) -> Result<ControlFlow<String, T>, Error> {
+    HELP_FLAG.take_help(&mut args);
+    let value = T::from_raw_args(&mut args)?;
+    match args.finish()? {
+        Some(help) => Ok(ControlFlow::Break(help)),
+        None => Ok(ControlFlow::Continue(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Args {
+        port: u16,
+    }
+
+    impl FromRawArgs for Args {
+        fn from_raw_args(args: &mut RawArgs) -> Result<Self, Error> {
+            Ok(Self {
+                port: crate::opt("port")
+                    .default("8080")
+                    .take(args)
+                    .then(|o| o.value().parse())?,
+            })
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let (standard, args) =
+            parse_raw_args::<(Standard, Args)>(test_args(&["test", "-v", "--port=80"]))
+                .expect("ok")
+                .continue_value()
+                .expect("continue");
+        assert_eq!(standard.verbose, 1);
+        assert_eq!(args, Args { port: 80 });
+
+        let help = parse_raw_args::<Args>(test_args(&["test", "--port=foo", "-h"]))
+            .expect("ok")
+            .break_value()
+            .expect("break");
+        assert!(help.starts_with("Usage:"));
+
+        let e = parse_raw_args::<Args>(test_args(&["test", "--foo"])).expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "unexpected argument '--foo' found\n\nTry '--help' for more information."
+        );
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
@@ -0,0 +1,430 @@
+use std::sync::OnceLock;
+
+use crate::{
+    FlagSpec, OptSpec, RawArgs,
+    args::{Taken, env_case},
+};
+
+/// A group of option and flag specifications that can be taken with a name prefix.
+///
+/// This is useful for defining common specifications once (e.g., in a shared library crate)
+/// and taking them multiple times for different purposes (e.g., `--db-host` and `--cache-host`).
+///
+/// [`SpecGroup::build()`] makes a [`PrefixedGroup`] that owns the prefixed names,
+/// and the specs retrieved from it via [`PrefixedGroup::opt()`] or [`PrefixedGroup::flag()`] are renamed as follows:
+/// - The long name and aliases become `{prefix}{name}` (e.g., `--db-host`)
+/// - The environment variable name becomes `{env_prefix}{env}` (e.g., `APP_DB_HOST`)
+/// - The short names are removed to avoid conflicts between groups
+///
+/// If neither prefix is set, the specifications are returned as they are.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::OnceLock;
+///
+/// use noargs::{PrefixedGroup, SpecGroup};
+///
+/// // Shared definitions.
+/// const CONNECTION: SpecGroup = SpecGroup::new(
+///     &[
+///         noargs::opt("host").short('h').env("HOST").default("localhost"),
+///         noargs::opt("port").short('p').env("PORT").default("80"),
+///     ],
+///     &[noargs::flag("tls").env("TLS")],
+/// );
+///
+/// static DB: OnceLock<PrefixedGroup> = OnceLock::new();
+/// static CACHE: OnceLock<PrefixedGroup> = OnceLock::new();
+///
+/// let mut args = noargs::RawArgs::new(
+///     ["app", "--db-host=db.local", "--cache-tls"].iter().map(|a| a.to_string()),
+/// );
+/// let db = DB.get_or_init(|| CONNECTION.with_prefix("db-").with_env_prefix("APP_DB_").build());
+/// let cache = CACHE.get_or_init(|| CONNECTION.with_prefix("cache-").build());
+///
+/// assert_eq!(db.opt("host").take(&mut args).value(), "db.local");
+/// assert_eq!(db.opt("host").env, Some("APP_DB_HOST"));
+/// assert_eq!(cache.opt("host").take(&mut args).value(), "localhost");
+/// assert_eq!(cache.opt("host").env, Some("CACHE_HOST"));
+/// assert!(cache.flag("tls").take(&mut args).is_present());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpecGroup {
+    /// Option specifications.
+    pub opts: &'static [OptSpec],
+
+    /// Flag specifications.
+    pub flags: &'static [FlagSpec],
+
+    /// Prefix of long names (e.g., `db-`).
+    pub prefix: &'static str,
+
+    /// Prefix of environment variable names (e.g., `APP_DB_`).
+    ///
+    /// If `None`, the upper snake case of [`SpecGroup::prefix`] is used (e.g., `DB_` for `db-`).
+    pub env_prefix: Option<&'static str>,
+}
+
+impl SpecGroup {
+    /// Makes a [`SpecGroup`] instance without prefixes.
+    pub const fn new(opts: &'static [OptSpec], flags: &'static [FlagSpec]) -> Self {
+        Self {
+            opts,
+            flags,
+            prefix: "",
+            env_prefix: None,
+        }
+    }
+
+    /// Updates the value of [`SpecGroup::prefix`].
+    pub const fn with_prefix(mut self, prefix: &'static str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Updates the value of [`SpecGroup::env_prefix`].
+    pub const fn with_env_prefix(mut self, prefix: &'static str) -> Self {
+        self.env_prefix = Some(prefix);
+        self
+    }
+
+    /// Makes a [`PrefixedGroup`] that owns the prefixed names of the specifications in this group.
+    pub fn build(self) -> PrefixedGroup {
+        let env_prefix = self
+            .env_prefix
+            .map_or_else(|| env_case(self.prefix), |p| p.to_owned());
+        let names = |name, env, stable_id, lists| {
+            PrefixedNames::new(self.prefix, &env_prefix, name, env, stable_id, lists)
+        };
+        PrefixedGroup {
+            opt_names: (self.opts.iter())
+                .map(|s| {
+                    names(
+                        s.name,
+                        s.env,
+                        s.stable_id,
+                        [s.aliases, s.conflicts_with, s.requires],
+                    )
+                })
+                .collect(),
+            flag_names: (self.flags.iter())
+                .map(|s| {
+                    names(
+                        s.name,
+                        s.env,
+                        s.stable_id,
+                        [s.aliases, s.conflicts_with, s.requires],
+                    )
+                })
+                .collect(),
+            group: self,
+        }
+    }
+
+    fn is_unprefixed(&self) -> bool {
+        self.prefix.is_empty() && self.env_prefix.is_none()
+    }
+}
+
+/// Specifications of a [`SpecGroup`] renamed with its prefixes.
+///
+/// The prefixed names are owned by this instance, and the specifications returned by its methods borrow them.
+/// Therefore, the methods require a `&'static` reference, which can be obtained by storing the instance in
+/// a `static` item (e.g., [`OnceLock`]) as shown in the [`SpecGroup`] example.
+#[derive(Debug)]
+pub struct PrefixedGroup {
+    group: SpecGroup,
+    opt_names: Vec<PrefixedNames>,
+    flag_names: Vec<PrefixedNames>,
+}
+
+impl PrefixedGroup {
+    /// Returns the prefixed option specification having the given (unprefixed) name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group does not contain an option named `name`.
+    pub fn opt(&'static self, name: &str) -> OptSpec {
+        let Some(i) = self.group.opts.iter().position(|spec| spec.name == name) else {
+            panic!("option '{name}' is not defined in the group");
+        };
+        self.rename_opt(i)
+    }
+
+    /// Returns the prefixed flag specification having the given (unprefixed) name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group does not contain a flag named `name`.
+    pub fn flag(&'static self, name: &str) -> FlagSpec {
+        let Some(i) = self.group.flags.iter().position(|spec| spec.name == name) else {
+            panic!("flag '{name}' is not defined in the group");
+        };
+        self.rename_flag(i)
+    }
+
+    /// Returns all the prefixed option specifications.
+    pub fn all_opts(&'static self) -> impl Iterator<Item = OptSpec> {
+        (0..self.group.opts.len()).map(|i| self.rename_opt(i))
+    }
+
+    /// Returns all the prefixed flag specifications.
+    pub fn all_flags(&'static self) -> impl Iterator<Item = FlagSpec> {
+        (0..self.group.flags.len()).map(|i| self.rename_flag(i))
+    }
+
+    fn rename_opt(&'static self, i: usize) -> OptSpec {
+        let spec = self.group.opts[i];
+        if self.group.is_unprefixed() {
+            return spec;
+        }
+        let names = &self.opt_names[i];
+        let [aliases, conflicts_with, requires] = names.lists();
+        OptSpec {
+            name: &names.name,
+            short: None,
+            env: names.env.as_deref(),
+            aliases,
+            conflicts_with,
+            requires,
+            short_aliases: &[],
+            stable_id: names.stable_id.as_deref(),
+            ..spec
+        }
+    }
+
+    fn rename_flag(&'static self, i: usize) -> FlagSpec {
+        let spec = self.group.flags[i];
+        if self.group.is_unprefixed() {
+            return spec;
+        }
+        let names = &self.flag_names[i];
+        let [aliases, conflicts_with, requires] = names.lists();
+        FlagSpec {
+            name: &names.name,
+            short: None,
+            env: names.env.as_deref(),
+            aliases,
+            conflicts_with,
+            requires,
+            short_aliases: &[],
+            stable_id: names.stable_id.as_deref(),
+            ..spec
+        }
+    }
+}
+
+/// Prefixed names of a specification in a [`PrefixedGroup`].
+#[derive(Debug)]
+struct PrefixedNames {
+    name: String,
+    env: Option<String>,
+    stable_id: Option<String>,
+
+    /// Aliases, `conflicts_with` and `requires`.
+    lists: [Vec<String>; 3],
+    list_refs: OnceLock<[Vec<&'static str>; 3]>,
+}
+
+impl PrefixedNames {
+    fn new(
+        prefix: &str,
+        env_prefix: &str,
+        name: &str,
+        env: Option<&str>,
+        stable_id: Option<&str>,
+        lists: [&[&str]; 3],
+    ) -> Self {
+        // An empty name (i.e., short-only spec) is kept as is.
+        let prefixed = |name: &str| {
+            if name.is_empty() {
+                String::new()
+            } else {
+                format!("{prefix}{name}")
+            }
+        };
+        Self {
+            name: prefixed(name),
+            env: env.map(|env| format!("{env_prefix}{env}")),
+            stable_id: stable_id.map(prefixed),
+            lists: lists.map(|names| names.iter().map(|name| prefixed(name)).collect()),
+            list_refs: OnceLock::new(),
+        }
+    }
+
+    fn lists(&'static self) -> [&'static [&'static str]; 3] {
+        let refs = self.list_refs.get_or_init(|| {
+            let [a, b, c] = &self.lists;
+            [a, b, c].map(|names| names.iter().map(|name| name.as_str()).collect())
+        });
+        [&refs[0], &refs[1], &refs[2]]
+    }
+}
+
+/// A group of options and flags that are validated together by [`RawArgs::finish()`].
+///
+/// By default, at most one member of the group can be given.
+/// Members are referred to by their long names, and are considered as given if they appear on the command line
+/// or are set via their environment variables.
+/// The group is shown in the usage line of the help text (e.g., `(--stdin | --file <PATH>)`).
+///
+/// # Examples
+///
+/// ```
+/// let mut args = noargs::RawArgs::new(["app", "--file", "a.txt"].iter().map(|a| a.to_string()));
+/// let stdin = noargs::flag("stdin").take(&mut args);
+/// let file = noargs::opt("file").ty("PATH").take(&mut args);
+/// noargs::group(&["stdin", "file"]).exactly_one().take(&mut args);
+///
+/// assert!(!stdin.is_present());
+/// assert_eq!(file.value(), "a.txt");
+/// assert!(args.finish().is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArgGroup {
+    /// Long names of the member options and flags.
+    ///
+    /// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+    pub names: &'static [&'static str],
+
+    /// If `true`, at least one member must be given.
+    pub required: bool,
+
+    /// If `true`, multiple members can be given at the same time.
+    pub multiple: bool,
+}
+
+impl ArgGroup {
+    /// Makes an [`ArgGroup`] instance whose members are mutually exclusive.
+    pub const fn new(names: &'static [&'static str]) -> Self {
+        Self {
+            names,
+            required: false,
+            multiple: false,
+        }
+    }
+
+    /// Requires exactly one member to be given.
+    pub const fn exactly_one(mut self) -> Self {
+        self.required = true;
+        self.multiple = false;
+        self
+    }
+
+    /// Requires at least one member to be given.
+    pub const fn at_least_one(mut self) -> Self {
+        self.required = true;
+        self.multiple = true;
+        self
+    }
+
+    /// Registers this group to be validated by [`RawArgs::finish()`] and shown in the help text.
+    ///
+    /// The member options and flags can be taken before or after calling this method.
+    pub fn take(self, args: &mut RawArgs) {
+        args.push_taken(Taken::Group(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Flag, RawArgs, flag, opt};
+
+    use super::*;
+
+    const GROUP: SpecGroup = SpecGroup::new(
+        &[opt("host").short('h').aliases(&["server"]).env("HOST")],
+        &[flag("tls").short('t')],
+    );
+
+    static DB: OnceLock<PrefixedGroup> = OnceLock::new();
+    static PLAIN: OnceLock<PrefixedGroup> = OnceLock::new();
+
+    #[test]
+    fn prefixed_specs() {
+        let db = DB.get_or_init(|| GROUP.with_prefix("db-").build());
+        let spec = db.opt("host");
+        assert_eq!(spec.name, "db-host");
+        assert_eq!(spec.short, None);
+        assert_eq!(spec.aliases, ["db-server"]);
+        assert_eq!(spec.env, Some("DB_HOST"));
+
+        let mut args = test_args(&["test", "--db-server=a", "--db-tls", "-t"]);
+        assert_eq!(spec.take(&mut args).value(), "a");
+        assert!(matches!(
+            db.flag("tls").take(&mut args),
+            Flag::Long { index: 2, .. }
+        ));
+        assert!(matches!(
+            PLAIN
+                .get_or_init(|| GROUP.build())
+                .flag("tls")
+                .take(&mut args),
+            Flag::Short { index: 3, .. }
+        ));
+        assert_eq!(db.all_opts().count(), 1);
+        assert_eq!(db.all_flags().count(), 1);
+    }
+
+    #[test]
+    fn arg_group() {
+        let group = crate::group(&["stdin", "file"]);
+        let parse = |raw_args: &[&str], group: ArgGroup| {
+            let mut args = test_args(raw_args);
+            args.metadata_mut().help_flag_name = None;
+            flag("stdin").take(&mut args);
+            opt("file").take(&mut args);
+            group.take(&mut args);
+            args.finish().map_err(|e| e.to_string(false))
+        };
+
+        assert!(parse(&["test"], group).is_ok());
+        assert!(parse(&["test", "--stdin"], group).is_ok());
+        assert_eq!(
+            parse(&["test", "--stdin", "--file=a"], group),
+            Err("argument '--file' cannot be used with '--stdin'".to_owned())
+        );
+
+        let group = group.exactly_one();
+        assert!(parse(&["test", "--file=a"], group).is_ok());
+        assert_eq!(
+            parse(&["test"], group),
+            Err("one of '--stdin' or '--file' must be given".to_owned())
+        );
+
+        let group = group.at_least_one();
+        assert!(parse(&["test", "--stdin", "--file=a"], group).is_ok());
+        assert!(parse(&["test"], group).is_err());
+
+        // Short-only member.
+        let group = crate::group(&["stdin", "f"]).exactly_one();
+        let parse = |raw_args: &[&str]| {
+            let mut args = test_args(raw_args);
+            args.metadata_mut().help_flag_name = None;
+            flag("stdin").take(&mut args);
+            opt("").short('f').take(&mut args);
+            group.take(&mut args);
+            args.finish().map_err(|e| e.to_string(false))
+        };
+        assert!(parse(&["test", "-f", "a"]).is_ok());
+        assert_eq!(
+            parse(&["test", "--stdin", "-f", "a"]),
+            Err("argument '-f' cannot be used with '--stdin'".to_owned())
+        );
+        assert_eq!(
+            parse(&["test"]),
+            Err("one of '--stdin' or '-f' must be given".to_owned())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "option 'port' is not defined in the group")]
+    fn undefined_opt() {
+        PLAIN.get_or_init(|| GROUP.build()).opt("port");
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
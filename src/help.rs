@@ -1,44 +1,131 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::{Mutex, OnceLock},
+};
 
 use crate::{
-    args::{RawArgs, Taken},
+    ArgGroup, ArgSpec, Metadata,
+    args::{RawArgs, Taken, split_command_line},
     formatter::Formatter,
 };
 
+static CACHE: OnceLock<Mutex<HashMap<HelpCacheKey, String>>> = OnceLock::new();
+
+/// Identifies the help text of a [`RawArgs`] instance.
+///
+/// The log is hashed by the specifications taken (and the presence of subcommands),
+/// so the key does not depend on the raw argument values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HelpCacheKey {
+    pub metadata: Metadata,
+    pub log_hash: u64,
+    pub is_terminal: bool,
+}
+
+impl HelpCacheKey {
+    pub fn new(args: &RawArgs, is_terminal: bool) -> Self {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for entry in args.log() {
+            std::mem::discriminant(entry).hash(&mut hasher);
+            match entry {
+                Taken::Arg(arg) => arg.spec().hash(&mut hasher),
+                Taken::Opt(opt) => opt.spec().hash(&mut hasher),
+                Taken::Flag(flag) => flag.spec().hash(&mut hasher),
+                Taken::Cmd(cmd) => (cmd.spec(), cmd.is_present()).hash(&mut hasher),
+                Taken::Sections(names) => names.hash(&mut hasher),
+                Taken::Group(group) => group.hash(&mut hasher),
+            }
+        }
+        Self {
+            metadata: args.metadata(),
+            log_hash: hasher.finish(),
+            is_terminal,
+        }
+    }
+}
+
+fn cache() -> &'static Mutex<HashMap<HelpCacheKey, String>> {
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// Returns the cached help text for `key`, or builds and caches it with `build`.
+pub fn cached<F>(key: HelpCacheKey, build: F) -> String
+where
+    F: FnOnce() -> String,
+{
+    if let Some(help) = cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return help.clone();
+    }
+    let help = build();
+    cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, help.clone());
+    help
+}
+
+/// Clears the help texts cached by [`Metadata::cache_help`](crate::Metadata::cache_help).
+pub fn clear_help_cache() {
+    cache().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
 #[derive(Debug)]
 pub struct HelpBuilder<'a> {
     args: &'a RawArgs,
     log: Vec<Taken>,
     fmt: Formatter,
     cmd_name: Option<&'static str>,
+    cmd_path: Vec<&'static str>,
+    man: bool,
 }
 
 impl<'a> HelpBuilder<'a> {
     pub fn new(args: &'a RawArgs, is_terminal: bool) -> Self {
         let mut this = Self {
             args,
-            log: args.log().to_vec(),
+            log: args
+                .log()
+                .iter()
+                .filter(|entry| !entry.is_hidden())
+                .cloned()
+                .collect(),
             fmt: Formatter::new(is_terminal),
             cmd_name: None,
+            cmd_path: Vec::new(),
+            man: false,
         };
 
         // Subcommand handling.
-        let Some((name, log_index)) = this.log.iter().enumerate().rev().find_map(|(i, entry)| {
-            if let Taken::Cmd(cmd) = entry
-                && cmd.present().is_some()
-            {
-                return Some((cmd.spec().name, i));
-            }
-            None
-        }) else {
+        let Some((name, log_index)) =
+            this.log
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(i, entry)| match entry {
+                    Taken::Cmd(cmd) if cmd.is_present() => Some((cmd.spec().name, i)),
+                    _ => None,
+                })
+        else {
             return this;
         };
         this.cmd_name = Some(name);
+        this.cmd_path = this
+            .log
+            .iter()
+            .filter_map(|entry| match entry {
+                Taken::Cmd(cmd) if cmd.is_present() => Some(cmd.spec().name),
+                _ => None,
+            })
+            .collect();
 
         let mut log = Vec::new();
         for (i, entry) in this.log.into_iter().enumerate() {
             let mut retain = true;
-            if matches!(entry, Taken::Arg(_) | Taken::Cmd(_)) {
+            if matches!(
+                entry,
+                Taken::Arg(_) | Taken::Cmd(_) | Taken::Sections(_) | Taken::Group(_)
+            ) {
                 retain = i > log_index;
             }
             if retain {
@@ -51,7 +138,7 @@ impl<'a> HelpBuilder<'a> {
     }
 
     fn is_full_mode(&self) -> bool {
-        self.args.metadata().full_help
+        self.man || self.args.metadata().full_help
     }
 
     fn doc_lines<'b>(&self, doc: &'b str) -> impl 'b + Iterator<Item = &'b str> {
@@ -66,6 +153,8 @@ impl<'a> HelpBuilder<'a> {
         self.build_commands();
         self.build_arguments();
         self.build_options();
+        self.build_footer();
+        self.build_after_help();
 
         let mut text = self.fmt.finish();
         if text.ends_with("\n\n") {
@@ -74,8 +163,8 @@ impl<'a> HelpBuilder<'a> {
         text
     }
 
-    fn build_description(&mut self) {
-        let description = if let Some(cmd_name) = self.cmd_name {
+    fn description(&self) -> &'static str {
+        if let Some(cmd_name) = self.cmd_name {
             // Use subcommand description when in subcommand context
             // Search in original args log, not filtered log
             self.args
@@ -94,8 +183,167 @@ impl<'a> HelpBuilder<'a> {
         } else {
             // Use app description for main command
             self.args.metadata().app_description
-        };
+        }
+    }
+
+    /// Builds a man page in roff format.
+    ///
+    /// Unlike the help text, all doc lines are included regardless of [`Metadata::full_help`](crate::Metadata::full_help).
+    pub fn build_man(mut self) -> String {
+        self.man = true;
+        let metadata = self.args.metadata();
+        let title = self.man_title();
+        let version = self.args.cmd_version().unwrap_or(metadata.app_version);
+
+        let mut man = format!(
+            ".TH \"{}\" \"{}\" \"{}\" \"{}\"\n",
+            roff_escape(&title.to_uppercase()),
+            roff_escape(metadata.man_section),
+            roff_escape(metadata.man_date),
+            roff_escape(format!("{title} {version}").trim_end())
+        );
+
+        let mut description = self.description().lines();
+        man.push_str(".SH NAME\n");
+        match description.next() {
+            Some(summary) => man.push_str(&format!(
+                "{} \\- {}\n",
+                roff_escape(&title),
+                roff_escape(summary)
+            )),
+            None => man.push_str(&format!("{}\n", roff_escape(&title))),
+        }
+
+        self.build_usage();
+        let usage = std::mem::replace(&mut self.fmt, Formatter::new(false)).finish();
+        let usage = usage.trim_end().trim_start_matches("Usage: ");
+        man.push_str(&format!(".SH SYNOPSIS\n{}\n", roff_escape(usage)));
+
+        let rest = description
+            .skip_while(|line| line.trim().is_empty())
+            .collect::<Vec<_>>();
+        if !rest.is_empty() {
+            man.push_str(".SH DESCRIPTION\n");
+            push_roff_lines(&mut man, rest.into_iter());
+        }
+
+        if self.has_subcommands() {
+            man.push_str(".SH COMMANDS\n");
+            for entry in &self.log {
+                let Taken::Cmd(cmd) = entry else {
+                    continue;
+                };
+                let cmd = cmd.spec();
+                man.push_str(&format!(".TP\n\\fB{}\\fR\n", roff_escape(cmd.name)));
+                push_roff_lines(&mut man, cmd.doc.lines());
+                if let Some(note) = cmd.deprecated {
+                    push_roff_lines(
+                        &mut man,
+                        [format!("[deprecated: {note}]").as_str()].into_iter(),
+                    );
+                }
+            }
+        }
+
+        if self.has_positional_args() {
+            man.push_str(".SH ARGUMENTS\n");
+            let mut known = HashSet::new();
+            for entry in &self.log {
+                let Taken::Arg(arg) = entry else {
+                    continue;
+                };
+                let arg = arg.spec();
+                if !known.insert(arg) {
+                    continue;
+                }
+                man.push_str(&format!(".TP\n\\fB{}\\fR\n", roff_escape(arg.name)));
+                push_roff_lines(&mut man, arg.doc.lines());
+                let notes = arg_notes(arg);
+                push_roff_lines(&mut man, notes.iter().map(|n| n.as_str()));
+            }
+        }
+
+        if self.has_options(true) {
+            man.push_str(".SH OPTIONS\n");
+            let mut known = HashSet::new();
+            for entry in &self.log {
+                let name = match entry {
+                    Taken::Opt(opt) => opt.spec().display_name(),
+                    Taken::Flag(flag) => flag.spec().display_name(),
+                    _ => continue,
+                };
+                if !known.insert(name) {
+                    continue;
+                }
+                let Some((doc, notes)) = self.option_details(entry) else {
+                    continue;
+                };
+                man.push_str(&format!(
+                    ".TP\n\\fB{}\\fR\n",
+                    roff_escape(&self.entry_name(entry))
+                ));
+                push_roff_lines(&mut man, doc.lines());
+                push_roff_lines(&mut man, notes.iter().map(|n| n.as_str()));
+            }
+        }
+
+        for (label, value) in self.footer() {
+            if label == "Version" {
+                // Already shown in the title line.
+                continue;
+            }
+            man.push_str(&format!(
+                ".SH {}\n{}\n",
+                label.to_uppercase(),
+                roff_escape(&value)
+            ));
+        }
+
+        let after_help = metadata.after_help;
+        if !after_help.is_empty() {
+            man.push_str(".SH NOTES\n");
+            push_roff_lines(&mut man, after_help.lines());
+        }
+
+        let see_also = self
+            .man_subcommands()
+            .into_iter()
+            .map(|name| format!("{title}-{name}({})", metadata.man_section))
+            .chain(metadata.man_see_also.iter().map(|entry| entry.to_string()))
+            .collect::<Vec<_>>();
+        if !see_also.is_empty() {
+            man.push_str(&format!(
+                ".SH SEE ALSO\n{}\n",
+                roff_escape(&see_also.join(", "))
+            ));
+        }
+
+        man
+    }
+
+    /// Returns the title of the man page (e.g., `myapp-start` for the `start` subcommand).
+    pub fn man_title(&self) -> String {
+        std::iter::once(self.args.metadata().app_name)
+            .chain(self.cmd_path.iter().copied())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
 
+    /// Returns the names of the subcommands that have their own man pages (see [`CmdSpec::man_page`](crate::CmdSpec::man_page)).
+    pub fn man_subcommands(&self) -> Vec<&'static str> {
+        self.log
+            .iter()
+            .filter_map(|entry| match entry {
+                Taken::Cmd(cmd) if cmd.spec().man_page && !cmd.spec().external => {
+                    Some(cmd.spec().name)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build_description(&mut self) {
+        let description = self.description();
         if description.is_empty() {
             return;
         }
@@ -113,8 +361,8 @@ impl<'a> HelpBuilder<'a> {
             self.fmt.bold(self.args.metadata().app_name),
         ));
 
-        if let Some(name) = self.cmd_name {
-            self.fmt.write(&format!(" ... {name}"));
+        if !self.cmd_path.is_empty() {
+            self.fmt.write(&format!(" {}", self.cmd_path.join(" ")));
         }
 
         // Required options.
@@ -126,7 +374,29 @@ impl<'a> HelpBuilder<'a> {
             if opt.example.is_none() {
                 continue;
             }
-            self.fmt.write(&format!(" --{} <{}>", opt.name, opt.ty));
+            self.fmt.write(&format!(
+                " {} {}",
+                opt.display_name(),
+                opt.value_placeholder()
+            ));
+        }
+
+        // Argument groups.
+        for entry in &self.log {
+            let Taken::Group(group) = entry else {
+                continue;
+            };
+            let members = group
+                .names
+                .iter()
+                .map(|name| self.group_member_usage(name))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            if group.required {
+                self.fmt.write(&format!(" ({members})"));
+            } else {
+                self.fmt.write(&format!(" [{members}]"));
+            }
         }
 
         // Other options.
@@ -153,26 +423,75 @@ impl<'a> HelpBuilder<'a> {
             self.fmt.write(" <COMMAND>");
         }
 
+        // Sections after `--` (or `Metadata::terminator`).
+        let sections = self.log.iter().find_map(|entry| match entry {
+            Taken::Sections(names) => Some(*names),
+            _ => None,
+        });
+        let terminator = self.args.metadata().terminator;
+        for name in sections.unwrap_or_default() {
+            self.fmt.write(&format!(" [{terminator} {name}]"));
+        }
+
         self.fmt.write("\n\n");
     }
 
+    fn group_member_usage(&self, name: &str) -> String {
+        self.log
+            .iter()
+            .find_map(|entry| match entry {
+                Taken::Opt(opt) if opt.spec().name == name => Some(format!(
+                    "{} {}",
+                    opt.spec().display_name(),
+                    opt.spec().value_placeholder()
+                )),
+                Taken::Flag(flag) if flag.spec().name == name => Some(flag.spec().display_name()),
+                _ => None,
+            })
+            .unwrap_or_else(|| format!("--{name}"))
+    }
+
     fn build_example(&mut self) {
-        if !self.has_examples() {
+        let examples = self.curated_examples();
+        if !self.has_examples() && examples.is_empty() {
             return;
         }
 
         self.fmt.write(&self.fmt.bold_underline("Example:\n"));
-        self.fmt
-            .write(&format!("  $ {}", self.args.metadata().app_name));
-
-        // [NOTE] Need to use `self.args.log()` instead of `self.log` here.
-        for entry in self.args.log() {
-            if let Some(example) = entry.example() {
-                self.fmt.write(&format!(" {}", example));
+        let app_name = self.args.metadata().app_name;
+        if self.has_examples() {
+            self.fmt.write(&format!("  $ {app_name}"));
+
+            // [NOTE] Need to use `self.args.log()` instead of `self.log` here.
+            for entry in self.args.log().iter().filter(|entry| !entry.is_hidden()) {
+                if let Some(example) = entry.example() {
+                    self.fmt.write(&format!(" {}", example));
+                }
             }
+            self.fmt.write("\n");
+        }
+        for example in examples {
+            self.fmt.write(&format!("  $ {app_name} {example}\n"));
         }
 
-        self.fmt.write("\n\n");
+        self.fmt.write("\n");
+    }
+
+    /// Returns the examples in [`Metadata::examples`](crate::Metadata::examples) that contain the subcommand path.
+    fn curated_examples(&self) -> Vec<&'static str> {
+        self.args
+            .metadata()
+            .examples
+            .iter()
+            .copied()
+            .filter(|example| {
+                let words = split_command_line(example).unwrap_or_default();
+                let mut words = words.iter();
+                self.cmd_path
+                    .iter()
+                    .all(|name| words.any(|word| word == name))
+            })
+            .collect()
     }
 
     fn calc_width_offset_newline<F>(&self, f: F) -> (usize, usize, &'static str)
@@ -218,6 +537,13 @@ impl<'a> HelpBuilder<'a> {
                 self.fmt
                     .write(&format!("{:offset$}{line}{newline}", "", offset = offset));
             }
+            if let Some(note) = cmd.deprecated {
+                self.fmt.write(&format!(
+                    "{:offset$}[deprecated: {note}]{newline}",
+                    "",
+                    offset = offset
+                ));
+            }
             self.fmt.write("\n");
         }
         if !self.is_full_mode() {
@@ -254,12 +580,9 @@ impl<'a> HelpBuilder<'a> {
                 self.fmt
                     .write(&format!("{:offset$}{line}{newline}", "", offset = offset));
             }
-            if let Some(default) = arg.default {
-                self.fmt.write(&format!(
-                    "{:offset$}[default: {default}]{newline}",
-                    "",
-                    offset = offset
-                ));
+            for note in arg_notes(arg) {
+                self.fmt
+                    .write(&format!("{:offset$}{note}{newline}", "", offset = offset));
             }
 
             self.fmt.write("\n");
@@ -273,17 +596,20 @@ impl<'a> HelpBuilder<'a> {
         match entry {
             Taken::Opt(opt) => {
                 let opt = opt.spec();
+                let value = opt.value_placeholder();
                 let name = match (opt.short, self.is_full_mode()) {
-                    (Some(short), false) => format!("-{short}, --{} <{}>", opt.name, opt.ty),
-                    (Some(short), true) => format!("--{}, -{short} <{}>", opt.name, opt.ty),
-                    (None, false) => format!("    --{} <{}>", opt.name, opt.ty),
-                    (None, true) => format!("--{} <{}>", opt.name, opt.ty),
+                    (Some(short), _) if opt.name.is_empty() => format!("-{short} {value}"),
+                    (Some(short), false) => format!("-{short}, --{} {value}", opt.name),
+                    (Some(short), true) => format!("--{}, -{short} {value}", opt.name),
+                    (None, false) => format!("    --{} {value}", opt.name),
+                    (None, true) => format!("--{} {value}", opt.name),
                 };
                 self.fmt.bold(&name).into_owned()
             }
             Taken::Flag(flag) => {
                 let flag = flag.spec();
                 let name = match (flag.short, self.is_full_mode()) {
+                    (Some(short), _) if flag.name.is_empty() => format!("-{short}"),
                     (Some(short), false) => format!("-{short}, --{}", flag.name),
                     (Some(short), true) => format!("--{}, -{short}", flag.name),
                     (None, false) => format!("    --{}", flag.name),
@@ -295,6 +621,7 @@ impl<'a> HelpBuilder<'a> {
                 format!("{}", self.fmt.bold(arg.spec().name))
             }
             Taken::Cmd(cmd) => self.fmt.bold(cmd.spec().name).into_owned(),
+            Taken::Sections(names) | Taken::Group(ArgGroup { names, .. }) => names.join(" "),
         }
     }
 
@@ -305,27 +632,36 @@ impl<'a> HelpBuilder<'a> {
 
         self.fmt.write(&self.fmt.bold_underline("Options:\n"));
 
-        let (width, offset, newline) =
-            self.calc_width_offset_newline(|e| matches!(e, Taken::Opt(_) | Taken::Flag(_)));
         let mut known = HashSet::new();
-        for entry in &self.log {
-            let name = entry.name();
-            let (doc, env, default) = match entry {
-                Taken::Opt(opt) => {
-                    let opt = opt.spec();
-                    (opt.doc, opt.env, opt.default)
-                }
-                Taken::Flag(flag) => {
-                    let flag = flag.spec();
-                    (flag.doc, flag.env, None)
-                }
-                _ => continue,
-            };
-
-            if known.contains(entry.name()) {
+        let mut entries = self
+            .log
+            .iter()
+            .filter(|entry| match entry {
+                Taken::Opt(opt) => known.insert(opt.spec().display_name()),
+                Taken::Flag(flag) => known.insert(flag.spec().display_name()),
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+        let total = entries.len();
+        if !self.is_full_mode() {
+            entries.retain(|entry| help_priority(entry) > 0);
+        }
+        let limit = self.summary_option_limit(entries.len(), total - entries.len());
+        if limit < entries.len() {
+            // Keep the most important options in the original order.
+            let mut indices = (0..entries.len()).collect::<Vec<_>>();
+            indices.sort_by_key(|&i| std::cmp::Reverse(help_priority(entries[i])));
+            indices.truncate(limit);
+            indices.sort_unstable();
+            entries = indices.into_iter().map(|i| entries[i]).collect();
+        }
+        let (width, offset, newline) = self.calc_width_offset_newline(|e| {
+            matches!(e, Taken::Opt(_) | Taken::Flag(_)) && entries.contains(&e)
+        });
+        for &entry in &entries {
+            let Some((doc, notes)) = self.option_details(entry) else {
                 continue;
-            }
-            known.insert(name);
+            };
 
             let name = self.entry_name(entry);
             self.fmt
@@ -334,28 +670,172 @@ impl<'a> HelpBuilder<'a> {
                 self.fmt
                     .write(&format!("{:offset$}{line}{newline}", "", offset = offset));
             }
-            if let Some(env) = env {
-                self.fmt.write(&format!(
-                    "{:offset$}[env: {env}]{newline}",
-                    "",
-                    offset = offset
-                ));
-            }
-            if let Some(default) = default {
-                self.fmt.write(&format!(
-                    "{:offset$}[default: {default}]{newline}",
-                    "",
-                    offset = offset
-                ));
+            for note in notes {
+                self.fmt
+                    .write(&format!("{:offset$}{note}{newline}", "", offset = offset));
             }
 
             self.fmt.write("\n");
         }
+        if entries.len() < total {
+            let rest = total - entries.len();
+            let hint = self
+                .args
+                .metadata()
+                .help_flag_name
+                .map_or(String::new(), |name| {
+                    format!(" (run '--{name}' to see all)")
+                });
+            let options = if rest == 1 { "option" } else { "options" };
+            self.fmt
+                .write(&format!("  ... {rest} more {options}{hint}\n"));
+        }
+        if !self.is_full_mode() {
+            self.fmt.write("\n");
+        }
+    }
+
+    /// Returns the doc and the bracketed notes (e.g., `[default: ...]`) of an option or flag entry.
+    fn option_details(&self, entry: &Taken) -> Option<(&'static str, Vec<String>)> {
+        let (doc, env, default, aliases, mut notes) = match entry {
+            Taken::Opt(opt) => {
+                let opt = opt.spec();
+                let aliases = if opt.hide_aliases {
+                    Vec::new()
+                } else {
+                    alias_names(opt.aliases, opt.short_aliases)
+                };
+                let mut annotations = Vec::new();
+                if !opt.choices.is_empty() && self.is_full_mode() {
+                    annotations.push(format!("[possible values: {}]", opt.choices.join(", ")));
+                }
+                if let Some((min, max)) = opt.range.filter(|_| self.is_full_mode()) {
+                    annotations.push(format!("[range: {min}-{max}]"));
+                }
+                if let Some(delimiter) = opt.delimiter {
+                    annotations.push(format!("[delimiter: '{delimiter}']"));
+                }
+                if let Some(note) = opt.deprecated {
+                    annotations.push(format!("[deprecated: {note}]"));
+                }
+                if opt.env_required {
+                    annotations.push("[env only]".to_owned());
+                }
+                (
+                    opt.doc,
+                    opt.env_name(&self.args.metadata()),
+                    opt.display_default(),
+                    aliases,
+                    annotations,
+                )
+            }
+            Taken::Flag(flag) => {
+                let flag = flag.spec();
+                let aliases = if flag.hide_aliases {
+                    Vec::new()
+                } else {
+                    alias_names(flag.aliases, flag.short_aliases)
+                };
+                let annotations = flag
+                    .deprecated
+                    .map(|note| format!("[deprecated: {note}]"))
+                    .into_iter()
+                    .collect();
+                (
+                    flag.doc,
+                    flag.env_name(&self.args.metadata()),
+                    None,
+                    aliases,
+                    annotations,
+                )
+            }
+            _ => return None,
+        };
+        if !aliases.is_empty() {
+            notes.push(format!("[aliases: {}]", aliases.join(", ")));
+        }
+        if let Some(env) = env {
+            notes.push(format!("[env: {env}]"));
+        }
+        if let Some(default) = default {
+            notes.push(format!("[default: {default}]"));
+        }
+        Some((doc, notes))
+    }
+
+    /// Returns the number of options shown in the summary help text
+    /// within [`Metadata::summary_max_lines`](crate::Metadata::summary_max_lines).
+    ///
+    /// `omitted` is the number of options already omitted by [`OptSpec::help_priority`](crate::OptSpec::help_priority).
+    fn summary_option_limit(&self, options: usize, omitted: usize) -> usize {
+        let Some(max_lines) = self.args.metadata().summary_max_lines else {
+            return options;
+        };
+        if self.is_full_mode() {
+            return options;
+        }
+
+        // The "Options:" header has already been written.
+        // Options are the last section in the summary, so no trailing empty line is needed.
+        let available = max_lines.saturating_sub(self.fmt.line_count());
+        if options + usize::from(omitted > 0) <= available {
+            options
+        } else {
+            // One more line for the "... N more options" line.
+            available.saturating_sub(1)
+        }
+    }
+
+    fn build_footer(&mut self) {
         if !self.is_full_mode() {
+            return;
+        }
+
+        let footer = self.footer();
+        for (label, value) in &footer {
+            self.fmt.write(&format!(
+                "{} {value}\n",
+                self.fmt.bold(&format!("{label}:"))
+            ));
+        }
+        if !footer.is_empty() {
             self.fmt.write("\n");
         }
     }
 
+    /// Returns the non-empty version, authors, homepage and license entries.
+    fn footer(&self) -> Vec<(&'static str, String)> {
+        let metadata = self.args.metadata();
+        let authors = metadata
+            .app_authors
+            .split(':')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let version = metadata.version_flag_name.map_or("", |_| {
+            self.args.cmd_version().unwrap_or(metadata.app_version)
+        });
+        [
+            ("Version", version.to_owned()),
+            ("Authors", authors),
+            ("Homepage", metadata.app_homepage.to_owned()),
+            ("License", metadata.app_license.to_owned()),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+    }
+
+    fn build_after_help(&mut self) {
+        let after_help = self.args.metadata().after_help;
+        if after_help.is_empty() {
+            return;
+        }
+        self.fmt.write(after_help);
+        self.fmt.write("\n\n");
+    }
+
     fn has_positional_args(&self) -> bool {
         self.log.iter().any(|entry| matches!(entry, Taken::Arg(_)))
     }
@@ -368,17 +848,65 @@ impl<'a> HelpBuilder<'a> {
         self.log.iter().any(|entry| match entry {
             Taken::Opt(opt) => include_requried || opt.spec().example.is_none(),
             Taken::Flag(_) => true,
-            Taken::Arg(_) | Taken::Cmd(_) => false,
+            Taken::Arg(_) | Taken::Cmd(_) | Taken::Sections(_) | Taken::Group(_) => false,
         })
     }
 
     fn has_examples(&self) -> bool {
-        self.log.iter().any(|entry| match entry {
-            Taken::Opt(opt) => opt.spec().example.is_some(),
-            Taken::Arg(arg) => arg.spec().example.is_some(),
-            _ => false,
-        })
+        self.log.iter().any(Taken::has_example)
+    }
+}
+
+fn help_priority(entry: &Taken) -> u8 {
+    match entry {
+        Taken::Opt(opt) => opt.spec().help_priority,
+        Taken::Flag(flag) => flag.spec().help_priority,
+        _ => u8::MAX,
+    }
+}
+
+fn arg_notes(arg: ArgSpec) -> Vec<String> {
+    let mut notes = Vec::new();
+    if !arg.choices.is_empty() {
+        notes.push(format!("[possible values: {}]", arg.choices.join(", ")));
+    }
+    if let Some(env) = arg.env {
+        notes.push(format!("[env: {env}]"));
     }
+    if let Some(default) = arg.default {
+        notes.push(format!("[default: {default}]"));
+    }
+    notes
+}
+
+/// Escapes `s` so that it is rendered literally in roff text.
+fn roff_escape(s: &str) -> String {
+    let escaped = s.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+/// Appends `lines` as roff text, using `.PP` for empty lines (paragraph breaks).
+fn push_roff_lines<'a>(man: &mut String, lines: impl Iterator<Item = &'a str>) {
+    for line in lines {
+        if line.trim().is_empty() {
+            man.push_str(".PP\n");
+        } else {
+            man.push_str(&roff_escape(line));
+            man.push('\n');
+        }
+    }
+}
+
+fn alias_names(aliases: &[&str], short_aliases: &[char]) -> Vec<String> {
+    aliases
+        .iter()
+        .map(|a| format!("--{a}"))
+        .chain(short_aliases.iter().map(|c| format!("-{c}")))
+        .collect()
 }
 
 #[cfg(test)]
@@ -645,85 +1173,399 @@ Options:
     }
 
     #[test]
-    fn after_subcommands_help() {
-        let mut args = test_args(&["test", "get"]);
-        args.metadata_mut().app_description = "Test";
-        HELP_FLAG.take(&mut args);
-        crate::cmd("put").doc("Put an entry").take(&mut args);
-        crate::cmd("get").doc("Get an entry").take(&mut args);
-        crate::flag("foo").doc("should included").take(&mut args);
-        crate::arg("<KEY>")
-            .doc("A key string")
-            .example("hi")
-            .take(&mut args);
+    fn nested_subcommands_help() {
+        let mut args = test_args(&["test", "remote", "add", "--help"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::cmd("tag").doc("Manage tags").take(&mut args);
+        let remote = crate::cmd("remote").doc("Manage remotes").take(&mut args);
+        {
+            let mut args = remote.scope(&mut args).expect("taken from args");
+            crate::cmd("add").doc("Add a remote").take(&mut args);
+            crate::arg("<NAME>").example("origin").take(&mut args);
+        }
 
         let help = HelpBuilder::new(&args, false).build();
         println!("{help}");
         assert_eq!(
             help,
-            r#"Get an entry
+            r#"Add a remote
 
-Usage: <APP_NAME> ... get [OPTIONS] <KEY>
+Usage: <APP_NAME> remote add [OPTIONS] <NAME>
 
 Example:
-  $ <APP_NAME> get hi
+  $ <APP_NAME> remote add origin
 
 Arguments:
-  <KEY> A key string
+  <NAME>
 
 Options:
-  -h, --help Print help ('--help' for full help, '-h' for summary)
-      --foo  should included
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
 "#
         );
+    }
+
+    #[test]
+    fn auto_env_help() {
+        let mut args = test_args(&["test", "--help"]);
+        args.metadata_mut().env_prefix = "MYAPP_";
+        HELP_FLAG.take_help(&mut args);
+        crate::opt("db-port").doc("Port").env_auto().take(&mut args);
+        crate::flag("dry-run")
+            .doc("Dry run")
+            .env_auto()
+            .take(&mut args);
+        crate::opt("host")
+            .doc("Host")
+            .env("HOST")
+            .env_auto()
+            .take(&mut args);
 
-        args.metadata_mut().full_help = true;
         let help = HelpBuilder::new(&args, false).build();
         println!("{help}");
-        assert_eq!(
-            help,
-            r#"Get an entry
+        assert!(help.contains("[env: MYAPP_DB_PORT]"));
+        assert!(help.contains("[env: MYAPP_DRY_RUN]"));
+        assert!(help.contains("[env: HOST]"));
+    }
 
-Usage: <APP_NAME> ... get [OPTIONS] <KEY>
+    #[test]
+    fn env_arg_help() {
+        let mut args = test_args(&["test", "--help"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::arg("[FILE]")
+            .doc("Input file")
+            .env("INPUT_FILE")
+            .take(&mut args);
 
-Example:
-  $ <APP_NAME> get hi
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS] [FILE]
 
 Arguments:
-  <KEY>
-    A key string
+  [FILE]
+    Input file
+    [env: INPUT_FILE]
 
 Options:
   --help, -h
     Print help ('--help' for full help, '-h' for summary)
-
-  --foo
-    should included
 "#
         );
     }
 
     #[test]
-    fn terminal_formatting() {
-        let mut args = test_args(&["test"]);
-        crate::flag("help").doc("Print help").take(&mut args);
+    fn range_help() {
+        let mut args = test_args(&["test", "--help"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::opt("port")
+            .doc("Port")
+            .ty_range_u64(1, 65535)
+            .default("8080")
+            .take(&mut args);
 
-        // Test that terminal formatting doesn't break the content structure
-        let help_terminal = HelpBuilder::new(&args, true).build();
-        let help_no_terminal = HelpBuilder::new(&args, false).build();
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
 
-        // Both should have the same basic structure, just different formatting
-        assert!(help_terminal.contains("Usage:"));
-        assert!(help_no_terminal.contains("Usage:"));
-        assert!(help_terminal.contains("Options:"));
-        assert!(help_no_terminal.contains("Options:"));
+Options:
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
+
+  --port <N (1-65535)>
+    Port
+    [range: 1-65535]
+    [default: 8080]
+"#
+        );
     }
 
     #[test]
-    fn empty_description() {
-        let mut args = test_args(&["test"]);
-        args.metadata_mut().app_description = "";
-        crate::flag("help").doc("Print help").take(&mut args);
+    fn hidden_help() {
+        let mut args = test_args(&["test", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::flag("debug-internals").hidden().take(&mut args);
+        crate::opt("trace").hidden().take(&mut args);
+        crate::flag("verbose").doc("Verbose").take(&mut args);
+        crate::cmd("run").doc("Run").take(&mut args);
+        crate::cmd("selftest").hidden().take(&mut args);
+        crate::arg("[SECRET]").hidden().take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS] <COMMAND>
+
+Commands:
+  run Run
+
+Options:
+  -h, --help    Print help ('--help' for full help, '-h' for summary)
+      --verbose Verbose
+"#
+        );
+
+        let mut args = test_args(&["test", "selftest", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::cmd("run").take(&mut args);
+        assert!(
+            crate::cmd("selftest")
+                .doc("Self test")
+                .hidden()
+                .take(&mut args)
+                .is_present()
+        );
+        let help = HelpBuilder::new(&args, false).build();
+        assert!(
+            help.starts_with("Self test\n\nUsage: <APP_NAME> selftest"),
+            "{help}"
+        );
+    }
+
+    #[test]
+    fn deprecated_help() {
+        let mut args = test_args(&["test", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::flag("colour")
+            .doc("Colorize output")
+            .deprecated("use --color instead")
+            .take(&mut args);
+        crate::opt("out")
+            .doc("Output file")
+            .deprecated("use --output instead")
+            .take(&mut args);
+        crate::cmd("rm")
+            .doc("Remove files")
+            .deprecated("use 'remove' instead")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS] <COMMAND>
+
+Commands:
+  rm Remove files [deprecated: use 'remove' instead]
+
+Options:
+  -h, --help        Print help ('--help' for full help, '-h' for summary)
+      --colour      Colorize output [deprecated: use --color instead]
+      --out <VALUE> Output file [deprecated: use --output instead]
+"#
+        );
+    }
+
+    #[test]
+    fn arg_group_help() {
+        let mut args = test_args(&["test", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::flag("stdin").doc("Read from stdin").take(&mut args);
+        crate::opt("file")
+            .ty("PATH")
+            .doc("Read from a file")
+            .take(&mut args);
+        crate::group(&["stdin", "file"])
+            .exactly_one()
+            .take(&mut args);
+        crate::group(&["json", "yaml"]).take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> (--stdin | --file <PATH>) [--json | --yaml] [OPTIONS]
+
+Options:
+  -h, --help        Print help ('--help' for full help, '-h' for summary)
+      --stdin       Read from stdin
+      --file <PATH> Read from a file
+"#
+        );
+    }
+
+    #[test]
+    fn curated_examples_help() {
+        let mut args = test_args(&["test", "-h"]);
+        args.metadata_mut().examples = &["run --port 8080", "build --release", "--verbose run"];
+        HELP_FLAG.take_help(&mut args);
+        crate::cmd("run").doc("Run").take(&mut args);
+        crate::cmd("build").doc("Build").take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS] <COMMAND>
+
+Example:
+  $ <APP_NAME> run --port 8080
+  $ <APP_NAME> build --release
+  $ <APP_NAME> --verbose run
+
+Commands:
+  run   Run
+  build Build
+
+Options:
+  -h, --help Print help ('--help' for full help, '-h' for summary)
+"#
+        );
+
+        let mut args = test_args(&["test", "run", "-h"]);
+        args.metadata_mut().examples = &["run --port 8080", "build --release", "--verbose run"];
+        crate::cmd("run").take(&mut args);
+        HELP_FLAG.take_help(&mut args);
+        crate::opt("port").example("80").take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> run --port <VALUE> [OPTIONS]
+
+Example:
+  $ <APP_NAME> run --port 80
+  $ <APP_NAME> run --port 8080
+  $ <APP_NAME> --verbose run
+
+Options:
+  -h, --help         Print help ('--help' for full help, '-h' for summary)
+      --port <VALUE>
+"#
+        );
+    }
+
+    #[test]
+    fn sections_help() {
+        let mut args = test_args(&["test", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::flag("release").doc("Release build").take(&mut args);
+        args.sections(&["BUILD_ARGS", "RUN_ARGS"]);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS] [-- BUILD_ARGS] [-- RUN_ARGS]
+
+Options:
+  -h, --help    Print help ('--help' for full help, '-h' for summary)
+      --release Release build
+"#
+        );
+    }
+
+    #[test]
+    fn bytes_help() {
+        let mut args = test_args(&["test", "-h"]);
+        HELP_FLAG.take_help(&mut args);
+        crate::opt("memory")
+            .doc("Memory limit")
+            .ty_bytes()
+            .default("536870912")
+            .take(&mut args);
+        crate::opt("jobs")
+            .ty("N")
+            .doc("Number of jobs")
+            .default_with(|| 4.to_string())
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help          Print help ('--help' for full help, '-h' for summary)
+      --memory <SIZE> Memory limit [default: 512MiB]
+      --jobs <N>      Number of jobs [default: 4]
+"#
+        );
+    }
+
+    #[test]
+    fn after_subcommands_help() {
+        let mut args = test_args(&["test", "get"]);
+        args.metadata_mut().app_description = "Test";
+        HELP_FLAG.take(&mut args);
+        crate::cmd("put").doc("Put an entry").take(&mut args);
+        crate::cmd("get").doc("Get an entry").take(&mut args);
+        crate::flag("foo").doc("should included").take(&mut args);
+        crate::arg("<KEY>")
+            .doc("A key string")
+            .example("hi")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Get an entry
+
+Usage: <APP_NAME> get [OPTIONS] <KEY>
+
+Example:
+  $ <APP_NAME> get hi
+
+Arguments:
+  <KEY> A key string
+
+Options:
+  -h, --help Print help ('--help' for full help, '-h' for summary)
+      --foo  should included
+"#
+        );
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Get an entry
+
+Usage: <APP_NAME> get [OPTIONS] <KEY>
+
+Example:
+  $ <APP_NAME> get hi
+
+Arguments:
+  <KEY>
+    A key string
+
+Options:
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
+
+  --foo
+    should included
+"#
+        );
+    }
+
+    #[test]
+    fn terminal_formatting() {
+        let mut args = test_args(&["test"]);
+        crate::flag("help").doc("Print help").take(&mut args);
+
+        // Test that terminal formatting doesn't break the content structure
+        let help_terminal = HelpBuilder::new(&args, true).build();
+        let help_no_terminal = HelpBuilder::new(&args, false).build();
+
+        // Both should have the same basic structure, just different formatting
+        assert!(help_terminal.contains("Usage:"));
+        assert!(help_no_terminal.contains("Usage:"));
+        assert!(help_terminal.contains("Options:"));
+        assert!(help_no_terminal.contains("Options:"));
+    }
+
+    #[test]
+    fn empty_description() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().app_description = "";
+        crate::flag("help").doc("Print help").take(&mut args);
 
         let help = HelpBuilder::new(&args, false).build();
         // Should not start with empty lines when description is empty
@@ -745,6 +1587,475 @@ Options:
         assert!(help_full.contains("A test application\nWith multiple lines"));
     }
 
+    #[test]
+    fn app_info_footer() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().app_authors = "Alice <alice@example.com>:Bob";
+        args.metadata_mut().app_homepage = "https://example.com";
+        args.metadata_mut().app_license = "MIT";
+        HELP_FLAG.take(&mut args);
+
+        // Not shown in summary mode.
+        let help = HelpBuilder::new(&args, false).build();
+        assert!(!help.contains("License:"));
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
+
+Authors: Alice <alice@example.com>, Bob
+Homepage: https://example.com
+License: MIT
+"#
+        );
+    }
+
+    #[test]
+    fn man_page() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().app_name = "app";
+        args.metadata_mut().app_version = "1.0.0";
+        args.metadata_mut().app_description = "A test application\n\nWith more details.";
+        args.metadata_mut().app_authors = "Alice <alice@example.com>:Bob";
+        args.metadata_mut().app_homepage = "https://example.com";
+        args.metadata_mut().app_license = "MIT";
+        args.metadata_mut().man_section = "8";
+        args.metadata_mut().man_date = "2025-01-31";
+        HELP_FLAG.take(&mut args);
+        crate::opt("level")
+            .short('l')
+            .default("3")
+            .doc("Level")
+            .take(&mut args);
+        crate::arg("<FILE>").doc("Input file").take(&mut args);
+
+        let man = HelpBuilder::new(&args, false).build_man();
+        println!("{man}");
+        assert_eq!(
+            man,
+            r#".TH "APP" "8" "2025\-01\-31" "app 1.0.0"
+.SH NAME
+app \- A test application
+.SH SYNOPSIS
+app [OPTIONS] <FILE>
+.SH DESCRIPTION
+With more details.
+.SH ARGUMENTS
+.TP
+\fB<FILE>\fR
+Input file
+.SH OPTIONS
+.TP
+\fB\-\-help, \-h\fR
+Print help ('\-\-help' for full help, '\-h' for summary)
+.TP
+\fB\-\-level, \-l <VALUE>\fR
+Level
+[default: 3]
+.SH AUTHORS
+Alice <alice@example.com>, Bob
+.SH HOMEPAGE
+https://example.com
+.SH LICENSE
+MIT
+"#
+        );
+    }
+
+    #[test]
+    fn version_flag_help() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().app_version = "0.1.0";
+        assert_eq!(VERSION_FLAG.short('V').take_version(&mut args), None);
+        assert_eq!(args.metadata().version_flag_name, Some("version"));
+        HELP_FLAG.take(&mut args);
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  --version, -V
+    Print version
+
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
+
+Version: 0.1.0
+"#
+        );
+
+        // Not shown if the version flag is not handled.
+        args.metadata_mut().version_flag_name = None;
+        let help = HelpBuilder::new(&args, false).build();
+        assert!(!help.contains("Version:"));
+
+        let mut args = test_args(&["test", "-V"]);
+        let version = VERSION_FLAG.short('V').take_version(&mut args);
+        assert_eq!(version.as_deref(), Some("<APP_NAME>\n"));
+    }
+
+    #[test]
+    fn after_help() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().app_license = "MIT";
+        args.metadata_mut().after_help = "See https://example.com/docs for more";
+        HELP_FLAG.take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help Print help ('--help' for full help, '-h' for summary)
+
+See https://example.com/docs for more
+"#
+        );
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  --help, -h
+    Print help ('--help' for full help, '-h' for summary)
+
+License: MIT
+
+See https://example.com/docs for more
+"#
+        );
+    }
+
+    #[test]
+    fn aliases_help() {
+        let mut args = test_args(&["test"]);
+        crate::flag("new")
+            .aliases(&["old"])
+            .short_aliases(&['o'])
+            .doc("Renamed flag")
+            .take(&mut args);
+        crate::opt("value")
+            .aliases(&["legacy-value"])
+            .hide_aliases()
+            .doc("Renamed option")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+      --new           Renamed flag [aliases: --old, -o]
+      --value <VALUE> Renamed option
+"#
+        );
+    }
+
+    #[test]
+    fn short_only_help() {
+        let mut args = test_args(&["test"]);
+        HELP_FLAG.take(&mut args);
+        crate::flag("")
+            .short('x')
+            .doc("Short only flag")
+            .take(&mut args);
+        crate::opt("")
+            .short('n')
+            .ty("N")
+            .doc("Short only option")
+            .example("3")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> -n <N> [OPTIONS]
+
+Example:
+  $ <APP_NAME> -n 3
+
+Options:
+  -h, --help Print help ('--help' for full help, '-h' for summary)
+  -x         Short only flag
+  -n <N>     Short only option
+"#
+        );
+    }
+
+    #[test]
+    fn multiple_values_help() {
+        let mut args = test_args(&["test"]);
+        crate::opt("point")
+            .ty("N")
+            .num_values(2)
+            .doc("Point")
+            .example("1 2")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> --point <N> <N>
+
+Example:
+  $ <APP_NAME> --point 1 2
+
+Options:
+      --point <N> <N> Point
+"#
+        );
+    }
+
+    #[test]
+    fn example_invocation() {
+        let mut args = test_args(&["test", "run"]);
+        assert_eq!(args.example_invocation(), None);
+
+        crate::flag("verbose").take(&mut args);
+        crate::opt("point")
+            .num_values(2)
+            .example("1 2")
+            .take(&mut args);
+        crate::opt("token")
+            .sensitive()
+            .example("abc")
+            .take(&mut args);
+        crate::opt("hidden").hidden().example("x").take(&mut args);
+        crate::cmd("run").take(&mut args);
+        crate::arg("<NAME>").example("a b").take(&mut args);
+        assert_eq!(
+            args.example_invocation().expect("example"),
+            [
+                "<APP_NAME>",
+                "--point",
+                "1",
+                "2",
+                "--token",
+                "abc",
+                "run",
+                "a b"
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_max_lines_help() {
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().summary_max_lines = Some(7);
+        HELP_FLAG.take(&mut args);
+        for name in ["alpha", "beta", "gamma", "delta", "epsilon"] {
+            crate::flag(name).doc("Flag").take(&mut args);
+        }
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help  Print help ('--help' for full help, '-h' for summary)
+      --alpha Flag
+      --beta  Flag
+  ... 3 more options (run '--help' to see all)
+"#
+        );
+        assert_eq!(help.lines().count(), 7);
+
+        args.metadata_mut().summary_max_lines = Some(9);
+        let help = HelpBuilder::new(&args, false).build();
+        assert_eq!(help.lines().count(), 9);
+        assert!(!help.contains("more options"));
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        assert!(help.contains("--epsilon"));
+    }
+
+    #[test]
+    fn help_priority_help() {
+        let mut args = test_args(&["test"]);
+        HELP_FLAG.take(&mut args);
+        crate::flag("alpha")
+            .doc("Alpha")
+            .help_priority(0)
+            .take(&mut args);
+        crate::flag("beta")
+            .doc("Beta")
+            .help_priority(10)
+            .take(&mut args);
+        crate::opt("gamma")
+            .doc("Gamma")
+            .help_priority(200)
+            .take(&mut args);
+        crate::flag("delta").doc("Delta").take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help          Print help ('--help' for full help, '-h' for summary)
+      --beta          Beta
+      --gamma <VALUE> Gamma
+      --delta         Delta
+  ... 1 more option (run '--help' to see all)
+"#
+        );
+
+        args.metadata_mut().summary_max_lines = Some(6);
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help          Print help ('--help' for full help, '-h' for summary)
+      --gamma <VALUE> Gamma
+  ... 3 more options (run '--help' to see all)
+"#
+        );
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        assert!(help.contains("--alpha"));
+        assert!(help.contains("--beta"));
+    }
+
+    #[test]
+    fn delimiter_help() {
+        let mut args = test_args(&["test"]);
+        crate::opt("features")
+            .delimiter(',')
+            .doc("Features")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+      --features <VALUE> Features [delimiter: ',']
+"#
+        );
+    }
+
+    #[test]
+    fn choices_help() {
+        let mut args = test_args(&["test"]);
+        crate::opt("format")
+            .short('f')
+            .choices(&["json", "yaml", "table"])
+            .doc("Output format")
+            .default("table")
+            .take(&mut args);
+        crate::opt("level")
+            .choices(&["low", "high"])
+            .doc("Level")
+            .example("low")
+            .take(&mut args);
+        crate::arg("[SHELL]")
+            .choices(&["bash", "zsh"])
+            .doc("Target shell")
+            .take(&mut args);
+
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> --level <low|high> [OPTIONS] [SHELL]
+
+Example:
+  $ <APP_NAME> --level low
+
+Arguments:
+  [SHELL] Target shell [possible values: bash, zsh]
+
+Options:
+  -f, --format <json|yaml|table> Output format [default: table]
+      --level <low|high>         Level
+"#
+        );
+
+        args.metadata_mut().full_help = true;
+        let help = HelpBuilder::new(&args, false).build();
+        println!("{help}");
+        assert_eq!(
+            help,
+            r#"Usage: <APP_NAME> --level <low|high> [OPTIONS] [SHELL]
+
+Example:
+  $ <APP_NAME> --level low
+
+Arguments:
+  [SHELL]
+    Target shell
+    [possible values: bash, zsh]
+
+Options:
+  --format, -f <json|yaml|table>
+    Output format
+    [possible values: json, yaml, table]
+    [default: table]
+
+  --level <low|high>
+    Level
+    [possible values: low, high]
+"#
+        );
+    }
+
+    #[test]
+    fn cached_help() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let build = |doc| {
+            let mut args = test_args(&["noargs", "cached", "--help"]);
+            args.metadata_mut().app_name = "cached_help_test";
+            args.metadata_mut().cache_help = true;
+            crate::HELP_FLAG.take_help(&mut args);
+            crate::cmd("cached").take(&mut args);
+            crate::opt("level")
+                .doc(doc)
+                .default_with(|| CALLS.fetch_add(1, Ordering::SeqCst).to_string())
+                .take(&mut args);
+            args.finish().expect("ok").expect("help")
+        };
+
+        let help = build("Foo");
+        assert!(help.contains("Foo"));
+        assert_eq!(build("Foo"), help);
+        assert!(build("Bar").contains("Bar"));
+
+        // The computed default is only updated after the cache is cleared.
+        clear_help_cache();
+        assert_ne!(build("Foo"), help);
+    }
+
     fn test_args(raw_args: &[&str]) -> RawArgs {
         RawArgs::new(raw_args.iter().map(|a| a.to_string()))
     }
@@ -21,10 +21,11 @@
 //!     // Set metadata for help
 //!     args.metadata_mut().app_name = env!("CARGO_PKG_NAME");
 //!     args.metadata_mut().app_description = env!("CARGO_PKG_DESCRIPTION");
+//!     args.metadata_mut().app_version = env!("CARGO_PKG_VERSION");
 //!
 //!     // Handle well-known flags
-//!     if noargs::VERSION_FLAG.take(&mut args).is_present() {
-//!         println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+//!     if let Some(version) = noargs::VERSION_FLAG.take_version(&mut args) {
+//!         print!("{version}");
 //!         return Ok(());
 //!     }
 //!     noargs::HELP_FLAG.take_help(&mut args);
@@ -101,15 +102,34 @@ mod cmd;
 mod error;
 mod flag;
 mod formatter;
+mod from_raw_args;
+mod group;
 mod help;
+mod lint;
+mod man;
 mod opt;
+mod output;
+mod reparse;
+mod standard;
+pub mod testing;
+pub mod values;
 
 pub use self::arg::{Arg, ArgSpec};
-pub use self::args::{Metadata, RawArgs};
+#[cfg(feature = "timings")]
+pub use self::args::Timings;
+pub use self::args::{ArgsPlacement, Metadata, RawArgs, Scope, Snapshot};
 pub use self::cmd::{Cmd, CmdSpec};
-pub use self::error::Error;
-pub use self::flag::{Flag, FlagSpec};
-pub use self::opt::{Opt, OptSpec};
+pub use self::error::{Error, ErrorDisplay};
+pub use self::flag::{Flag, FlagSource, FlagSpec};
+pub use self::from_raw_args::FromRawArgs;
+pub use self::group::{ArgGroup, PrefixedGroup, SpecGroup};
+pub use self::help::clear_help_cache;
+pub use self::lint::{Diagnostic, Severity, lint};
+pub use self::man::{ManPage, man_pages};
+pub use self::opt::{Opt, OptSpec, RepeatPolicy};
+pub use self::output::{Output, StdOutput};
+pub use self::reparse::ReparseDiff;
+pub use self::standard::Standard;
 
 /// A specialized [`std::result::Result`] type for the [`Error`] type.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -121,6 +141,51 @@ pub fn raw_args() -> RawArgs {
     RawArgs::new(std::env::args())
 }
 
+/// Parses the command-line arguments into `T` in one shot.
+///
+/// This is a shorthand for the following steps:
+/// 1. Makes [`RawArgs`] by [`raw_args()`]
+/// 2. Takes [`HELP_FLAG`] by [`FlagSpec::take_help()`]
+/// 3. Takes `T` by [`FromRawArgs::from_raw_args()`]
+/// 4. Calls [`RawArgs::finish()`], and if help is requested, returns [`ControlFlow::Break`](std::ops::ControlFlow::Break) with the help text
+///
+/// Printing the help text (and exiting) is left to the caller.
+/// Use the imperative API directly for more complex cases (e.g., subcommands).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+///
+/// struct Args {
+///     port: u16,
+/// }
+///
+/// impl noargs::FromRawArgs for Args {
+///     fn from_raw_args(args: &mut noargs::RawArgs) -> noargs::Result<Self> {
+///         args.metadata_mut().app_name = env!("CARGO_PKG_NAME");
+///         let port = noargs::opt("port").default("8080").take(args).then(|o| o.value().parse())?;
+///         Ok(Self { port })
+///     }
+/// }
+///
+/// fn main() -> noargs::Result<()> {
+///     let (standard, args): (noargs::Standard, Args) = match noargs::parse()? {
+///         ControlFlow::Continue(parsed) => parsed,
+///         ControlFlow::Break(help) => {
+///             print!("{help}");
+///             return Ok(());
+///         }
+///     };
+///     println!("Listening on port {} (verbose: {})", args.port, standard.verbose);
+///     Ok(())
+/// }
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn parse<T: FromRawArgs>() -> Result<std::ops::ControlFlow<String, T>> {
+    from_raw_args::parse_raw_args(raw_args())
+}
+
 /// Makes an [`ArgSpec`] instance with a specified name.
 ///
 /// # Recommended Naming Convention
@@ -129,6 +194,9 @@ pub fn raw_args() -> RawArgs {
 /// - Optional: `[NAME]`
 /// - Zero or more: `[NAME]...`
 /// - One or more: `<NAME>...`
+///
+/// Note that a bare `-` (conventionally meaning the standard input or output) is never taken by
+/// options and flags, so it can be taken as a positional argument (e.g., `cat -`).
 pub const fn arg(name: &'static str) -> ArgSpec {
     ArgSpec::new(name)
 }
@@ -148,10 +216,122 @@ pub const fn cmd(name: &'static str) -> CmdSpec {
     CmdSpec::new(name)
 }
 
+/// Makes an [`ArgGroup`] instance with the long names of the member options and flags.
+pub const fn group(names: &'static [&'static str]) -> ArgGroup {
+    ArgGroup::new(names)
+}
+
+/// Makes a [`CmdSpec`] instance that matches any subcommand name (see [`CmdSpec::external`]).
+///
+/// This is a shorthand for `noargs::cmd("<COMMAND>").external()`.
+pub const fn any_cmd() -> CmdSpec {
+    CmdSpec::new("<COMMAND>").external()
+}
+
 /// Well-known flag (`--help, -h`) for printing help information.
 pub const HELP_FLAG: FlagSpec = flag("help")
     .short('h')
     .doc("Print help ('--help' for full help, '-h' for summary)");
 
 /// Well-known flag (`--version`) for printing version information.
+///
+/// Use [`FlagSpec::take_version()`] to take this flag.
+/// The short name can be set with the builder methods (e.g., `noargs::VERSION_FLAG.short('V')`),
+/// while the long name comes from [`Metadata::version_flag_name`], which can also be set to `None`
+/// to disable the flag.
 pub const VERSION_FLAG: FlagSpec = flag("version").doc("Print version");
+
+/// Well-known flag (`--verbose, -v`) for increasing verbosity (can be specified multiple times).
+pub const VERBOSE_FLAG: FlagSpec = flag("verbose")
+    .short('v')
+    .doc("Increase verbosity (can be specified multiple times)");
+
+/// Well-known flag (`--quiet, -q`) for suppressing non-error output.
+pub const QUIET_FLAG: FlagSpec = flag("quiet").short('q').doc("Suppress non-error output");
+
+/// Well-known flag (`--no-color`) for disabling colored output.
+///
+/// Following the [NO_COLOR](https://no-color.org/) convention, this flag is also set by the `NO_COLOR` environment variable.
+pub const NO_COLOR_FLAG: FlagSpec = flag("no-color")
+    .env("NO_COLOR")
+    .doc("Disable colored output");
+
+/// Well-known flag (`--yes, -y`) for answering yes to all prompts.
+pub const YES_FLAG: FlagSpec = flag("yes").short('y').doc("Answer yes to all prompts");
+
+/// Well-known flag (`--dry-run, -n`) for showing what would be done without doing it.
+pub const DRY_RUN_FLAG: FlagSpec = flag("dry-run")
+    .short('n')
+    .doc("Show what would be done without doing it");
+
+/// Common environment variable values meaning "false" (`0`, `false`, `no` and `off`).
+///
+/// See also: [`FlagSpec::falsey_env_values`]
+pub const FALSEY_ENV_VALUES: &[&str] = &["0", "false", "no", "off"];
+
+/// Takes the well-known flags ([`VERBOSE_FLAG`], [`QUIET_FLAG`], [`NO_COLOR_FLAG`], [`YES_FLAG`] and [`DRY_RUN_FLAG`]) at once.
+///
+/// # Examples
+///
+/// ```
+/// let mut args = noargs::RawArgs::new(["app", "-vv", "--dry-run"].iter().map(|a| a.to_string()));
+/// let standard = noargs::take_standard(&mut args);
+/// assert_eq!(standard.verbose, 2);
+/// assert!(standard.dry_run);
+/// ```
+pub fn take_standard(args: &mut RawArgs) -> Standard {
+    Standard::take(args)
+}
+
+/// Re-emits the options and flags named `names` that were taken from the command line in canonical form.
+///
+/// This is useful to forward a subset of the arguments to a child process.
+/// Each of `names` is either a long name (e.g., `"verbose"`) or a display name (e.g., `"-x"` for a short-only spec).
+/// Option values are emitted as in [`OptSpec::to_args()`] (e.g., `--jobs=4`), so values starting with `-` are kept intact.
+/// Values from environment variables and default values are not included.
+///
+/// # Examples
+///
+/// ```
+/// let mut args = noargs::RawArgs::new(["app", "-v", "--jobs=4", "--color=never", "-v"].iter().map(|a| a.to_string()));
+/// noargs::flag("verbose").short('v').take_all(&mut args);
+/// noargs::opt("jobs").take(&mut args);
+/// noargs::opt("color").take(&mut args);
+///
+/// assert_eq!(
+///     noargs::forward(&args, &["verbose", "jobs"]),
+///     ["--verbose", "--verbose", "--jobs=4"]
+/// );
+/// ```
+pub fn forward(args: &RawArgs, names: &[&str]) -> Vec<String> {
+    args.forwarded_args(names)
+}
+
+/// Joins `args` into a single Windows command line string, quoting each argument as needed.
+///
+/// [`std::process::Command`] already quotes its arguments, but a command line string is needed
+/// when arguments are passed through another layer (e.g., `cmd /c`, a scheduled task or a shortcut).
+/// The result is split back into the same arguments by `CommandLineToArgvW` (and the Microsoft C runtime),
+/// even if they contain spaces, double quotes or trailing backslashes.
+/// Note that the program name (the first argument of a full command line) follows different rules,
+/// so it should not be included in `args`.
+///
+/// # Examples
+///
+/// ```
+/// let mut args = noargs::RawArgs::new(["app", "--out", r"C:\My Files\", "--name", r#"say "hi""#].iter().map(|a| a.to_string()));
+/// noargs::opt("out").take(&mut args);
+/// noargs::opt("name").take(&mut args);
+///
+/// assert_eq!(
+///     noargs::windows_command_line(noargs::forward(&args, &["out", "name"])),
+///     r#""--out=C:\My Files\\" "--name=say \"hi\"""#
+/// );
+/// ```
+pub fn windows_command_line<I>(args: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    args::join_windows_command_line(args)
+}
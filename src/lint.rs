@@ -0,0 +1,121 @@
+use crate::{Error, Metadata, RawArgs};
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// Parsing fails.
+    Error,
+
+    /// Parsing succeeds, but there may be a mistake (see [`RawArgs::warnings()`]).
+    Warning,
+}
+
+/// Diagnostic reported by [`lint()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// Severity.
+    pub severity: Severity,
+
+    /// Message (without terminal styling and the `Try '--help' ...` line).
+    pub message: String,
+}
+
+/// Checks the given command-line arguments against the specifications taken by `parse` without running the application.
+///
+/// Environment variables are not read during the check (see [`Metadata::ignore_env`]), and no help text is printed.
+/// Note that `parse` should only take arguments (application side effects in it are not prevented).
+///
+/// # Examples
+///
+/// ```
+/// fn parse(args: &mut noargs::RawArgs) -> noargs::Result<usize> {
+///     noargs::HELP_FLAG.take_help(args);
+///     noargs::opt("jobs").default("1").take(args).then(|o| o.value().parse())
+/// }
+///
+/// let diagnostics = noargs::lint(["ci", "--jobs=four"].iter().map(|a| a.to_string()), parse);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].severity, noargs::Severity::Error);
+/// assert_eq!(
+///     diagnostics[0].message,
+///     r#"argument '--jobs' has an invalid value "four": invalid digit found in string"#
+/// );
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn lint<I, F, T>(argv: I, parse: F) -> Vec<Diagnostic>
+where
+    I: Iterator<Item = String>,
+    F: FnOnce(&mut RawArgs) -> Result<T, Error>,
+{
+    let mut args = RawArgs::new(argv);
+    args.metadata_mut().ignore_env = true;
+    let result = parse(&mut args);
+
+    let mut diagnostics = args
+        .warnings()
+        .iter()
+        .map(|message| Diagnostic {
+            severity: Severity::Warning,
+            message: message.clone(),
+        })
+        .collect::<Vec<_>>();
+    let metadata = Metadata {
+        help_flag_name: None,
+        ..args.metadata()
+    };
+    if let Err(e) = result.and_then(|_| args.finish()) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: e.render(false, Some(metadata)),
+        });
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn lint_without_env() {
+        let parse = |args: &mut RawArgs| {
+            crate::opt("output").ty("PATH").take(args);
+            let token = crate::opt("token").env("PATH").take(args);
+            crate::arg("<INPUT>").take(args);
+            token.then(|o| o.value().parse::<String>())
+        };
+
+        let diagnostics = lint(
+            ["test", "--output", "in.txt"].iter().map(|a| a.to_string()),
+            parse,
+        );
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    severity: Severity::Warning,
+                    message: "'in.txt' was used as the value of '--output', but '<INPUT>' is missing; did you mean '--output <PATH> in.txt'?".to_owned(),
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    message: "missing '--token' option".to_owned(),
+                },
+            ]
+        );
+
+        let diagnostics = lint(
+            ["test", "--token=x", "in.txt", "--bar"]
+                .iter()
+                .map(|a| a.to_string()),
+            parse,
+        );
+        assert_eq!(
+            diagnostics,
+            [Diagnostic {
+                severity: Severity::Error,
+                message: "unexpected argument '--bar' found".to_owned(),
+            }]
+        );
+    }
+}
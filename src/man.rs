@@ -0,0 +1,130 @@
+use crate::{Error, RawArgs, args::Taken, help::HelpBuilder};
+
+/// Man page built by [`man_pages()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManPage {
+    /// File name (e.g., `myapp-start.1`).
+    pub name: String,
+
+    /// Page content in roff format.
+    pub content: String,
+}
+
+/// Builds the man pages of the application whose specifications are taken by `parse`.
+///
+/// The first page describes the application itself.
+/// It is followed by a page for each subcommand having [`CmdSpec::man_page`](crate::CmdSpec::man_page)
+/// (including nested ones), which is built by running `parse` with the subcommand path as the arguments.
+/// `parse` is run in help mode (see [`Metadata::help_mode`](crate::Metadata::help_mode)) without reading
+/// environment variables, and its result is ignored.
+/// Note that `parse` should only take arguments (application side effects in it are not prevented).
+///
+/// # Examples
+///
+/// ```
+/// fn parse(args: &mut noargs::RawArgs) -> noargs::Result<()> {
+///     args.metadata_mut().app_name = "myapp";
+///     noargs::HELP_FLAG.take_help(args);
+///     if noargs::cmd("start").doc("Start the server").man_page().take(args).is_present() {
+///         noargs::opt("port").doc("Port to listen on").default("8080").take(args);
+///     }
+///     Ok(())
+/// }
+///
+/// let pages = noargs::man_pages(parse);
+/// assert_eq!(pages.len(), 2);
+/// assert_eq!(pages[0].name, "myapp.1");
+/// assert!(pages[0].content.contains(".SH SEE ALSO\nmyapp\\-start(1)\n"));
+/// assert_eq!(pages[1].name, "myapp-start.1");
+/// assert!(pages[1].content.contains("\\-\\-port"));
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn man_pages<F, T>(mut parse: F) -> Vec<ManPage>
+where
+    F: FnMut(&mut RawArgs) -> Result<T, Error>,
+{
+    let mut pages = Vec::new();
+    let mut cmd_paths: Vec<Vec<&str>> = vec![Vec::new()];
+    let mut i = 0;
+    while let Some(cmd_path) = cmd_paths.get(i).cloned() {
+        i += 1;
+
+        let argv = std::iter::once(String::new()).chain(cmd_path.iter().map(|s| s.to_string()));
+        let mut args = RawArgs::new(argv);
+        args.metadata_mut().help_mode = true;
+        args.metadata_mut().full_help = true;
+        args.metadata_mut().ignore_env = true;
+        let _ = parse(&mut args);
+
+        let taken_path = args
+            .log()
+            .iter()
+            .filter_map(|entry| match entry {
+                Taken::Cmd(cmd) if cmd.is_present() => Some(cmd.spec().name),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if taken_path != cmd_path {
+            // `parse` did not take the subcommand (e.g., it returned early).
+            continue;
+        }
+
+        let builder = HelpBuilder::new(&args, false);
+        let name = format!("{}.{}", builder.man_title(), args.metadata().man_section);
+        cmd_paths.extend(builder.man_subcommands().into_iter().map(|name| {
+            let mut path = cmd_path.clone();
+            path.push(name);
+            path
+        }));
+        pages.push(ManPage {
+            name,
+            content: builder.build_man(),
+        });
+    }
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn nested_man_pages() {
+        let parse = |args: &mut RawArgs| {
+            args.metadata_mut().app_name = "app";
+            args.metadata_mut().man_section = "8";
+            args.metadata_mut().man_see_also = &["git(1)"];
+            crate::HELP_FLAG.take_help(args);
+            if crate::cmd("remote").man_page().take(args).is_present() {
+                if crate::cmd("add").man_page().take(args).is_present() {
+                    crate::arg("<NAME>")
+                        .take(args)
+                        .then(|a| a.value().parse::<String>())?;
+                }
+                crate::cmd("remove").take(args);
+            }
+            crate::cmd("status").take(args);
+            Ok(())
+        };
+
+        let pages = man_pages(parse);
+        let names = pages.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["app.8", "app-remote.8", "app-remote-add.8"]);
+        assert!(
+            pages[0]
+                .content
+                .ends_with(".SH SEE ALSO\napp\\-remote(8), git(1)\n")
+        );
+        assert!(
+            pages[1]
+                .content
+                .ends_with(".SH SEE ALSO\napp\\-remote\\-add(8), git(1)\n")
+        );
+        assert!(
+            pages[2]
+                .content
+                .contains(".SH ARGUMENTS\n.TP\n\\fB<NAME>\\fR\n")
+        );
+    }
+}
@@ -1,14 +1,21 @@
+use std::borrow::Cow;
+
 use crate::{
-    args::{Metadata, RawArgs},
+    args::{
+        self, DetachedValue, Metadata, RawArgs, canonical_choice, is_env_reference,
+        is_negative_number, match_slash_name, redact, strip_long_name,
+    },
     error::Error,
 };
 
 /// Specification for [`Opt`].
 ///
-/// Note that `noargs` does not support options with only short names.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// If [`OptSpec::name`] is empty, the option only has a short name (e.g., `noargs::opt("").short('x')`).
+#[derive(Debug, Clone, Copy)]
 pub struct OptSpec {
     /// Option long name (usually kebab-case).
+    ///
+    /// An empty name means that this option has no long name.
     pub name: &'static str,
 
     /// Option short name.
@@ -29,10 +36,150 @@ pub struct OptSpec {
     /// Default value.
     pub default: Option<&'static str>,
 
+    /// Function that computes the default value at runtime (see [`OptSpec::default_with()`]).
+    ///
+    /// This is only used if [`OptSpec::default`] is `None`.
+    pub default_fn: Option<fn() -> String>,
+
     /// Example value (if this is set, the option is considered to be requried when generating the help text).
     ///
     /// This is only used if `RawArgs::metadata().help_mode` is `true`.
     pub example: Option<&'static str>,
+
+    /// Number of values consumed per occurrence (default: `1`).
+    ///
+    /// If this is greater than `1`, the option takes the following arguments as its values
+    /// (e.g., `--point X Y`), which can be retrieved via [`Opt::values()`].
+    /// In this case, the values of [`OptSpec::example`], [`OptSpec::default`] and [`OptSpec::env`]
+    /// are separated by whitespace (e.g., `.default("0 0")`).
+    pub num_values: usize,
+
+    /// Delimiter that separates multiple values in a single argument (e.g., `,` for `--features a,b,c`).
+    ///
+    /// The separated values can be retrieved via [`Opt::values()`].
+    pub delimiter: Option<char>,
+
+    /// If `true`, a bare `-<digits>` argument (e.g., `-5`) is interpreted as the value of this option
+    /// (e.g., `head -5` as a shorthand of `head -n 5`).
+    ///
+    /// Arguments after the terminator ([`Metadata::terminator`], `--` by default) are never interpreted this way.
+    pub digits_shorthand: bool,
+
+    /// Hint appended to the error message when this option or its value is missing
+    /// (e.g., `"provide a port number, e.g. --port 8080"`).
+    pub missing_hint: Option<&'static str>,
+
+    /// Possible values.
+    ///
+    /// If this is not empty, the values are shown instead of [`OptSpec::ty`] in the help text (e.g., `--format <json|yaml>`),
+    /// and [`Opt::then()`] reports an error if the value is not one of them.
+    pub choices: &'static [&'static str],
+
+    /// If `true`, values matching [`OptSpec::choices`] ignoring ASCII case are replaced with the canonical spelling
+    /// (e.g., `--format JSON` is taken as `json`).
+    pub ignore_case: bool,
+
+    /// If `true` and [`OptSpec::env`] is `None`, the environment variable name is derived from [`OptSpec::name`]
+    /// and [`Metadata::env_prefix`](crate::Metadata::env_prefix) when taking this option (e.g., `MYAPP_DB_PORT` for `db-port`).
+    pub env_auto: bool,
+
+    /// If `true`, the value of this option is considered sensitive (e.g., a password),
+    /// and is shown as `[redacted]` in error messages, help examples and [`Debug`](std::fmt::Debug) output.
+    ///
+    /// See also: [`Opt::is_sensitive()`]
+    pub sensitive: bool,
+
+    /// Inclusive range of the value as an unsigned integer (see [`OptSpec::ty_range_u64()`]).
+    pub range: Option<(u64, u64)>,
+
+    /// If `true`, the value of this option is a byte size (see [`OptSpec::ty_bytes()`]).
+    pub bytes: bool,
+
+    /// Alternative long names (e.g., old names kept for backward compatibility).
+    pub aliases: &'static [&'static str],
+
+    /// Alternative short names.
+    pub short_aliases: &'static [char],
+
+    /// If `true`, [`OptSpec::aliases`] and [`OptSpec::short_aliases`] are not shown in the help text.
+    pub hide_aliases: bool,
+
+    /// Long names of options and flags that cannot be used together with this option (e.g., `--json` and `--yaml`).
+    ///
+    /// If this option and any of them are given on the command line, [`RawArgs::finish()`] reports an error.
+    /// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+    pub conflicts_with: &'static [&'static str],
+
+    /// Long names of options and flags that must also be given when this option is given (e.g., `--cert-file` for `--key-file`).
+    ///
+    /// If this option is given on the command line and any of them is given neither on the command line nor
+    /// via its environment variable, [`RawArgs::finish()`] reports an error.
+    /// A short-only option or flag is referred to by its short name (e.g., `"x"` for `-x`).
+    pub requires: &'static [&'static str],
+
+    /// If `true`, a value given as a separate argument may look like an option (e.g., `--output --verbose`).
+    ///
+    /// By default, such an argument is not consumed and the value is considered missing
+    /// (a single `-` and negative numbers such as `-5` are always accepted).
+    /// A value that looks like an option can still be given as `--output=--verbose`.
+    pub allow_hyphen_values: bool,
+
+    /// Policy applied by [`OptSpec::take()`] when this option appears multiple times (default: `None`).
+    ///
+    /// With [`RepeatPolicy::FirstWins`] and [`RepeatPolicy::LastWins`], an occurrence missing its value
+    /// is returned as [`Opt::MissingValue`] regardless of its position.
+    /// If this is `None`, only the first occurrence is taken and the others are left unconsumed
+    /// (so [`RawArgs::finish()`] reports them as unexpected arguments unless they are taken later).
+    pub repeat: Option<RepeatPolicy>,
+
+    /// If set, only raw arguments whose indices are greater than this value are considered when taking this option
+    /// (e.g., the index of a subcommand to ignore the arguments before it).
+    pub after_index: Option<usize>,
+
+    /// If set, only raw arguments whose indices are less than this value are considered when taking this option
+    /// (e.g., the index of `--` to ignore the arguments after it).
+    pub before_index: Option<usize>,
+
+    /// Stable identifier of this option (see [`OptSpec::id()`]).
+    pub stable_id: Option<&'static str>,
+
+    /// If `true`, this option belongs to every subcommand (e.g., `--verbose` taken before subcommand dispatch).
+    ///
+    /// A global option is searched for in all the raw arguments even if [`Metadata::posix_ordering`](crate::Metadata::posix_ordering)
+    /// is `true`, so it matches even if written after the subcommand name (e.g., `myapp run --verbose`).
+    /// As options taken before a subcommand are kept in the log, it is also shown in the help text of the subcommand.
+    pub global: bool,
+
+    /// If `true`, this option is taken only from the environment variable [`OptSpec::env`]
+    /// (the command line is not searched), and it is mandatory unless [`OptSpec::default`] is set.
+    ///
+    /// If the variable is not set, [`RawArgs::finish()`] reports [`Error::MissingOpt`]
+    /// (e.g., "environment variable 'DATABASE_URL' is not set").
+    /// This makes it possible to declare mandatory configuration of services through the same API.
+    /// The help text shows `[env only]` for such an option.
+    pub env_required: bool,
+
+    /// If `true`, this option is not shown in the help text (but is parsed as usual).
+    pub hidden: bool,
+
+    /// Importance of this option in the summary help text (`-h`) (default: `128`).
+    ///
+    /// If `0`, this option is omitted from the summary help text (but is still shown in the full help text).
+    /// If [`Metadata::summary_max_lines`](crate::Metadata::summary_max_lines) is set,
+    /// options with lower priorities are omitted first.
+    pub help_priority: u8,
+
+    /// If `true`, this option is expected to be taken more than once (e.g., in a loop).
+    ///
+    /// This suppresses the warning recorded when [`Metadata::strict_takes`](crate::Metadata::strict_takes)
+    /// is enabled and the same specification is taken again.
+    pub repeatable: bool,
+
+    /// If set, this option is deprecated with the given note (e.g., `"use --new-name instead"`).
+    ///
+    /// A deprecated option is parsed as usual, but a warning is recorded when it is given on the command line
+    /// (see [`RawArgs::warnings()`]), and the note is shown in the help text.
+    pub deprecated: Option<&'static str>,
 }
 
 impl OptSpec {
@@ -44,7 +191,34 @@ impl OptSpec {
         doc: "",
         env: None,
         default: None,
+        default_fn: None,
         example: None,
+        num_values: 1,
+        delimiter: None,
+        digits_shorthand: false,
+        missing_hint: None,
+        choices: &[],
+        ignore_case: false,
+        env_auto: false,
+        sensitive: false,
+        range: None,
+        bytes: false,
+        aliases: &[],
+        short_aliases: &[],
+        conflicts_with: &[],
+        requires: &[],
+        hide_aliases: false,
+        allow_hyphen_values: false,
+        repeat: None,
+        after_index: None,
+        before_index: None,
+        stable_id: None,
+        env_required: false,
+        hidden: false,
+        help_priority: 128,
+        global: false,
+        repeatable: false,
+        deprecated: None,
     };
 
     /// Makes an [`OptSpec`] instance with a specified name (equivalent to `noargs::opt(name)`).
@@ -85,149 +259,829 @@ impl OptSpec {
         self
     }
 
+    /// Updates the value of [`OptSpec::default_fn`].
+    ///
+    /// `f` is called each time the default value is needed (so it can depend on the current state, e.g., the current directory),
+    /// and the result is shown in the help text in the same way as [`OptSpec::default`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn num_cpus() -> String {
+    ///     std::thread::available_parallelism().map_or(1, |n| n.get()).to_string()
+    /// }
+    ///
+    /// let mut args = noargs::RawArgs::new(["app"].iter().map(|a| a.to_string()));
+    /// let jobs: usize = noargs::opt("jobs")
+    ///     .default_with(num_cpus)
+    ///     .take(&mut args)
+    ///     .then(|o| o.value().parse())?;
+    /// assert!(jobs > 0);
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub const fn default_with(mut self, f: fn() -> String) -> Self {
+        self.default_fn = Some(f);
+        self
+    }
+
     /// Updates the value of [`OptSpec::example`].
     pub const fn example(mut self, example: &'static str) -> Self {
         self.example = Some(example);
         self
     }
 
-    /// Takes the first [`Opt`] instance that satisfies this specification from the raw arguments.
-    pub fn take(self, args: &mut RawArgs) -> Opt {
-        let metadata = args.metadata();
-        args.with_record_opt(|args| {
-            if args.metadata().help_mode {
-                return if self.default.is_some() {
-                    Opt::Default {
-                        spec: self,
-                        metadata,
-                    }
-                } else if self.example.is_some() {
-                    Opt::Example {
-                        spec: self,
-                        metadata,
-                    }
-                } else {
-                    Opt::None { spec: self }
-                };
-            }
+    /// Updates the value of [`OptSpec::num_values`].
+    pub const fn num_values(mut self, n: usize) -> Self {
+        self.num_values = n;
+        self
+    }
 
-            let mut pending = None;
-            for (index, raw_arg) in args.raw_args_mut().iter_mut().enumerate() {
-                if let Some(mut pending) = pending.take() {
-                    match &mut pending {
-                        Opt::Long { value, .. } | Opt::Short { value, .. } => {
-                            if let Some(v) = raw_arg.value.take() {
-                                *value = v;
-                            } else {
-                                return Opt::MissingValue {
-                                    spec: self,
-                                    long: matches!(pending, Opt::Long { .. }),
-                                };
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
-                    return pending;
-                }
+    /// Updates the value of [`OptSpec::delimiter`].
+    pub const fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
 
-                let Some(value) = &mut raw_arg.value else {
-                    continue;
-                };
-                if !value.starts_with('-') {
-                    continue;
-                }
+    /// Sets [`OptSpec::digits_shorthand`] to `true`.
+    pub const fn digits_shorthand(mut self) -> Self {
+        self.digits_shorthand = true;
+        self
+    }
 
-                if let Some(value) = value.strip_prefix("--") {
-                    // Long name option.
-                    let Some(value) = value.strip_prefix(self.name) else {
-                        continue;
-                    };
-                    match value.chars().next() {
-                        None => {
-                            raw_arg.value = None;
-                            pending = Some(Opt::Long {
-                                spec: self,
-                                metadata,
-                                index,
-                                value: "".to_owned(),
-                            });
-                        }
-                        Some('=') => {
-                            let opt_value = value[1..].to_owned();
-                            raw_arg.value = None;
-                            return Opt::Long {
-                                spec: self,
-                                metadata,
-                                index,
-                                value: opt_value,
-                            };
-                        }
-                        Some(_) => {}
-                    }
-                    continue;
-                }
+    /// Updates the value of [`OptSpec::missing_hint`].
+    pub const fn missing_hint(mut self, hint: &'static str) -> Self {
+        self.missing_hint = Some(hint);
+        self
+    }
 
-                // Short name option.
-                let Some(short_char) = self.short else {
-                    continue;
+    /// Updates the value of [`OptSpec::choices`].
+    pub const fn choices(mut self, values: &'static [&'static str]) -> Self {
+        self.choices = values;
+        self
+    }
+
+    /// Sets [`OptSpec::ignore_case`] to `true`.
+    pub const fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// Sets [`OptSpec::env_auto`] to `true`.
+    pub const fn env_auto(mut self) -> Self {
+        self.env_auto = true;
+        self
+    }
+
+    /// Sets [`OptSpec::ty`] to `"N"` and [`OptSpec::range`] to `Some((min, max))`.
+    ///
+    /// The type is shown as `N (min-max)` in the help text (with a `[range: min-max]` annotation in full help),
+    /// and [`Opt::then()`] reports an error if the value is not an integer within the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--port=0"].iter().map(|a| a.to_string()));
+    /// let port = noargs::opt("port").ty_range_u64(1, 65535).take(&mut args);
+    /// assert!(port.then(|o| o.value().parse::<u16>()).is_err());
+    /// ```
+    pub const fn ty_range_u64(mut self, min: u64, max: u64) -> Self {
+        self.ty = "N";
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets [`OptSpec::ty`] to `"SIZE"` and [`OptSpec::bytes`] to `true`.
+    ///
+    /// The default value is shown in the canonical form of [`values::format_byte_size()`](crate::values::format_byte_size)
+    /// in the help text (e.g., `[default: 512MiB]` for `.default("536870912")`).
+    /// The value can be parsed with [`values::parse_byte_size()`](crate::values::parse_byte_size).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--memory=1G"].iter().map(|a| a.to_string()));
+    /// let memory = noargs::opt("memory")
+    ///     .ty_bytes()
+    ///     .default("536870912")
+    ///     .take(&mut args)
+    ///     .then(|o| noargs::values::parse_byte_size(o.value()))?;
+    /// assert_eq!(memory, 1 << 30);
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub const fn ty_bytes(mut self) -> Self {
+        self.ty = "SIZE";
+        self.bytes = true;
+        self
+    }
+
+    /// Sets [`OptSpec::sensitive`] to `true`.
+    pub const fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::aliases`].
+    pub const fn aliases(mut self, names: &'static [&'static str]) -> Self {
+        self.aliases = names;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::short_aliases`].
+    pub const fn short_aliases(mut self, names: &'static [char]) -> Self {
+        self.short_aliases = names;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::conflicts_with`].
+    pub const fn conflicts_with(mut self, names: &'static [&'static str]) -> Self {
+        self.conflicts_with = names;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::requires`].
+    pub const fn requires(mut self, names: &'static [&'static str]) -> Self {
+        self.requires = names;
+        self
+    }
+
+    /// Sets [`OptSpec::hide_aliases`] to `true`.
+    pub const fn hide_aliases(mut self) -> Self {
+        self.hide_aliases = true;
+        self
+    }
+
+    /// Sets [`OptSpec::allow_hyphen_values`] to `true`.
+    pub const fn allow_hyphen_values(mut self) -> Self {
+        self.allow_hyphen_values = true;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::repeat`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use noargs::RepeatPolicy;
+    ///
+    /// let mut args = noargs::RawArgs::new(["app", "--level=1", "--level=2"].iter().map(|a| a.to_string()));
+    /// let level = noargs::opt("level").repeat(RepeatPolicy::LastWins).take(&mut args);
+    /// assert_eq!(level.value(), "2");
+    /// assert!(args.finish().is_ok());
+    ///
+    /// let mut args = noargs::RawArgs::new(["app", "--level=1", "--level=2"].iter().map(|a| a.to_string()));
+    /// noargs::opt("level").repeat(RepeatPolicy::Deny).take(&mut args);
+    /// assert!(args.finish().is_err()); // "argument '--level' cannot be used multiple times"
+    /// ```
+    pub const fn repeat(mut self, policy: RepeatPolicy) -> Self {
+        self.repeat = Some(policy);
+        self
+    }
+
+    /// Updates the value of [`OptSpec::after_index`].
+    pub const fn after_index(mut self, index: usize) -> Self {
+        self.after_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`OptSpec::before_index`].
+    pub const fn before_index(mut self, index: usize) -> Self {
+        self.before_index = Some(index);
+        self
+    }
+
+    /// Updates the value of [`OptSpec::stable_id`].
+    pub const fn stable_id(mut self, id: &'static str) -> Self {
+        self.stable_id = Some(id);
+        self
+    }
+
+    /// Sets [`OptSpec::env_required`] to `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--database-url=foo"].iter().map(|a| a.to_string()));
+    /// let url = noargs::opt("database-url")
+    ///     .env("TEST_DATABASE_URL")
+    ///     .env_required()
+    ///     .take(&mut args);
+    /// assert!(!url.is_present());
+    /// noargs::opt("database-url").take(&mut args);
+    /// assert!(args.finish().is_err()); // "environment variable 'TEST_DATABASE_URL' is not set"
+    /// ```
+    pub const fn env_required(mut self) -> Self {
+        self.env_required = true;
+        self
+    }
+
+    /// Sets [`OptSpec::hidden`] to `true`.
+    pub const fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::help_priority`].
+    pub const fn help_priority(mut self, priority: u8) -> Self {
+        self.help_priority = priority;
+        self
+    }
+
+    /// Sets [`OptSpec::global`] to `true`.
+    pub const fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
+
+    /// Sets [`OptSpec::repeatable`] to `true`.
+    pub const fn repeatable(mut self) -> Self {
+        self.repeatable = true;
+        self
+    }
+
+    /// Updates the value of [`OptSpec::deprecated`].
+    pub const fn deprecated(mut self, note: &'static str) -> Self {
+        self.deprecated = Some(note);
+        self
+    }
+
+    /// Returns the identifier of this option, which is [`OptSpec::stable_id`] if set, or [`OptSpec::name`] otherwise.
+    ///
+    /// Unlike the equality of specifications, this identifier can be kept unchanged across renames
+    /// (e.g., by setting the old name to `stable_id` and moving it to the aliases),
+    /// so that external tools can track the option.
+    /// The same applies to [`ArgSpec::id()`](crate::ArgSpec::id), [`FlagSpec::id()`](crate::FlagSpec::id)
+    /// and [`CmdSpec::id()`](crate::CmdSpec::id).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let old = noargs::opt("port");
+    /// let new = noargs::opt("listen-port").aliases(&["port"]).stable_id("port");
+    /// assert_ne!(old, new);
+    /// assert_eq!(old.id(), new.id());
+    /// ```
+    pub const fn id(&self) -> &'static str {
+        match self.stable_id {
+            Some(id) => id,
+            None => self.name,
+        }
+    }
+
+    /// Builds raw arguments that specify `values` for this option (e.g., `["--port=8080"]`).
+    ///
+    /// This is useful to construct a command line of an application that uses the same specifications.
+    /// `values` should have [`OptSpec::num_values`] elements.
+    /// The first value is joined to the name with `=`, so that it is parsed back as is even if it starts with `-`.
+    /// The other values follow as separate raw arguments
+    /// (so they cannot start with `-` unless [`OptSpec::allow_hyphen_values`] is `true`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(noargs::opt("port").to_args(&["8080"]), ["--port=8080"]);
+    /// assert_eq!(noargs::opt("").short('p').to_args(&["8080"]), ["-p=8080"]);
+    /// assert_eq!(noargs::opt("point").num_values(2).to_args(&["1", "2"]), ["--point=1", "2"]);
+    /// ```
+    pub fn to_args(self, values: &[&str]) -> Vec<String> {
+        let name = self.display_name();
+        let mut values = values.iter();
+        let mut args = vec![match values.next() {
+            Some(value) => format!("{name}={value}"),
+            None => name,
+        }];
+        args.extend(values.map(|value| (*value).to_owned()));
+        args
+    }
+
+    /// Takes all [`Opt`] instances that satisfy this specification from the raw arguments.
+    ///
+    /// Unlike calling [`OptSpec::take()`] in a `while` loop, this method always terminates even if
+    /// the result is present without consuming any raw argument (e.g., [`Opt::Default`]).
+    /// Such a result is included only if nothing is found in the raw arguments.
+    /// [`OptSpec::repeat`] is ignored by this method.
+    pub fn take_all(self, args: &mut RawArgs) -> Vec<Opt> {
+        let this = Self {
+            repeat: None,
+            repeatable: true,
+            ..self
+        };
+        let mut taken = Vec::new();
+        loop {
+            let x = this.take(args);
+            if x.index().is_some() {
+                taken.push(x);
+                continue;
+            }
+            if taken.is_empty() && x.is_present() {
+                taken.push(x);
+            }
+            return taken;
+        }
+    }
+
+    /// Looks up the first occurrence of this option in the raw arguments without consuming them
+    /// or recording this option in `args`.
+    ///
+    /// This is useful for two-phase parsing (e.g., reading `--config` early to load defaults before taking other options).
+    /// Only the command line is looked up: [`Opt::Long`], [`Opt::Short`], [`Opt::MissingValue`] or [`Opt::None`] is returned,
+    /// and environment variables and default values are not resolved.
+    /// As nothing is recorded, this option is not shown in the help text unless it is taken later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["app", "--verbose", "--config=app.toml"].iter().map(|a| a.to_string()));
+    /// let config = noargs::opt("config").default("default.toml");
+    /// assert_eq!(config.peek(&args).value(), "app.toml");
+    ///
+    /// noargs::flag("verbose").take(&mut args);
+    /// assert_eq!(config.take(&mut args).value(), "app.toml");
+    /// assert!(!config.peek(&args).is_present());
+    /// assert!(args.finish()?.is_none());
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    pub fn peek(self, args: &RawArgs) -> Opt {
+        let metadata = args.metadata();
+        let rivals = args.abbrev_rivals(self.long_names());
+        let num_values = self.num_values.max(1);
+        let search_len = if self.global {
+            args.raw_args().len()
+        } else {
+            args.option_search_len()
+        };
+        let window = if self.env_required {
+            0..0
+        } else {
+            args.index_window(self.after_index, self.before_index, args.raw_args().len())
+        };
+        let terminator = args.terminator_index().unwrap_or(usize::MAX);
+        let raw_args = &args.raw_args()[..window.end];
+        for index in window.start..window.end.min(search_len) {
+            let raw_arg = &raw_args[index];
+            let Some(value) = &raw_arg.value else {
+                continue;
+            };
+            let before_terminator = index < terminator;
+            let Some(m) =
+                self.match_raw_arg(&metadata, value, rivals.as_deref(), before_terminator)
+            else {
+                continue;
+            };
+            let opt_name = match m.cluster_at {
+                0 => value.clone(),
+                i => format!("-{}", value[i..].chars().next().unwrap_or_default()),
+            };
+            let mut values = m.value.into_iter().collect::<Vec<_>>();
+            let following = raw_args[index + 1..].iter().map(|raw_arg| {
+                raw_arg
+                    .value
+                    .as_deref()
+                    .filter(|v| self.allow_hyphen_values || !looks_like_opt(v))
+            });
+            for v in following.take(num_values - values.len()) {
+                let Some(v) = v else {
+                    break;
                 };
+                values.push(v.to_owned());
+            }
+            let complete = values.len() == num_values;
+            let mut values = values.into_iter();
+            let mut opt = self.found(
+                m.long,
+                metadata,
+                index,
+                values.next().unwrap_or_default(),
+                raw_arg.source.clone(),
+            );
+            if !complete {
+                return self.missing_value(&opt, opt_name);
+            }
+            if let Opt::Long { extra_values, .. } | Opt::Short { extra_values, .. } = &mut opt {
+                extra_values.extend(values);
+            }
+            if self.ignore_case {
+                opt.canonicalize_choices();
+            }
+            return opt;
+        }
+        Opt::None { spec: self }
+    }
 
-                if let Some(value_after_dash) = value.strip_prefix('-')
-                    && let Some(value_after_short) = value_after_dash.strip_prefix(short_char)
-                {
-                    if value_after_short.is_empty() {
-                        // Format: -f (value in next argument)
-                        raw_arg.value = None;
-                        pending = Some(Opt::Short {
-                            spec: self,
-                            metadata,
-                            index,
-                            value: "".to_owned(),
-                        });
-                    } else {
-                        // Format: -fVALUE (value concatenated directly)
-                        let opt_value = value_after_short.to_owned();
-                        raw_arg.value = None;
-                        return Opt::Short {
-                            spec: self,
-                            metadata,
-                            index,
-                            value: opt_value,
-                        };
-                    }
-                }
+    /// Takes the first [`Opt`] instance that satisfies this specification from the raw arguments.
+    pub fn take(self, args: &mut RawArgs) -> Opt {
+        args.with_record_opt(|args| {
+            let mut opt = self.take_by_precedence(args);
+            if self.ignore_case {
+                opt.canonicalize_choices();
             }
+            opt
+        })
+    }
 
-            if pending.is_some() {
-                Opt::MissingValue {
-                    spec: self,
-                    long: matches!(pending, Some(Opt::Long { .. })),
-                }
-            } else if let Some(value) = self
-                .env
-                .and_then(|name| std::env::var(name).ok())
-                .filter(|v| !v.is_empty())
-            {
-                Opt::Env {
-                    spec: self,
-                    metadata,
-                    value,
-                }
-            } else if self.default.is_some() {
+    fn take_by_precedence(self, args: &mut RawArgs) -> Opt {
+        let opt = self.take_once(args);
+        if opt.index().is_some() && args.metadata().env_overrides_cli {
+            if let Some(env_opt) = self.take_env(args) {
+                // The environment variable takes precedence, so the other occurrences are also ignored.
+                while self.take_once(args).index().is_some() {}
+                return env_opt;
+            }
+        }
+        match self.repeat {
+            Some(policy) if opt.index().is_some() => self.take_repeated(args, opt, policy),
+            _ => opt,
+        }
+    }
+
+    fn take_once(self, args: &mut RawArgs) -> Opt {
+        let metadata = args.metadata();
+        if args.metadata().help_mode {
+            return if let Some(value) = self.default_value() {
                 Opt::Default {
                     spec: self,
                     metadata,
+                    value,
                 }
-            } else if self.example.is_some() && args.metadata().help_mode {
+            } else if self.example.is_some() {
                 Opt::Example {
                     spec: self,
                     metadata,
                 }
             } else {
                 Opt::None { spec: self }
+            };
+        }
+
+        let rivals = args.abbrev_rivals(self.long_names());
+        let num_values = self.num_values.max(1);
+        let mut pending = None;
+        let mut detached = None;
+        let search_len = if self.global {
+            args.raw_args().len()
+        } else {
+            args.option_search_len()
+        };
+        let window = if self.env_required {
+            0..0
+        } else {
+            args.index_window(self.after_index, self.before_index, args.raw_args().len())
+        };
+        let terminator = args.terminator_index().unwrap_or(usize::MAX);
+        for (index, raw_arg) in args.raw_args_mut().iter_mut().enumerate() {
+            if index < window.start {
+                continue;
+            }
+            if index >= window.end || (pending.is_none() && index >= search_len) {
+                break;
+            }
+            if let Some((mut opt, opt_name, needed)) = pending.take() {
+                let (Opt::Long {
+                    value,
+                    extra_values,
+                    ..
+                }
+                | Opt::Short {
+                    value,
+                    extra_values,
+                    ..
+                }) = &mut opt
+                else {
+                    unreachable!();
+                };
+                let looks_like_opt = raw_arg
+                    .value
+                    .as_deref()
+                    .is_some_and(|v| !self.allow_hyphen_values && looks_like_opt(v));
+                let v = if looks_like_opt {
+                    None
+                } else {
+                    raw_arg.value.take()
+                };
+                let Some(v) = v else {
+                    return self.missing_value(&opt, opt_name);
+                };
+                if needed == num_values {
+                    *value = v;
+                } else {
+                    extra_values.push(v);
+                }
+                if needed > 1 {
+                    pending = Some((opt, opt_name, needed - 1));
+                    continue;
+                }
+                detached = Some((opt, opt_name, index));
+                break;
+            }
+
+            let Some(value) = &mut raw_arg.value else {
+                continue;
+            };
+            let before_terminator = index < terminator;
+            let Some(m) =
+                self.match_raw_arg(&metadata, value, rivals.as_deref(), before_terminator)
+            else {
+                continue;
+            };
+            let opt_name = match m.cluster_at {
+                0 => raw_arg.value.take().unwrap_or_default(),
+                i => {
+                    // Leave the preceding flags to be taken by `FlagSpec::take()`.
+                    let opt_name = format!("-{}", value[i..].chars().next().unwrap_or_default());
+                    value.truncate(i);
+                    opt_name
+                }
+            };
+            if m.abbreviation.is_some() {
+                raw_arg.abbreviation = m.abbreviation;
+            }
+            let needed = if m.value.is_some() {
+                num_values - 1
+            } else {
+                num_values
+            };
+            let opt = self.found(
+                m.long,
+                metadata,
+                index,
+                m.value.unwrap_or_default(),
+                raw_arg.source.clone(),
+            );
+            if needed == 0 {
+                return opt;
+            }
+            pending = Some((opt, opt_name, needed));
+        }
+
+        if let Some((opt, opt_name, index)) = detached {
+            if num_values == 1 && !self.sensitive {
+                args.push_detached_value(DetachedValue {
+                    index,
+                    opt_name,
+                    opt_ty: self.ty,
+                    value: opt.value().to_owned(),
+                });
+            }
+            opt
+        } else if let Some((opt, opt_name, _)) = pending {
+            self.missing_value(&opt, opt_name)
+        } else if let Some(opt) = self.take_env(args) {
+            opt
+        } else if let Some(value) = self.default_value() {
+            Opt::Default {
+                spec: self,
+                metadata,
+                value,
             }
+        } else if self.example.is_some() && args.metadata().help_mode {
+            Opt::Example {
+                spec: self,
+                metadata,
+            }
+        } else {
+            Opt::None { spec: self }
+        }
+    }
+
+    fn take_env(self, args: &RawArgs) -> Option<Opt> {
+        let value = self
+            .env_name(&args.metadata())
+            .and_then(|name| args.env_var(&name))?;
+        Some(Opt::Env {
+            spec: self,
+            metadata: args.metadata(),
+            value,
+        })
+    }
+
+    /// Consumes the remaining occurrences of this option according to `policy` (`first` is the first occurrence).
+    /// Returns how `value` (an unconsumed raw argument) names this option, if it does.
+    ///
+    /// The digits shorthand is only recognized when `before_terminator` is `true`.
+    fn match_raw_arg(
+        self,
+        metadata: &Metadata,
+        value: &str,
+        rivals: Option<&[&str]>,
+        before_terminator: bool,
+    ) -> Option<OptMatch> {
+        if let Some(arg) = value.strip_prefix('/').filter(|_| metadata.slash_syntax) {
+            // Windows style option (e.g., `/name:value`).
+            let (name, opt_value) = match arg.split_once(':') {
+                Some((name, opt_value)) => (name, Some(opt_value.to_owned())),
+                None => (arg, None),
+            };
+            let long = match_slash_name(name, self.long_names(), self.short_names())?;
+            return Some(OptMatch {
+                long,
+                cluster_at: 0,
+                value: opt_value,
+                abbreviation: None,
+            });
+        }
+        if !value.starts_with('-') {
+            return None;
+        }
+        if metadata.negative_numbers_as_args && is_negative_number(value) {
+            return None;
+        }
+
+        if let Some(value) = value.strip_prefix("--") {
+            // Long name option.
+            let (value, abbreviation) = strip_long_name(value, self.long_names(), rivals)?;
+            return Some(OptMatch {
+                long: true,
+                cluster_at: 0,
+                value: value.strip_prefix('=').map(|v| v.to_owned()),
+                abbreviation: abbreviation.map(|name| name.to_owned()),
+            });
+        }
+
+        // Digits shorthand (e.g., `-5`).
+        if self.digits_shorthand
+            && before_terminator
+            && value.len() > 1
+            && value[1..].chars().all(|c| c.is_ascii_digit())
+        {
+            return Some(OptMatch {
+                long: false,
+                cluster_at: 0,
+                value: Some(value[1..].to_owned()),
+                abbreviation: None,
+            });
+        }
+
+        // Short name option.
+        let is_short_name = |c: char| self.short_names().any(|s| s == c);
+        let i = if value[1..].starts_with(is_short_name) {
+            1
+        } else if metadata.short_option_clusters {
+            // Format: -xzf (a cluster of short flags followed by this option)
+            value
+                .char_indices()
+                .skip(2)
+                .find(|(i, c)| is_short_name(*c) && (metadata.is_valid_flag_chars)(&value[1..*i]))
+                .map(|(i, _)| i)?
+        } else {
+            return None;
+        };
+        let short_len = value[i..].chars().next().map_or(0, char::len_utf8);
+        let value_after_short = &value[i + short_len..];
+        Some(OptMatch {
+            long: false,
+            cluster_at: if i == 1 { 0 } else { i },
+            // Format: -f (value in next argument), -fVALUE (value concatenated directly) or -f=VALUE
+            value: (!value_after_short.is_empty()).then(|| {
+                value_after_short
+                    .strip_prefix('=')
+                    .unwrap_or(value_after_short)
+                    .to_owned()
+            }),
+            abbreviation: None,
         })
     }
+
+    fn found(
+        self,
+        long: bool,
+        metadata: Metadata,
+        index: usize,
+        value: String,
+        source: Option<String>,
+    ) -> Opt {
+        if long {
+            Opt::Long {
+                spec: self,
+                metadata,
+                index,
+                value,
+                extra_values: Vec::new(),
+                source,
+            }
+        } else {
+            Opt::Short {
+                spec: self,
+                metadata,
+                index,
+                value,
+                extra_values: Vec::new(),
+                source,
+            }
+        }
+    }
+
+    fn take_repeated(self, args: &mut RawArgs, first: Opt, policy: RepeatPolicy) -> Opt {
+        let mut taken = first;
+        loop {
+            let next = self.take_once(args);
+            if next.index().is_none() {
+                return taken;
+            }
+            match policy {
+                RepeatPolicy::Deny => args.push_repeated_opt(next),
+                // An occurrence without its value is reported whichever occurrence wins.
+                _ if matches!(taken, Opt::MissingValue { .. }) => {}
+                _ if matches!(next, Opt::MissingValue { .. }) => taken = next,
+                RepeatPolicy::FirstWins => {}
+                RepeatPolicy::LastWins => taken = next,
+            }
+        }
+    }
+}
+
+impl OptSpec {
+    /// Returns [`OptSpec::env`], or the name derived by [`OptSpec::env_auto`].
+    pub(crate) fn env_name(&self, metadata: &Metadata) -> Option<Cow<'static, str>> {
+        args::env_name(self.env, self.env_auto, self.name, metadata)
+    }
+
+    pub(crate) fn long_names(&self) -> impl Clone + Iterator<Item = &'static str> {
+        let name = (!self.name.is_empty()).then_some(self.name);
+        name.into_iter().chain(self.aliases.iter().copied())
+    }
+
+    pub(crate) fn short_names(&self) -> impl Iterator<Item = char> {
+        self.short
+            .into_iter()
+            .chain(self.short_aliases.iter().copied())
+    }
+
+    pub(crate) fn display_name(&self) -> String {
+        match self.short {
+            Some(short) if self.name.is_empty() => format!("-{short}"),
+            _ => format!("--{}", self.name),
+        }
+    }
+
+    pub(crate) fn value_name(&self) -> String {
+        if !self.choices.is_empty() {
+            self.choices.join("|")
+        } else if let Some((min, max)) = self.range {
+            format!("{} ({min}-{max})", self.ty)
+        } else {
+            self.ty.to_owned()
+        }
+    }
+
+    fn missing_value(self, opt: &Opt, raw_arg: String) -> Opt {
+        Opt::MissingValue {
+            spec: self,
+            long: matches!(opt, Opt::Long { .. }),
+            index: opt.index().expect("infallible"),
+            raw_arg,
+            source: opt.injected_by().map(|s| s.to_owned()),
+        }
+    }
+
+    /// Returns [`OptSpec::default`] or the result of [`OptSpec::default_fn`] (called each time).
+    fn default_value(&self) -> Option<String> {
+        match (self.default, self.default_fn) {
+            (Some(default), _) => Some(default.to_owned()),
+            (None, Some(f)) => Some(f()),
+            (None, None) => None,
+        }
+    }
+
+    pub(crate) fn display_default(&self) -> Option<String> {
+        let default = self.default_value()?;
+        match crate::values::parse_byte_size(&default) {
+            Ok(bytes) if self.bytes => Some(crate::values::format_byte_size(bytes)),
+            _ => Some(default),
+        }
+    }
+
+    pub(crate) fn value_placeholder(&self) -> String {
+        vec![format!("<{}>", self.value_name()); self.num_values.max(1)].join(" ")
+    }
+}
+
+/// Returns `true` if `arg` looks like an option or a flag rather than a value (see [`OptSpec::allow_hyphen_values`]).
+/// How a raw argument names an option (see `OptSpec::match_raw_arg()`).
+#[derive(Debug)]
+struct OptMatch {
+    long: bool,
+    /// Byte offset of the short name in a cluster of short flags (e.g., `2` for `-xo`), or `0` if not in a cluster.
+    cluster_at: usize,
+    /// Value given in the same raw argument (e.g., `--name=value`).
+    value: Option<String>,
+    abbreviation: Option<String>,
+}
+
+fn looks_like_opt(arg: &str) -> bool {
+    arg.starts_with('-') && arg != "-" && !is_negative_number(arg)
+}
+
+/// Policy for an option that appears multiple times (see [`OptSpec::repeat`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepeatPolicy {
+    /// Takes the first occurrence and consumes the others.
+    FirstWins,
+
+    /// Takes the last occurrence and consumes the others.
+    LastWins,
+
+    /// Takes the first occurrence and consumes the others,
+    /// but [`RawArgs::finish()`] reports [`Error::RepeatedOpt`].
+    Deny,
 }
 
 impl Default for OptSpec {
@@ -236,45 +1090,241 @@ impl Default for OptSpec {
     }
 }
 
+// [NOTE]
+// PartialEq, Eq, Hash are manually implemented to avoid
+// the `unpredictable_function_pointer_comparisons` warning.
+// (`default_fn` should not be compared)
+
+impl PartialEq for OptSpec {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.short == other.short
+            && self.ty == other.ty
+            && self.doc == other.doc
+            && self.env == other.env
+            && self.default == other.default
+            && self.example == other.example
+            && self.num_values == other.num_values
+            && self.delimiter == other.delimiter
+            && self.digits_shorthand == other.digits_shorthand
+            && self.missing_hint == other.missing_hint
+            && self.choices == other.choices
+            && self.ignore_case == other.ignore_case
+            && self.env_auto == other.env_auto
+            && self.sensitive == other.sensitive
+            && self.range == other.range
+            && self.bytes == other.bytes
+            && self.aliases == other.aliases
+            && self.short_aliases == other.short_aliases
+            && self.hide_aliases == other.hide_aliases
+            && self.conflicts_with == other.conflicts_with
+            && self.requires == other.requires
+            && self.allow_hyphen_values == other.allow_hyphen_values
+            && self.repeat == other.repeat
+            && self.after_index == other.after_index
+            && self.before_index == other.before_index
+            && self.stable_id == other.stable_id
+            && self.global == other.global
+            && self.env_required == other.env_required
+            && self.hidden == other.hidden
+            && self.help_priority == other.help_priority
+            && self.repeatable == other.repeatable
+            && self.deprecated == other.deprecated
+    }
+}
+
+impl Eq for OptSpec {}
+
+impl std::hash::Hash for OptSpec {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.short.hash(state);
+        self.ty.hash(state);
+        self.doc.hash(state);
+        self.env.hash(state);
+        self.default.hash(state);
+        self.example.hash(state);
+        self.num_values.hash(state);
+        self.delimiter.hash(state);
+        self.digits_shorthand.hash(state);
+        self.missing_hint.hash(state);
+        self.choices.hash(state);
+        self.ignore_case.hash(state);
+        self.env_auto.hash(state);
+        self.sensitive.hash(state);
+        self.range.hash(state);
+        self.bytes.hash(state);
+        self.aliases.hash(state);
+        self.short_aliases.hash(state);
+        self.hide_aliases.hash(state);
+        self.conflicts_with.hash(state);
+        self.requires.hash(state);
+        self.allow_hyphen_values.hash(state);
+        self.repeat.hash(state);
+        self.after_index.hash(state);
+        self.before_index.hash(state);
+        self.stable_id.hash(state);
+        self.global.hash(state);
+        self.env_required.hash(state);
+        self.hidden.hash(state);
+        self.help_priority.hash(state);
+        self.repeatable.hash(state);
+        self.deprecated.hash(state);
+    }
+}
+
 /// A named argument with value.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The [`Debug`](std::fmt::Debug) implementation shows sensitive values as `[redacted]` (see [`Opt::is_sensitive()`]).
+///
+/// The enum and its variants are `#[non_exhaustive]`, so that new fields can be added in a compatible way.
+/// Use the accessor methods (e.g., [`Opt::value()`] and [`Opt::index()`]) or `..` in patterns to read them.
+#[derive(Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
+#[non_exhaustive]
 pub enum Opt {
+    #[non_exhaustive]
     Long {
         spec: OptSpec,
         metadata: Metadata,
         index: usize,
         value: String,
+        extra_values: Vec<String>,
+        source: Option<String>,
     },
+    #[non_exhaustive]
     Short {
         spec: OptSpec,
         metadata: Metadata,
         index: usize,
         value: String,
+        extra_values: Vec<String>,
+        source: Option<String>,
     },
+    #[non_exhaustive]
     Env {
         spec: OptSpec,
         metadata: Metadata,
         value: String,
     },
+    #[non_exhaustive]
     Default {
         spec: OptSpec,
         metadata: Metadata,
+        value: String,
     },
-    Example {
-        spec: OptSpec,
-        metadata: Metadata,
-    },
+    #[non_exhaustive]
+    Example { spec: OptSpec, metadata: Metadata },
+    #[non_exhaustive]
     MissingValue {
         spec: OptSpec,
         long: bool,
+        index: usize,
+        raw_arg: String,
+        source: Option<String>,
     },
-    None {
-        spec: OptSpec,
-    },
+    #[non_exhaustive]
+    None { spec: OptSpec },
 }
 
+impl std::fmt::Debug for Opt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sensitive = self.is_sensitive();
+        match self {
+            Opt::Long {
+                spec,
+                metadata,
+                index,
+                value: v,
+                extra_values,
+                source,
+            } => f
+                .debug_struct("Long")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("index", index)
+                .field("value", redact(v, sensitive))
+                .field("extra_values", redact(extra_values, sensitive))
+                .field("source", source)
+                .finish(),
+            Opt::Short {
+                spec,
+                metadata,
+                index,
+                value: v,
+                extra_values,
+                source,
+            } => f
+                .debug_struct("Short")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("index", index)
+                .field("value", redact(v, sensitive))
+                .field("extra_values", redact(extra_values, sensitive))
+                .field("source", source)
+                .finish(),
+            Opt::Env {
+                spec,
+                metadata,
+                value: v,
+            } => f
+                .debug_struct("Env")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("value", redact(v, sensitive))
+                .finish(),
+            Opt::Default {
+                spec,
+                metadata,
+                value,
+            } => f
+                .debug_struct("Default")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .field("value", value)
+                .finish(),
+            Opt::Example { spec, metadata } => f
+                .debug_struct("Example")
+                .field("spec", spec)
+                .field("metadata", metadata)
+                .finish(),
+            Opt::MissingValue {
+                spec,
+                long,
+                index,
+                raw_arg,
+                source,
+            } => f
+                .debug_struct("MissingValue")
+                .field("spec", spec)
+                .field("long", long)
+                .field("index", index)
+                .field("raw_arg", raw_arg)
+                .field("source", source)
+                .finish(),
+            Opt::None { spec } => f.debug_struct("None").field("spec", spec).finish(),
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
 impl Opt {
+    /// Returns `true` if this option was taken by `spec`, comparing [`OptSpec::id()`] instead of the whole specification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// const PORT: noargs::OptSpec = noargs::opt("port").stable_id("port");
+    ///
+    /// let mut args = noargs::RawArgs::new(["app", "--port=80"].iter().map(|a| a.to_string()));
+    /// let port = PORT.doc("Port number").take(&mut args);
+    /// assert_ne!(port.spec(), PORT);
+    /// assert!(port.is(PORT));
+    /// ```
+    pub fn is(&self, spec: OptSpec) -> bool {
+        self.spec().id() == spec.id()
+    }
+
     /// Returns the specification of this option.
     pub fn spec(&self) -> OptSpec {
         match self {
@@ -293,11 +1343,54 @@ impl Opt {
         !matches!(self, Opt::None { .. })
     }
 
+    /// Returns `true` if this option is given on the command line (including [`Opt::MissingValue`]).
+    pub fn is_from_cli(&self) -> bool {
+        matches!(
+            self,
+            Opt::Long { .. } | Opt::Short { .. } | Opt::MissingValue { .. }
+        )
+    }
+
+    /// Returns `true` if the value of this option comes from the environment variable.
+    pub fn is_env(&self) -> bool {
+        matches!(self, Opt::Env { .. })
+    }
+
+    /// Returns `true` if the value of this option is the default value.
+    pub fn is_default(&self) -> bool {
+        matches!(self, Opt::Default { .. })
+    }
+
     /// Returns `true` if this option is present and has a value.
     pub fn is_value_present(&self) -> bool {
         !matches!(self, Opt::None { .. } | Opt::MissingValue { .. })
     }
 
+    /// Returns `true` if an empty value is explicitly given on the command line (e.g., `--name=` or `--name ""`).
+    ///
+    /// This makes it possible to distinguish "clear this setting" from a forgotten value ([`Opt::MissingValue`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["example", "--name=", "--name"].iter().map(|a| a.to_string()));
+    /// let name = noargs::opt("name");
+    /// assert!(name.take(&mut args).is_explicit_empty());
+    /// assert!(!name.take(&mut args).is_explicit_empty()); // Missing value
+    /// ```
+    pub fn is_explicit_empty(&self) -> bool {
+        matches!(self, Opt::Long { .. } | Opt::Short { .. })
+            && self.unsplit_values().iter().all(|v| v.is_empty())
+    }
+
+    /// Returns `true` if the value of this option is sensitive.
+    ///
+    /// This is the case if [`OptSpec::sensitive`] is `true`, or the value is resolved by [`Metadata::env_resolver`].
+    pub fn is_sensitive(&self) -> bool {
+        self.spec().sensitive
+            || matches!(self, Opt::Env { spec: OptSpec { env: Some(name), .. }, .. } if is_env_reference(name))
+    }
+
     /// Returns `Some(self)` if this option is present.
     pub fn present(self) -> Option<Self> {
         self.is_present().then_some(self)
@@ -328,6 +1421,8 @@ impl Opt {
     /// # Errors
     ///
     /// - Returns [`Error::MissingOpt`] if `self.is_value_present()` is `false` (option is missing)
+    /// - Returns [`Error::InvalidOpt`] if the value is out of [`OptSpec::range`]
+    /// - Returns [`Error::InvalidOpt`] if the value is not one of [`OptSpec::choices`]
     /// - Returns [`Error::InvalidOpt`] if `f(self)` returns `Err(_)` (validation or conversion failed)
     pub fn then<F, T, E>(self, f: F) -> Result<T, Error>
     where
@@ -339,40 +1434,212 @@ impl Opt {
                 opt: Box::new(self),
             });
         }
-        f(self.clone()).map_err(|e| Error::InvalidOpt {
-            opt: Box::new(self),
-            reason: e.to_string(),
-        })
+        if let Some((min, max)) = self.spec().range {
+            let in_range = |v: &str| v.parse::<u64>().is_ok_and(|n| (min..=max).contains(&n));
+            if !self.values().into_iter().all(in_range) {
+                return Err(Error::InvalidOpt {
+                    opt: Box::new(self),
+                    reason: format!("expected an integer in the range {min}-{max}"),
+                });
+            }
+        }
+        let choices = self.spec().choices;
+        if !choices.is_empty() && !self.values().into_iter().all(|v| choices.contains(&v)) {
+            return Err(Error::InvalidOpt {
+                opt: Box::new(self),
+                reason: format!("expected one of: {}", choices.join(", ")),
+            });
+        }
+        f(self.clone()).map_err(|e| Error::InvalidOpt {
+            opt: Box::new(self),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Parses the value of this option as `T` and returns it together with this option.
+    ///
+    /// Unlike [`Opt::then()`], this keeps the option available after the conversion,
+    /// so that its provenance (e.g., [`Opt::index()`]) can be reported later without re-parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["example", "--num=42"].iter().map(|a| a.to_string()));
+    /// let (num, opt) = noargs::opt("num").take(&mut args).parse_keep::<u32>()?;
+    /// assert_eq!(num, 42);
+    /// assert_eq!(opt.index(), Some(1));
+    /// # Ok::<(), noargs::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Opt::then()`].
+    pub fn parse_keep<T>(self) -> Result<(T, Self), Error>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.clone().then(|opt| opt.value().parse())?;
+        Ok((value, self))
+    }
+
+    /// Shorthand for `self.present().map(|opt| opt.then(f)).transpose()`.
+    pub fn present_and_then<F, T, E>(self, f: F) -> Result<Option<T>, Error>
+    where
+        F: FnOnce(Self) -> Result<T, E>,
+        E: std::fmt::Display,
+    {
+        self.present().map(|opt| opt.then(f)).transpose()
+    }
+
+    /// Returns the raw value of this option, or `default` if the value is not present.
+    ///
+    /// This is useful when the fallback value is computed at runtime (i.e., [`OptSpec::default`] is not applicable).
+    /// Note that [`Opt::MissingValue`] also results in the fallback value; use [`Opt::then()`] to report it as an error.
+    pub fn present_or(&self, default: &str) -> String {
+        self.present_or_else(|| default.to_owned())
+    }
+
+    /// Returns the raw value of this option, or the result of `f()` if the value is not present.
+    pub fn present_or_else<F>(&self, f: F) -> String
+    where
+        F: FnOnce() -> String,
+    {
+        if self.is_value_present() {
+            self.value().to_owned()
+        } else {
+            f()
+        }
+    }
+
+    /// Returns the raw value of this option, or an empty string if not present.
+    pub fn value(&self) -> &str {
+        match self {
+            Opt::Long { value, .. }
+            | Opt::Short { value, .. }
+            | Opt::Env { value, .. }
+            | Opt::Default { value, .. } => value,
+            Opt::Example { spec, .. } => spec.example.unwrap_or(""),
+            Opt::MissingValue { .. } | Opt::None { .. } => "",
+        }
+    }
+
+    /// Returns all the raw values of this option (see [`OptSpec::num_values`] and [`OptSpec::delimiter`]).
+    ///
+    /// If this option is not present or has no value, an empty vector is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut args = noargs::RawArgs::new(["example", "--features=a,b", "--features", "c"].iter().map(|a| a.to_string()));
+    /// let features = noargs::opt("features").delimiter(',').take_all(&mut args);
+    /// let values = features.iter().flat_map(|o| o.values()).collect::<Vec<_>>();
+    /// assert_eq!(values, ["a", "b", "c"]);
+    /// ```
+    pub fn values(&self) -> Vec<&str> {
+        let values = self.unsplit_values();
+        let Some(delimiter) = self.spec().delimiter else {
+            return values;
+        };
+        values
+            .into_iter()
+            .flat_map(|v| v.split(delimiter))
+            .collect()
+    }
+
+    fn canonicalize_choices(&mut self) {
+        let spec = self.spec();
+        let canonicalize = |value: &mut String| {
+            let canonical = |v: &str| canonical_choice(spec.choices, v).unwrap_or(v).to_owned();
+            *value = match spec.delimiter {
+                Some(delimiter) => value
+                    .split(delimiter)
+                    .map(canonical)
+                    .collect::<Vec<_>>()
+                    .join(delimiter.encode_utf8(&mut [0; 4])),
+                None => canonical(value),
+            };
+        };
+        match self {
+            Opt::Long {
+                value,
+                extra_values,
+                ..
+            }
+            | Opt::Short {
+                value,
+                extra_values,
+                ..
+            } => {
+                canonicalize(value);
+                extra_values.iter_mut().for_each(canonicalize);
+            }
+            Opt::Env { value, .. } => canonicalize(value),
+            Opt::Default { .. }
+            | Opt::Example { .. }
+            | Opt::MissingValue { .. }
+            | Opt::None { .. } => {}
+        }
     }
 
-    /// Shorthand for `self.present().map(|opt| opt.then(f)).transpose()`.
-    pub fn present_and_then<F, T, E>(self, f: F) -> Result<Option<T>, Error>
-    where
-        F: FnOnce(Self) -> Result<T, E>,
-        E: std::fmt::Display,
-    {
-        self.present().map(|opt| opt.then(f)).transpose()
+    pub(crate) fn unsplit_values(&self) -> Vec<&str> {
+        match self {
+            Opt::Long {
+                value,
+                extra_values,
+                ..
+            }
+            | Opt::Short {
+                value,
+                extra_values,
+                ..
+            } => std::iter::once(value)
+                .chain(extra_values)
+                .map(|v| v.as_str())
+                .collect(),
+            Opt::Env { .. } | Opt::Default { .. } | Opt::Example { .. } => {
+                if self.spec().num_values > 1 {
+                    // Values are separated by whitespace as in the command line.
+                    self.value().split_whitespace().collect()
+                } else {
+                    vec![self.value()]
+                }
+            }
+            Opt::MissingValue { .. } | Opt::None { .. } => Vec::new(),
+        }
     }
 
-    /// Returns the raw value of this option, or an empty string if not present.
-    pub fn value(&self) -> &str {
+    /// Consumes this option and returns the owned raw value, or an empty string if not present.
+    pub fn into_value(self) -> String {
         match self {
             Opt::Long { value, .. } | Opt::Short { value, .. } | Opt::Env { value, .. } => value,
-            Opt::Default { spec, .. } => spec.default.unwrap_or(""),
-            Opt::Example { spec, .. } => spec.example.unwrap_or(""),
-            Opt::MissingValue { .. } | Opt::None { .. } => "",
+            _ => self.value().to_owned(),
         }
     }
 
     /// Returns the index at which the raw value associated with the name of this option was located in [`RawArgs`].
     pub fn index(&self) -> Option<usize> {
-        if let Opt::Long { index, .. } | Opt::Short { index, .. } = self {
+        if let Opt::Long { index, .. }
+        | Opt::Short { index, .. }
+        | Opt::MissingValue { index, .. } = self
+        {
             Some(*index)
         } else {
             None
         }
     }
 
+    /// Returns the label of the source that injected the raw argument of this option
+    /// (see [`RawArgs::push_injected()`]), or `None` if it was given by the user.
+    pub fn injected_by(&self) -> Option<&str> {
+        match self {
+            Opt::Long { source, .. }
+            | Opt::Short { source, .. }
+            | Opt::MissingValue { source, .. } => source.as_deref(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn metadata(&self) -> Option<Metadata> {
         match self {
             Opt::Long { metadata, .. }
@@ -409,6 +1676,13 @@ mod tests {
         assert!(matches!(opt.take(&mut args), Opt::Long { index: 2, .. }));
         assert!(matches!(opt.take(&mut args), Opt::Default { .. }));
         assert!(matches!(opt.take(&mut args), Opt::Default { .. }));
+
+        let mut args = test_args(&["test", "--bar=2", "--bar"]);
+        let bar = opt.take(&mut args);
+        assert!(bar.is_from_cli() && !bar.is_env() && !bar.is_default());
+        assert!(opt.take(&mut args).is_from_cli()); // Missing value
+        let bar = opt.take(&mut args);
+        assert!(!bar.is_from_cli() && !bar.is_env() && bar.is_default());
     }
 
     #[test]
@@ -545,7 +1819,191 @@ mod tests {
         // Short option without concatenated value should look for separate value
         let x_opt = crate::opt("x-opt").short('x');
         let result3 = x_opt.take(&mut args);
-        assert!(matches!(result3, Opt::MissingValue { .. }));
+        assert!(matches!(result3, Opt::MissingValue { index: 3, .. }));
+    }
+
+    #[test]
+    fn numeric_short_options() {
+        let mut args = test_args(&["test", "-n5", "-1", "-20"]);
+        let flag = crate::flag("").short('1');
+        assert!(matches!(
+            flag.take(&mut args),
+            crate::Flag::Short { index: 2, .. }
+        ));
+
+        let opt = crate::opt("lines").short('n').digits_shorthand();
+        assert_eq!(opt.take(&mut args).value(), "5");
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 3, .. }));
+        assert_eq!(result.value(), "20");
+        assert!(matches!(opt.take(&mut args), Opt::None { .. }));
+
+        let mut args = test_args(&["test", "--", "-5"]);
+        assert!(matches!(opt.peek(&args), Opt::None { .. }));
+        assert!(matches!(opt.take(&mut args), Opt::None { .. }));
+        assert_eq!(args.take_rest(), ["-5"]);
+    }
+
+    #[test]
+    fn slash_syntax_opt() {
+        let mut args = test_args(&["test", "/out:a.txt", "/o", "b.txt", "/out"]);
+        args.metadata_mut().slash_syntax = true;
+
+        let opt = crate::opt("out").short('o');
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Long { index: 1, .. }));
+        assert_eq!(result.value(), "a.txt");
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 2, .. }));
+        assert_eq!(result.value(), "b.txt");
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::MissingValue { index: 4, .. }));
+    }
+
+    #[test]
+    fn negative_number_opt() {
+        let mut args = test_args(&["test", "-5", "-3.14", "-n", "-2"]);
+        args.metadata_mut().negative_numbers_as_args = true;
+
+        let opt = crate::opt("lines").short('n').digits_shorthand();
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 3, .. }));
+        assert_eq!(result.value(), "-2");
+        assert!(matches!(
+            crate::opt("").short('3').take(&mut args),
+            Opt::None { .. }
+        ));
+
+        assert_eq!(crate::arg("<A>").take(&mut args).value(), "-5");
+        assert_eq!(crate::arg("<B>").take(&mut args).value(), "-3.14");
+    }
+
+    #[test]
+    fn multiple_values() {
+        let mut args = test_args(&["test", "--point", "1", "2", "-p=3", "4", "--point", "5"]);
+        let opt = crate::opt("point").short('p').num_values(2);
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Long { index: 1, .. }));
+        assert_eq!(result.values(), ["1", "2"]);
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 4, .. }));
+        assert_eq!(result.values(), ["3", "4"]);
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::MissingValue { index: 6, .. }));
+
+        let opt = opt.default("0 0").ty_range_u64(0, 9);
+        let result = opt.take(&mut args);
+        assert_eq!(result.values(), ["0", "0"]);
+        assert_eq!(
+            result
+                .then(|o| Ok::<_, std::num::ParseIntError>(o.values().len()))
+                .ok(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn delimited_values() {
+        let mut args = test_args(&["test", "--point", "1,2", "3", "--foo="]);
+        let opt = crate::opt("point").num_values(2).delimiter(',');
+        assert_eq!(opt.take(&mut args).values(), ["1", "2", "3"]);
+
+        let opt = crate::opt("foo").delimiter(',');
+        assert_eq!(opt.take(&mut args).values(), [""]);
+        assert!(opt.take(&mut args).values().is_empty());
+        assert_eq!(opt.default("x,y").take(&mut args).values(), ["x", "y"]);
+    }
+
+    #[test]
+    fn posix_ordering() {
+        let args = || test_args(&["test", "--out", "a", "build", "target", "--out=b", "-v"]);
+
+        let mut permuted = args();
+        let opt = crate::opt("out");
+        assert_eq!(opt.take_all(&mut permuted).len(), 2);
+        assert!(crate::flag("").short('v').take(&mut permuted).is_present());
+
+        let mut strict = args();
+        strict.metadata_mut().posix_ordering = true;
+        let flag = crate::flag("").short('v');
+        assert!(!flag.take(&mut strict).is_present());
+        assert_eq!(opt.take_all(&mut strict).len(), 1);
+        assert!(crate::cmd("build").take(&mut strict).is_present());
+        assert!(!flag.take(&mut strict).is_present());
+        assert_eq!(
+            strict.remaining_args().map(|(_, a)| a).collect::<Vec<_>>(),
+            ["target", "--out=b", "-v"]
+        );
+
+        // A positional argument following a flag also stops the search.
+        let mut strict = test_args(&["test", "-v", "build", "--out=b"]);
+        strict.metadata_mut().posix_ordering = true;
+        assert!(!opt.take(&mut strict).is_present());
+        assert!(flag.take(&mut strict).is_present());
+        assert!(!opt.take(&mut strict).is_present());
+    }
+
+    #[test]
+    fn take_all() {
+        let mut args = test_args(&["test", "-f", "1", "--foo=2"]);
+        let opt = crate::opt("foo").short('f').default("0");
+        let values = opt.take_all(&mut args);
+        assert_eq!(
+            values.iter().map(|o| o.value()).collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+
+        let values = opt.take_all(&mut args);
+        assert!(matches!(values[..], [Opt::Default { .. }]));
+        assert!(crate::opt("foo").take_all(&mut args).is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "'--foo' has been taken 1000 times in a row without consuming any argument"
+    )]
+    fn infinite_loop_detection() {
+        let mut args = test_args(&["test"]);
+        let opt = crate::opt("foo").default("0");
+        while opt.take(&mut args).is_present() {}
+    }
+
+    #[test]
+    fn into_value() {
+        let mut args = test_args(&["test", "--foo", "1"]);
+        let opt = crate::opt("foo").default("2");
+        assert_eq!(opt.take(&mut args).into_value(), "1");
+        assert_eq!(opt.take(&mut args).into_value(), "2");
+        assert_eq!(crate::opt("foo").take(&mut args).into_value(), "");
+    }
+
+    #[test]
+    fn present_or_fallback() {
+        let mut args = test_args(&["test", "--foo=1"]);
+        let opt = crate::opt("foo");
+        assert_eq!(opt.take(&mut args).present_or("2"), "1");
+        assert_eq!(opt.take(&mut args).present_or("2"), "2");
+        assert_eq!(opt.take(&mut args).present_or_else(|| 3.to_string()), "3");
+    }
+
+    #[test]
+    fn short_option_equals_value() {
+        let mut args = test_args(&["test", "-f=bar", "-f=", "-f==x"]);
+        let opt = crate::opt("foo").short('f');
+        assert_eq!(opt.take(&mut args).value(), "bar");
+
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 2, .. }));
+        assert_eq!(result.value(), "");
+
+        assert_eq!(opt.take(&mut args).value(), "=x");
     }
 
     #[test]
@@ -562,6 +2020,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::explicit_auto_deref)]
     fn non_str_default() {
         const DEFAULT_PORT: u16 = 8080;
 
@@ -576,6 +2035,546 @@ mod tests {
         assert_eq!(result.value(), "8080");
     }
 
+    #[test]
+    fn opt_aliases() {
+        let mut args = test_args(&["test", "--old=1", "--newer=2", "-o", "3", "--new", "4"]);
+        let opt = crate::opt("new").aliases(&["old"]).short_aliases(&['o']);
+        assert_eq!(opt.take(&mut args).value(), "1");
+        assert_eq!(opt.take(&mut args).value(), "3");
+        assert_eq!(opt.take(&mut args).value(), "4");
+        assert!(matches!(opt.take(&mut args), Opt::None { .. }));
+        assert_eq!(args.remaining_args().next(), Some((2, "--newer=2")));
+    }
+
+    #[test]
+    fn opt_index_window() {
+        let mut args = test_args(&["test", "-x", "1", "run", "-x", "2", "--", "-x", "3"]);
+        let opt = crate::opt("").short('x').after_index(3).before_index(6);
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 4, .. }));
+        assert_eq!(result.value(), "2");
+        assert!(matches!(opt.take(&mut args), Opt::None { .. }));
+
+        let mut args = test_args(&["test", "-x", "--", "1"]);
+        let opt = crate::opt("").short('x').before_index(2);
+        assert!(matches!(opt.take(&mut args), Opt::MissingValue { .. }));
+    }
+
+    #[test]
+    fn to_args_round_trip() {
+        let port = crate::opt("port").short('p');
+        let point = crate::opt("point").num_values(2);
+        let verbose = crate::flag("verbose");
+        let name = crate::opt("name");
+        let mut raw_args = vec!["test".to_owned()];
+        raw_args.extend(port.to_args(&["-1"]));
+        raw_args.extend(name.to_args(&["--x"]));
+        raw_args.extend(point.to_args(&["3 4", "5"]));
+        raw_args.extend(verbose.to_args());
+        raw_args.extend(crate::arg("<NAME>").to_args("foo"));
+        raw_args.extend(crate::arg("<PATTERN>").to_args("-x"));
+
+        let mut args = RawArgs::new(raw_args.into_iter());
+        assert_eq!(port.take(&mut args).value(), "-1");
+        assert_eq!(name.take(&mut args).value(), "--x");
+        assert_eq!(point.take(&mut args).values(), ["3 4", "5"]);
+        assert!(verbose.take(&mut args).is_present());
+        assert_eq!(crate::arg("<NAME>").take(&mut args).value(), "foo");
+        assert_eq!(crate::arg("<PATTERN>").take(&mut args).value(), "-x");
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn explicit_empty_opt() {
+        let mut args = test_args(&["test", "-n=", "-n", "", "--name", "foo", "-n"]);
+        let opt = crate::opt("name").short('n').default("bar");
+        assert!(opt.take(&mut args).is_explicit_empty());
+        assert!(opt.take(&mut args).is_explicit_empty());
+        assert!(!opt.take(&mut args).is_explicit_empty());
+        assert!(matches!(opt.take(&mut args), Opt::MissingValue { .. }));
+        assert!(!opt.take(&mut args).is_explicit_empty());
+    }
+
+    #[test]
+    fn opt_id() {
+        assert_eq!(crate::opt("port").id(), "port");
+
+        let renamed = crate::opt("listen-port")
+            .aliases(&["port"])
+            .stable_id("port");
+        assert_eq!(renamed.id(), "port");
+
+        let mut args = test_args(&["test", "--port=80"]);
+        let port = renamed.take(&mut args);
+        assert!(port.is(crate::opt("port")));
+        assert!(!port.is(crate::opt("listen-port")));
+    }
+
+    #[test]
+    fn resolved_env_opt() {
+        let mut args = test_args(&["test", "--pin=abc"]);
+        args.metadata_mut().env_resolver =
+            Some(|name| name.strip_prefix("TEST:").map(|v| v.to_owned()));
+
+        let opt = crate::opt("port").env("TEST:8o8o").take(&mut args);
+        assert!(matches!(opt, Opt::Env { .. }));
+        assert!(opt.is_sensitive());
+        let e = opt.then(|o| o.value().parse::<u16>()).expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "environment variable 'TEST:8o8o' for '--port' has an invalid value [redacted]: invalid digit found in string\n\nTry '--help' for more information."
+        );
+
+        let opt = crate::opt("pin").sensitive().take(&mut args);
+        let e = opt
+            .clone()
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "argument '--pin' has an invalid value [redacted]: invalid digit found in string\n\nTry '--help' for more information."
+        );
+        assert!(!format!("{opt:?}").contains("abc"));
+        assert!(format!("{opt:?}").contains(r#"value: [redacted]"#));
+
+        args.metadata_mut().env_resolver = None;
+        let opt = crate::opt("port").env("TEST:8080").take(&mut args);
+        assert!(matches!(opt, Opt::None { .. }));
+    }
+
+    #[test]
+    fn peek_opt() {
+        let mut args = test_args(&["test", "-xp", "1", "2", "--name"]);
+        args.metadata_mut().short_option_clusters = true;
+        args.metadata_mut().env_resolver = Some(|_| panic!("resolver called"));
+
+        let point = crate::opt("point").short('p').num_values(2);
+        let peeked = point.peek(&args);
+        assert!(matches!(peeked, Opt::Short { index: 1, .. }));
+        assert_eq!(peeked.values(), ["1", "2"]);
+        assert!(matches!(
+            crate::opt("name").default("x").peek(&args),
+            Opt::MissingValue { index: 4, .. }
+        ));
+        let other = crate::opt("other").env("TEST:other").default("x");
+        assert!(matches!(other.peek(&args), Opt::None { .. }));
+
+        assert_eq!(args.remaining_args().count(), 4);
+        assert_eq!(point.take(&mut args).values(), ["1", "2"]);
+        assert!(!point.peek(&args).is_present());
+    }
+
+    #[test]
+    fn choices_opt() {
+        let mut args = test_args(&["test", "--format=xml", "--format=yaml"]);
+        let opt = crate::opt("format").choices(&["json", "yaml", "text"]);
+        let e = opt
+            .take(&mut args)
+            .then(|o| Ok::<_, String>(o.value().to_owned()))
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '--format' has an invalid value "xml": expected one of: json, yaml, text
+
+Try '--help' for more information."#
+        );
+        let value = opt
+            .take(&mut args)
+            .then(|o| Ok::<_, String>(o.value().to_owned()));
+        assert_eq!(value.ok().as_deref(), Some("yaml"));
+    }
+
+    #[test]
+    fn ignore_case_choices() {
+        let mut args = test_args(&[
+            "test",
+            "--format=JSON",
+            "--format",
+            "Yaml,TEXT",
+            "--format=XML",
+        ]);
+        let opt = crate::opt("format")
+            .choices(&["json", "yaml", "text"])
+            .ignore_case();
+        assert_eq!(opt.take(&mut args).value(), "json");
+        assert_eq!(
+            opt.delimiter(',').take(&mut args).values(),
+            ["yaml", "text"]
+        );
+        let e = opt
+            .take(&mut args)
+            .then(|o| Ok::<_, String>(o.value().to_owned()))
+            .expect_err("error");
+        assert!(e.to_string(false).contains(r#"invalid value "XML""#));
+    }
+
+    #[test]
+    fn short_option_clusters() {
+        let mut args = test_args(&["test", "-xzf", "file.tar", "-vCdir"]);
+        assert!(matches!(
+            crate::opt("file").short('f').take(&mut args),
+            Opt::None { .. }
+        ));
+
+        args.metadata_mut().short_option_clusters = true;
+        let opt = crate::opt("file").short('f').take(&mut args);
+        assert_eq!(opt.value(), "file.tar");
+        assert_eq!(opt.index(), Some(1));
+        let opt = crate::opt("directory").short('C').take(&mut args);
+        assert_eq!(opt.value(), "dir");
+        for c in ['x', 'z', 'v'] {
+            assert!(crate::flag("").short(c).take(&mut args).is_present());
+        }
+        assert!(args.finish().is_ok());
+
+        let mut args = test_args(&["test", "-xzf"]);
+        args.metadata_mut().short_option_clusters = true;
+        let opt = crate::opt("file").short('f').take(&mut args);
+        assert!(matches!(opt, Opt::MissingValue { ref raw_arg, .. } if raw_arg == "-f"));
+    }
+
+    #[test]
+    fn ranged_opt() {
+        let mut args = test_args(&["test", "--port=0", "--port=80", "--port=x"]);
+        args.metadata_mut().help_flag_name = None;
+        let opt = crate::opt("port").ty_range_u64(1, 65535);
+
+        let e = opt
+            .take(&mut args)
+            .then(|o| o.value().parse::<u16>())
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            r#"argument '--port' has an invalid value "0": expected an integer in the range 1-65535"#
+        );
+        assert_eq!(
+            opt.take(&mut args).then(|o| o.value().parse::<u16>()).ok(),
+            Some(80)
+        );
+        assert!(
+            opt.take(&mut args)
+                .then(|o| o.value().parse::<u16>())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn short_only_opt() {
+        let mut args = test_args(&["test", "--", "foo", "-x", "bar"]);
+        let opt = crate::opt("").short('x');
+        let result = opt.take(&mut args);
+        assert!(matches!(result, Opt::Short { index: 3, .. }));
+        assert_eq!(result.value(), "bar");
+        assert!(matches!(opt.take(&mut args), Opt::None { .. }));
+    }
+
+    #[test]
+    fn abbreviated_opt() {
+        let mut args = test_args(&["test", "--out=foo", "--in", "bar"]);
+        args.metadata_mut().allow_abbrev = true;
+
+        let result = crate::opt("output").take(&mut args);
+        assert!(matches!(result, Opt::Long { index: 1, .. }));
+        assert_eq!(result.value(), "foo");
+
+        let result = crate::opt("input").take(&mut args);
+        assert!(matches!(result, Opt::Long { index: 2, .. }));
+        assert_eq!(result.value(), "bar");
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn parse_keep() {
+        let mut args = test_args(&["test", "--num", "42", "--num=x"]);
+        let opt = crate::opt("num");
+        let (num, taken) = opt.take(&mut args).parse_keep::<u8>().expect("ok");
+        assert_eq!(num, 42);
+        assert!(matches!(taken, Opt::Long { index: 1, .. }));
+
+        let e = opt.take(&mut args).parse_keep::<u8>().expect_err("error");
+        assert!(matches!(e, Error::InvalidOpt { .. }));
+        let e = opt.take(&mut args).parse_keep::<u8>().expect_err("error");
+        assert!(matches!(e, Error::MissingOpt { .. }));
+    }
+
+    #[test]
+    fn hyphen_values() {
+        let opt = crate::opt("output").short('o');
+        let mut args = test_args(&["test", "--output", "--verbose", "-o", "-", "-o", "-5"]);
+        let taken = opt.take(&mut args);
+        assert!(matches!(taken, Opt::MissingValue { index: 1, .. }));
+        assert!(crate::flag("verbose").take(&mut args).is_present());
+        assert_eq!(opt.take(&mut args).value(), "-");
+        assert_eq!(opt.take(&mut args).value(), "-5");
+
+        let e = taken
+            .present_and_then(|o| o.value().parse::<String>())
+            .expect_err("error");
+        assert_eq!(e.to_string(false), "missing '--output' value (argument #1)");
+
+        let mut args = test_args(&["test", "--output", "--verbose", "-o=-v"]);
+        let opt = opt.allow_hyphen_values();
+        assert_eq!(opt.take(&mut args).value(), "--verbose");
+        assert_eq!(opt.take(&mut args).value(), "-v");
+    }
+
+    #[test]
+    fn prepended_args() {
+        let profile = || ["--level=1", "--", "x"].iter().map(|a| a.to_string());
+        let raw_args = ["test", "--level=2", "--", "y"];
+
+        let mut args = test_args(&raw_args);
+        args.prepend_args(profile(), "profile", crate::ArgsPlacement::BeforeUserArgs);
+        assert_eq!(crate::opt("level").take(&mut args).value(), "1");
+        assert_eq!(args.arg_source(1), Some("profile"));
+        assert_eq!(args.arg_source(4), None);
+
+        let mut args = test_args(&raw_args);
+        args.prepend_args(profile(), "profile", crate::ArgsPlacement::AfterUserArgs);
+        let level = crate::opt("level").repeat(RepeatPolicy::FirstWins);
+        assert_eq!(level.take(&mut args).value(), "2");
+        assert_eq!(args.take_rest(), ["x", "--", "y"]);
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn env_required_opt() {
+        let opt = crate::opt("db-url")
+            .env("TEST_ENV_OPT_REQUIRED")
+            .env_required();
+        let mut args = test_args(&["test", "--db-url=cli"]);
+        let db_url = opt.take(&mut args);
+        assert!(matches!(db_url, Opt::None { .. }));
+        assert!(crate::opt("db-url").take(&mut args).is_present());
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "environment variable 'TEST_ENV_OPT_REQUIRED' is not set"
+        );
+        let e = db_url
+            .then(|o| o.value().parse::<String>())
+            .expect_err("error");
+        assert!(matches!(e, Error::MissingOpt { .. }));
+
+        let mut args = test_args(&["test"]);
+        assert!(opt.default("x").take(&mut args).is_default());
+        assert!(args.finish().is_ok());
+
+        unsafe {
+            std::env::set_var("TEST_ENV_OPT_REQUIRED", "env");
+        }
+        let mut args = test_args(&["test"]);
+        assert_eq!(opt.take(&mut args).value(), "env");
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn env_auto_opt() {
+        let opt = crate::opt("auto-port").env_auto();
+        unsafe {
+            std::env::set_var("TEST_ENV_OPT_AUTO_PORT", "x");
+        }
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().env_prefix = "TEST_ENV_OPT_";
+        let port = opt.take(&mut args);
+        assert_eq!(port.value(), "x");
+        assert_eq!(port.spec().env, None);
+        let e = port.then(|o| o.value().parse::<u16>()).expect_err("error");
+        assert!(e.to_string(false).starts_with(
+            "environment variable 'TEST_ENV_OPT_AUTO_PORT' for '--auto-port' has an invalid value"
+        ));
+
+        let mut args = test_args(&["test"]);
+        args.metadata_mut().env_prefix = "TEST_ENV_OPT_";
+        crate::opt("auto-host")
+            .env_auto()
+            .env_required()
+            .take(&mut args);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "environment variable for '--auto-host' is not set"
+        );
+    }
+
+    #[test]
+    fn repeated_opt() {
+        let raw_args = ["test", "--foo=1", "-f", "2", "bar", "--foo=3"];
+
+        let mut args = test_args(&raw_args);
+        let opt = crate::opt("foo").short('f');
+        assert_eq!(opt.take(&mut args).value(), "1");
+        assert!(args.finish().is_err());
+
+        let mut args = test_args(&raw_args);
+        let opt = opt.repeat(RepeatPolicy::FirstWins);
+        assert_eq!(opt.take(&mut args).value(), "1");
+        assert!(crate::arg("<BAR>").take(&mut args).is_present());
+        assert!(args.finish().is_ok());
+
+        let mut args = test_args(&raw_args);
+        let opt = opt.repeat(RepeatPolicy::LastWins);
+        assert_eq!(opt.take(&mut args).value(), "3");
+        assert_eq!(opt.take_all(&mut args).len(), 0);
+
+        let mut args = test_args(&raw_args);
+        let opt = opt.repeat(RepeatPolicy::Deny);
+        assert_eq!(opt.take(&mut args).value(), "1");
+        assert!(crate::arg("<BAR>").take(&mut args).is_present());
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "argument '-f' cannot be used multiple times (argument #2)\n\nTry '--help' for more information."
+        );
+
+        let mut args = test_args(&raw_args);
+        assert_eq!(opt.take_all(&mut args).len(), 3);
+        assert!(args.finish().is_err());
+    }
+
+    #[test]
+    fn repeated_opt_missing_value() {
+        for policy in [RepeatPolicy::FirstWins, RepeatPolicy::LastWins] {
+            let opt = crate::opt("foo").repeat(policy);
+
+            let mut args = test_args(&["test", "--foo=1", "--foo"]);
+            assert!(matches!(
+                opt.take(&mut args),
+                Opt::MissingValue { index: 2, .. }
+            ));
+
+            let mut args = test_args(&["test", "--foo", "--foo=2"]);
+            assert!(matches!(
+                opt.take(&mut args),
+                Opt::MissingValue { index: 1, .. }
+            ));
+            assert!(args.finish().is_ok());
+        }
+    }
+
+    #[test]
+    fn computed_default() {
+        static CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        fn hostname() -> String {
+            CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            "localhost".to_owned()
+        }
+
+        let opt = crate::opt("host").default_with(hostname);
+        let mut args = test_args(&["test", "--host=example.com"]);
+        assert_eq!(opt.take(&mut args).value(), "example.com");
+        assert_eq!(CALLS.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        let mut args = test_args(&["test"]);
+        let taken = opt.take(&mut args);
+        assert!(matches!(taken, Opt::Default { .. }));
+        assert_eq!(taken.value(), "localhost");
+        assert_eq!(opt.take(&mut args).value(), "localhost");
+        assert_eq!(CALLS.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let mut args = test_args(&["test"]);
+        assert_eq!(opt.default("foo").take(&mut args).value(), "foo");
+    }
+
+    #[test]
+    fn opts_from_env() {
+        unsafe {
+            std::env::set_var("TEST_ENV_OPT_FLAGS", r#"--foo=1 --bar "a \"b\"" --baz"#);
+        }
+        let mut args = test_args(&["test", "--foo=0", "--", "--qux"]);
+        args.extend_from_env("TEST_ENV_OPT_FLAGS").expect("extend");
+        assert_eq!(crate::opt("foo").take(&mut args).value(), "0");
+        assert_eq!(crate::opt("foo").take(&mut args).value(), "1");
+        assert_eq!(crate::opt("bar").take(&mut args).value(), r#"a "b""#);
+        assert_eq!(args.take_rest(), ["--qux"]);
+        let e = args.finish().expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "unexpected argument '--baz' found (injected by TEST_ENV_OPT_FLAGS)\n\nTry '--help' for more information."
+        );
+
+        unsafe {
+            std::env::set_var("TEST_ENV_OPT_FLAGS", "--foo 'bar");
+        }
+        let mut args = test_args(&["test"]);
+        let e = args
+            .extend_from_env("TEST_ENV_OPT_FLAGS")
+            .expect_err("error");
+        assert_eq!(
+            e.to_string(false),
+            "failed to parse environment variable 'TEST_ENV_OPT_FLAGS': unterminated single quote\n\nTry '--help' for more information."
+        );
+    }
+
+    #[test]
+    fn split_command_line() {
+        use crate::args::split_command_line;
+
+        assert_eq!(split_command_line(""), Ok(vec![]));
+        assert_eq!(
+            split_command_line(r#"  a\ b 'c d'"e\"f\g" '' x\y "#),
+            Ok(vec![
+                "a b".to_owned(),
+                r#"c de"f\g"#.to_owned(),
+                "".to_owned(),
+                "xy".to_owned()
+            ])
+        );
+        assert_eq!(split_command_line("a\\"), Err("trailing backslash"));
+        assert_eq!(
+            split_command_line(r#"a "b"#),
+            Err("unterminated double quote")
+        );
+    }
+
+    #[test]
+    fn join_windows_command_line() {
+        use crate::args::join_windows_command_line;
+
+        assert_eq!(join_windows_command_line([""; 0]), "");
+        assert_eq!(
+            join_windows_command_line(["a", "", "b c", r"x\y", r"x\y z"]),
+            r#"a "" "b c" x\y "x\y z""#
+        );
+        assert_eq!(
+            join_windows_command_line([r#"say "hi""#, r#"a\"b"#, r"C:\dir\", r"C:\my dir\\"]),
+            r#""say \"hi\"" "a\\\"b" C:\dir\ "C:\my dir\\\\""#
+        );
+    }
+
+    #[test]
+    fn deprecated_specs() {
+        let mut args = test_args(&["test", "--out=a.txt", "--colour", "rm"]);
+        let opt = crate::opt("out")
+            .deprecated("use --output instead")
+            .take(&mut args);
+        assert_eq!(opt.value(), "a.txt");
+        crate::opt("level")
+            .default("1")
+            .deprecated("no longer used")
+            .take(&mut args);
+        assert!(
+            crate::flag("colour")
+                .deprecated("use --color instead")
+                .take(&mut args)
+                .is_present()
+        );
+        assert!(
+            crate::cmd("rm")
+                .deprecated("use 'remove' instead")
+                .take(&mut args)
+                .is_present()
+        );
+        assert_eq!(
+            args.warnings(),
+            [
+                "'--out' is deprecated: use --output instead",
+                "'--colour' is deprecated: use --color instead",
+                "'rm' command is deprecated: use 'remove' instead",
+            ]
+        );
+    }
+
     fn test_args(raw_args: &[&str]) -> RawArgs {
         RawArgs::new(raw_args.iter().map(|a| a.to_string()))
     }
@@ -0,0 +1,226 @@
+use std::{
+    io::{IsTerminal, Write},
+    process::ExitCode,
+};
+
+use crate::Error;
+
+/// Destination of texts produced by `noargs`.
+///
+/// This trait encapsulates the common convention for command-line applications:
+/// - Requested help (e.g., `--help`) is written to the standard output, and the application exits with status `0`
+/// - Errors (including help hints caused by errors) are written to the standard error, and the application exits with a non-zero status
+///
+/// # Examples
+///
+/// ```no_run
+/// use noargs::Output;
+///
+/// fn main() -> std::process::ExitCode {
+///     let mut args = noargs::raw_args();
+///     noargs::HELP_FLAG.take_help(&mut args);
+///     if let Some(exit_code) = noargs::StdOutput.report(args.finish()) {
+///         return exit_code;
+///     }
+///
+///     // Do application logic
+///
+///     std::process::ExitCode::SUCCESS
+/// }
+/// ```
+pub trait Output {
+    /// Writes a text to the standard output.
+    fn write_stdout(&mut self, text: &str);
+
+    /// Writes a text to the standard error.
+    fn write_stderr(&mut self, text: &str);
+
+    /// Returns `true` if the standard output is a terminal (used by [`RawArgs::finish_with()`](crate::RawArgs::finish_with)
+    /// to decide whether help texts are styled).
+    fn is_stdout_terminal(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the standard error is a terminal (used to decide whether error texts are styled).
+    fn is_stderr_terminal(&self) -> bool {
+        false
+    }
+
+    /// Writes the result of [`RawArgs::finish()`](crate::RawArgs::finish) following the convention described above.
+    ///
+    /// Returns `None` if the application should continue (i.e., `result` is `Ok(None)`),
+    /// or `Some(exit_code)` if the application should exit.
+    fn report(&mut self, result: Result<Option<String>, Error>) -> Option<ExitCode> {
+        match result {
+            Ok(None) => None,
+            Ok(Some(help)) => {
+                self.write_stdout(&help);
+                Some(ExitCode::SUCCESS)
+            }
+            Err(e) => {
+                let text = e.to_string(e.is_styled(|| self.is_stderr_terminal()));
+                self.write_stderr(&format!("{text}\n"));
+                Some(ExitCode::from(Error::EXIT_CODE))
+            }
+        }
+    }
+}
+
+/// [`Output`] implementation that writes to the standard output and the standard error of the process.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StdOutput;
+
+impl Output for StdOutput {
+    fn write_stdout(&mut self, text: &str) {
+        let _ = std::io::stdout().write_all(text.as_bytes());
+    }
+
+    fn write_stderr(&mut self, text: &str) {
+        let _ = std::io::stderr().write_all(text.as_bytes());
+    }
+
+    fn is_stdout_terminal(&self) -> bool {
+        std::io::stdout().is_terminal()
+    }
+
+    fn is_stderr_terminal(&self) -> bool {
+        std::io::stderr().is_terminal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RawArgs;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct TestOutput {
+        stdout: String,
+        stderr: String,
+    }
+
+    impl Output for TestOutput {
+        fn write_stdout(&mut self, text: &str) {
+            self.stdout.push_str(text);
+        }
+
+        fn write_stderr(&mut self, text: &str) {
+            self.stderr.push_str(text);
+        }
+    }
+
+    #[test]
+    fn requested_help_goes_to_stdout() {
+        let mut args = test_args(&["test", "--help"]);
+        crate::HELP_FLAG.take_help(&mut args);
+
+        let mut output = TestOutput::default();
+        let exit_code = output.report(args.finish());
+        assert_eq!(exit_code, Some(ExitCode::SUCCESS));
+        assert!(output.stdout.starts_with("Usage:"));
+        assert!(output.stderr.is_empty());
+    }
+
+    #[test]
+    fn error_goes_to_stderr() {
+        let args = test_args(&["test", "--foo"]);
+
+        let mut output = TestOutput::default();
+        let exit_code = output.report(args.finish());
+        assert_eq!(exit_code, Some(ExitCode::from(Error::EXIT_CODE)));
+        assert!(output.stdout.is_empty());
+        assert_eq!(
+            output.stderr,
+            "unexpected argument '--foo' found\n\nTry '--help' for more information.\n"
+        );
+    }
+
+    #[test]
+    fn no_output_on_success() {
+        let args = test_args(&["test"]);
+
+        let mut output = TestOutput::default();
+        assert_eq!(output.report(args.finish()), None);
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+    }
+
+    #[test]
+    fn finish_with() {
+        use std::ops::ControlFlow;
+
+        let mut args = test_args(&["test", "--help"]);
+        crate::HELP_FLAG.take_help(&mut args);
+        let mut output = TestOutput::default();
+        assert_eq!(
+            args.finish_with(&mut output),
+            ControlFlow::Break(ExitCode::SUCCESS)
+        );
+        assert!(output.stdout.starts_with("Usage:"));
+        assert!(!output.stdout.contains('\x1B'));
+        assert!(output.stderr.is_empty());
+
+        let args = test_args(&["test", "--foo"]);
+        let mut output = TestOutput::default();
+        assert_eq!(
+            args.finish_with(&mut output),
+            ControlFlow::Break(ExitCode::from(Error::EXIT_CODE))
+        );
+        assert!(output.stdout.is_empty());
+        assert!(
+            output
+                .stderr
+                .starts_with("unexpected argument '--foo' found")
+        );
+
+        let args = test_args(&["test"]);
+        let mut output = TestOutput::default();
+        assert_eq!(args.finish_with(&mut output), ControlFlow::Continue(()));
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+    }
+
+    #[test]
+    fn styling_per_stream() {
+        #[derive(Default)]
+        struct Terminal(TestOutput);
+
+        impl Output for Terminal {
+            fn write_stdout(&mut self, text: &str) {
+                self.0.write_stdout(text);
+            }
+
+            fn write_stderr(&mut self, text: &str) {
+                self.0.write_stderr(text);
+            }
+
+            fn is_stdout_terminal(&self) -> bool {
+                true
+            }
+        }
+
+        let mut args = test_args(&["test", "--help"]);
+        crate::HELP_FLAG.take_help(&mut args);
+        let mut output = Terminal::default();
+        let _ = args.finish_with(&mut output);
+        assert!(output.0.stdout.starts_with("\x1B[1m\x1B[4mUsage:"));
+
+        let mut args = test_args(&["test", "--help"]);
+        args.metadata_mut().style_help = Some(false);
+        crate::HELP_FLAG.take_help(&mut args);
+        let mut output = Terminal::default();
+        let _ = args.finish_with(&mut output);
+        assert!(output.0.stdout.starts_with("Usage:"));
+
+        let mut args = test_args(&["test", "--foo"]);
+        args.metadata_mut().style_errors = Some(true);
+        let mut output = Terminal::default();
+        let _ = args.finish_with(&mut output);
+        assert!(output.0.stderr.contains("\x1B[1m--help\x1B[0m"));
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{RawArgs, args::Taken};
+
+/// Differences of the options and flags between two parsing results of the same specifications.
+///
+/// This is useful for long-running processes (e.g., daemons) that re-parse a new argument list at runtime
+/// (e.g., on `SIGHUP`) and apply only the changed settings.
+///
+/// # Examples
+///
+/// ```
+/// fn parse(args: &mut noargs::RawArgs) -> noargs::Result<(usize, bool)> {
+///     let port = noargs::opt("port").default("8080").take(args).then(|o| o.value().parse())?;
+///     let verbose = noargs::flag("verbose").take(args).is_present();
+///     Ok((port, verbose))
+/// }
+///
+/// let mut args = noargs::RawArgs::new(["daemon", "--verbose"].iter().map(|a| a.to_string()));
+/// parse(&mut args)?;
+/// args.clone().finish()?;
+///
+/// // Reload.
+/// let mut new_args = args.reparse(["daemon", "--port=3000", "--verbose"].iter().map(|a| a.to_string()));
+/// parse(&mut new_args)?;
+/// let diff = noargs::ReparseDiff::new(&args, &new_args);
+/// new_args.finish()?;
+///
+/// assert_eq!(diff.changed, ["--port"]);
+/// # Ok::<(), noargs::Error>(())
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReparseDiff {
+    /// Display names (e.g., `--port`) of the options and flags whose values differ.
+    pub changed: Vec<String>,
+}
+
+impl ReparseDiff {
+    /// Compares the options and flags taken from `old` and `new`.
+    ///
+    /// Values are compared in their raw form, so specifying the default value explicitly is considered as unchanged.
+    /// Options and flags taken multiple times (e.g., via [`OptSpec::take_all()`](crate::OptSpec::take_all))
+    /// are compared as sequences.
+    pub fn new(old: &RawArgs, new: &RawArgs) -> Self {
+        let old_values = values(old);
+        let new_values = values(new);
+        let lookup = |values: &[Entry], name: &str| {
+            values
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.clone())
+        };
+
+        let mut changed: Vec<String> = Vec::new();
+        for (name, _) in new_values.iter().chain(&old_values) {
+            if changed.contains(name) {
+                continue;
+            }
+            if lookup(&old_values, name) != lookup(&new_values, name) {
+                changed.push(name.clone());
+            }
+        }
+        Self { changed }
+    }
+
+    /// Returns `true` if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+type Entry = (String, Vec<Option<String>>);
+
+fn values(args: &RawArgs) -> Vec<Entry> {
+    let mut values: Vec<Entry> = Vec::new();
+    for taken in args.log() {
+        let (name, value) = match taken {
+            Taken::Opt(opt) => (
+                opt.spec().display_name(),
+                opt.is_value_present().then(|| opt.values().join("\0")),
+            ),
+            Taken::Flag(flag) => (
+                flag.spec().display_name(),
+                flag.is_present().then(String::new),
+            ),
+            _ => continue,
+        };
+        if let Some((_, v)) = values.iter_mut().find(|(n, _)| *n == name) {
+            v.push(value);
+        } else {
+            values.push((name, vec![value]));
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reparse_diff() {
+        let parse = |args: &mut RawArgs| {
+            crate::opt("port").default("8080").take(args);
+            crate::opt("host").take(args);
+            crate::flag("verbose").short('v').take_all(args);
+            crate::flag("quiet").take(args);
+        };
+
+        let mut old = test_args(&["test", "--host=a", "-v", "--quiet"]);
+        parse(&mut old);
+        let mut new = old.reparse(
+            ["test", "--port=8080", "-vv", "--host", "a"]
+                .iter()
+                .map(|a| a.to_string()),
+        );
+        parse(&mut new);
+        assert_eq!(
+            ReparseDiff::new(&old, &new).changed,
+            ["--verbose", "--quiet"]
+        );
+        assert!(ReparseDiff::new(&new, &new).is_empty());
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{DRY_RUN_FLAG, NO_COLOR_FLAG, QUIET_FLAG, RawArgs, VERBOSE_FLAG, YES_FLAG};
+
+/// Values of the well-known flags taken by [`take_standard()`](crate::take_standard).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Standard {
+    /// Number of times [`VERBOSE_FLAG`] is specified (e.g., `2` for `-vv`).
+    pub verbose: usize,
+
+    /// `true` if [`QUIET_FLAG`] is specified.
+    pub quiet: bool,
+
+    /// `true` if [`NO_COLOR_FLAG`] is specified (or the `NO_COLOR` environment variable is set).
+    ///
+    /// If `true`, help and error texts are not styled (see [`Metadata::style_help`](crate::Metadata::style_help)).
+    pub no_color: bool,
+
+    /// `true` if [`YES_FLAG`] is specified.
+    pub yes: bool,
+
+    /// `true` if [`DRY_RUN_FLAG`] is specified.
+    pub dry_run: bool,
+}
+
+impl Standard {
+    pub(crate) fn take(args: &mut RawArgs) -> Self {
+        let this = Self {
+            verbose: VERBOSE_FLAG.take_all(args).len(),
+            quiet: QUIET_FLAG.take(args).is_present(),
+            no_color: NO_COLOR_FLAG.take(args).is_present(),
+            yes: YES_FLAG.take(args).is_present(),
+            dry_run: DRY_RUN_FLAG.take(args).is_present(),
+        };
+        if this.dry_run {
+            args.metadata_mut().dry_run = true;
+        }
+        if this.no_color {
+            args.metadata_mut().style_help = Some(false);
+            args.metadata_mut().style_errors = Some(false);
+        }
+        this
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_standard() {
+        let mut args = test_args(&["test", "-vvq", "--verbose", "--dry-run", "-y"]);
+        let standard = crate::take_standard(&mut args);
+        assert_eq!(
+            standard,
+            Standard {
+                verbose: 3,
+                quiet: true,
+                no_color: standard.no_color, // Depends on the `NO_COLOR` environment variable.
+                yes: true,
+                dry_run: true,
+            }
+        );
+        assert_eq!(args.remaining_args().next(), None);
+        assert!(args.metadata().dry_run);
+    }
+
+    fn test_args(raw_args: &[&str]) -> RawArgs {
+        RawArgs::new(raw_args.iter().map(|a| a.to_string()))
+    }
+}
@@ -0,0 +1,276 @@
+//! Utilities for testing command-line interfaces built with `noargs`.
+//!
+//! # Examples
+//!
+//! ```
+//! use noargs::{RawArgs, testing::{Outcome, cases}};
+//!
+//! fn parse(args: &mut RawArgs) -> noargs::Result<usize> {
+//!     noargs::HELP_FLAG.take_help(args);
+//!     noargs::opt("count").default("1").take(args).then(|o| o.value().parse())
+//! }
+//!
+//! cases(
+//!     &[
+//!         (&[], Outcome::Ok),
+//!         (&["--count", "3"], Outcome::Ok),
+//!         (&["--count=foo"], Outcome::Error(r#"argument '--count' has an invalid value "foo": invalid digit found in string
+//!
+//! Try '--help' for more information."#)),
+//!         (&["--bar"], Outcome::Error("unexpected argument '--bar' found\n\nTry '--help' for more information.")),
+//!     ],
+//!     parse,
+//! );
+//! ```
+use crate::{Error, RawArgs};
+
+/// Expected outcome of a test case passed to [`cases()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// Parsing succeeds without help text.
+    Ok,
+
+    /// Parsing succeeds and the given help text is produced.
+    Help(&'static str),
+
+    /// Parsing fails with the given error text.
+    Error(&'static str),
+}
+
+/// Runs `parse` and [`RawArgs::finish()`] for each `(args, outcome)` case and checks that the outcome is as expected.
+///
+/// `args` does not include the program name (it is automatically prepended).
+/// Texts are compared without terminal styling.
+///
+/// # Panics
+///
+/// Panics with the diffs of the texts if any of the cases fails.
+#[allow(clippy::result_large_err)]
+pub fn cases<F, T>(cases: &[(&[&str], Outcome)], mut parse: F)
+where
+    F: FnMut(&mut RawArgs) -> Result<T, Error>,
+{
+    let mut failures = Vec::new();
+    for (args, expected) in cases {
+        let mut raw_args = RawArgs::new(
+            std::iter::once("<APP_NAME>")
+                .chain(args.iter().copied())
+                .map(|a| a.to_owned()),
+        );
+        let result = parse(&mut raw_args).and_then(|_| raw_args.finish());
+        let actual = match result {
+            Ok(None) => (Outcome::Ok, String::new()),
+            Ok(Some(help)) => (Outcome::Help(""), strip_styles(&help)),
+            Err(e) => (Outcome::Error(""), e.to_string(false)),
+        };
+        let expected_text = match expected {
+            Outcome::Ok => "",
+            Outcome::Help(text) | Outcome::Error(text) => text,
+        };
+        let same_kind = std::mem::discriminant(expected) == std::mem::discriminant(&actual.0);
+        if same_kind && expected_text == actual.1 {
+            continue;
+        }
+
+        let kind = |outcome: &Outcome| match outcome {
+            Outcome::Ok => "ok",
+            Outcome::Help(_) => "help",
+            Outcome::Error(_) => "error",
+        };
+        failures.push(format!(
+            "case {args:?}: expected {}, but got {}\n{}",
+            kind(expected),
+            kind(&actual.0),
+            diff(expected_text, &actual.1)
+        ));
+    }
+
+    if !failures.is_empty() {
+        panic!(
+            "{} of {} cases failed\n\n{}",
+            failures.len(),
+            cases.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+/// Runs `parse` and [`RawArgs::finish()`] for each example command line and checks that it parses successfully.
+///
+/// `examples` are written in the same form as [`Metadata::examples`](crate::Metadata::examples)
+/// (shell command lines without the program name), so that the documented examples can be verified
+/// together with other known-good invocations.
+///
+/// # Panics
+///
+/// Panics with the errors if any of the examples fails to parse or produces help text.
+///
+/// # Examples
+///
+/// ```
+/// const EXAMPLES: &[&str] = &["--count 3", "--count=1"];
+///
+/// fn parse(args: &mut noargs::RawArgs) -> noargs::Result<usize> {
+///     args.metadata_mut().examples = EXAMPLES;
+///     noargs::HELP_FLAG.take_help(args);
+///     noargs::opt("count").default("1").take(args).then(|o| o.value().parse())
+/// }
+///
+/// noargs::testing::check_examples(EXAMPLES, parse);
+/// noargs::testing::check_examples(&["", "--count 10"], parse);
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn check_examples<F, T>(examples: &[&str], mut parse: F)
+where
+    F: FnMut(&mut RawArgs) -> Result<T, Error>,
+{
+    let mut failures = Vec::new();
+    for example in examples {
+        let result = RawArgs::from_command_line(example).and_then(|mut raw_args| {
+            parse(&mut raw_args)?;
+            raw_args.finish()
+        });
+        match result {
+            Ok(None) => {}
+            Ok(Some(_)) => failures.push(format!("example {example:?}: help is produced")),
+            Err(e) => failures.push(format!("example {example:?}: {}", e.to_string(false))),
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!(
+            "{} of {} examples failed\n\n{}",
+            failures.len(),
+            examples.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+fn strip_styles(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    let mut lines = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => lines.push(format!("  {e}")),
+            (e, a) => {
+                lines.extend(e.map(|e| format!("- {e}")));
+                lines.extend(a.map(|a| format!("+ {a}")));
+            }
+        }
+    }
+    lines
+        .iter()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::result_large_err)]
+    fn parse(args: &mut RawArgs) -> Result<bool, Error> {
+        crate::HELP_FLAG.take_help(args);
+        Ok(crate::flag("foo").doc("Foo").take(args).is_present())
+    }
+
+    #[test]
+    fn passing_cases() {
+        cases(
+            &[
+                (&[], Outcome::Ok),
+                (&["--foo"], Outcome::Ok),
+                (
+                    &["-h"],
+                    Outcome::Help(
+                        r#"Usage: <APP_NAME> [OPTIONS]
+
+Options:
+  -h, --help Print help ('--help' for full help, '-h' for summary)
+      --foo  Foo
+"#,
+                    ),
+                ),
+                (
+                    &["--bar"],
+                    Outcome::Error(
+                        "unexpected argument '--bar' found\n\nTry '--help' for more information.",
+                    ),
+                ),
+            ],
+            parse,
+        );
+    }
+
+    #[test]
+    fn failing_cases() {
+        let result = std::panic::catch_unwind(|| {
+            cases(
+                &[
+                    (&["--foo"], Outcome::Ok),
+                    (
+                        &["--bar"],
+                        Outcome::Error("unexpected argument '--baz' found"),
+                    ),
+                ],
+                parse,
+            );
+        });
+        let message = result.expect_err("should fail");
+        let message = message.downcast_ref::<String>().expect("string");
+        assert_eq!(
+            message,
+            r#"1 of 2 cases failed
+
+case ["--bar"]: expected error, but got error
+- unexpected argument '--baz' found
++ unexpected argument '--bar' found
++
++ Try '--help' for more information.
+"#
+        );
+    }
+
+    #[test]
+    fn failing_examples() {
+        check_examples(&["", "--foo"], parse);
+
+        let result = std::panic::catch_unwind(|| {
+            check_examples(&["--foo", "--bar", "-h"], parse);
+        });
+        let message = result.expect_err("should fail");
+        let message = message.downcast_ref::<String>().expect("string");
+        assert_eq!(
+            message,
+            r#"2 of 3 examples failed
+
+example "--bar": unexpected argument '--bar' found
+
+Try '--help' for more information.
+example "-h": help is produced"#
+        );
+    }
+
+    #[test]
+    fn styles_are_stripped() {
+        assert_eq!(
+            strip_styles("\x1B[1m\x1B[4mUsage:\x1B[0m foo"),
+            "Usage: foo"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! Parsers for commonly used option and argument values.
+//!
+//! The functions in this module can be used with [`Opt::then()`](crate::Opt::then) and [`Arg::then()`](crate::Arg::then)
+//! so that applications share the same syntax and error messages.
+//!
+//! # Examples
+//!
+//! ```
+//! let mut args = noargs::RawArgs::new(["logs", "--since=2024-01-02T03:04:05Z"].iter().map(|a| a.to_string()));
+//! let since = noargs::opt("since")
+//!     .ty("TIME")
+//!     .take(&mut args)
+//!     .present_and_then(|o| noargs::values::parse_time(o.value()))?;
+//! assert!(since.is_some());
+//! # Ok::<(), noargs::Error>(())
+//! ```
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Error returned by the parsers in this module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseValueError {
+    message: String,
+}
+
+impl ParseValueError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseValueError {}
+
+/// Parses a boolean value (`true`/`false`, `yes`/`no`, `on`/`off` or `1`/`0`, ignoring ASCII case).
+///
+/// # Examples
+///
+/// ```
+/// let mut args = noargs::RawArgs::new(["app", "--color=ON"].iter().map(|a| a.to_string()));
+/// let color = noargs::opt("color")
+///     .default("off")
+///     .take(&mut args)
+///     .then(|o| noargs::values::parse_bool(o.value()))?;
+/// assert!(color);
+/// # Ok::<(), noargs::Error>(())
+/// ```
+pub fn parse_bool(s: &str) -> Result<bool, ParseValueError> {
+    const TRUE: [&str; 4] = ["true", "yes", "on", "1"];
+    const FALSE: [&str; 4] = ["false", "no", "off", "0"];
+    if TRUE.iter().any(|v| v.eq_ignore_ascii_case(s)) {
+        Ok(true)
+    } else if FALSE.iter().any(|v| v.eq_ignore_ascii_case(s)) {
+        Ok(false)
+    } else {
+        Err(ParseValueError::new(
+            "expected one of 'true', 'false', 'yes', 'no', 'on', 'off', '1' or '0'",
+        ))
+    }
+}
+
+/// Parses a timestamp (see [`parse_rfc3339()`]) or a relative time (see [`parse_relative_time()`]) relative to the current time.
+pub fn parse_time(s: &str) -> Result<SystemTime, ParseValueError> {
+    parse_relative_time(s, SystemTime::now())
+        .or_else(|_| parse_rfc3339(s))
+        .map_err(|_| {
+            ParseValueError::new(
+                "expected an RFC 3339 timestamp (e.g., '2024-01-02T03:04:05Z') or a relative time (e.g., '-1h')",
+            )
+        })
+}
+
+/// Parses an RFC 3339 timestamp (e.g., `2024-01-02T03:04:05Z` or `2024-01-02 12:04:05.5+09:00`).
+pub fn parse_rfc3339(s: &str) -> Result<SystemTime, ParseValueError> {
+    let error =
+        || ParseValueError::new("expected an RFC 3339 timestamp (e.g., '2024-01-02T03:04:05Z')");
+    let b = s.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't' | b' ') {
+        return Err(error());
+    }
+    if b[13] != b':' || b[16] != b':' {
+        return Err(error());
+    }
+    let num = |range: std::ops::Range<usize>| -> Result<u64, ParseValueError> {
+        let digits = s.get(range).ok_or_else(error)?;
+        if !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+        digits.parse().map_err(|_| error())
+    };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(error());
+    }
+
+    let mut rest = &s[19..];
+    let mut nanos = 0;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac.bytes().take_while(|c| c.is_ascii_digit()).count();
+        if len == 0 {
+            return Err(error());
+        }
+        for (i, c) in frac[..len.min(9)].bytes().enumerate() {
+            nanos += u32::from(c - b'0') * 10u32.pow(8 - i as u32);
+        }
+        rest = &frac[len..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first() {
+                Some(b'+') => 1,
+                Some(b'-') => -1,
+                _ => return Err(error()),
+            };
+            let b = rest.as_bytes();
+            if b.len() != 6 || b[3] != b':' {
+                return Err(error());
+            }
+            let digits = |r: std::ops::Range<usize>| {
+                if !rest.as_bytes()[r.clone()].iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+                rest[r].parse::<i64>().ok()
+            };
+            let (Some(h), Some(m)) = (digits(1..3), digits(4..6)) else {
+                return Err(error());
+            };
+            if h > 23 || m > 59 {
+                return Err(error());
+            }
+            sign * (h * 3600 + m * 60)
+        }
+    };
+
+    let days = days_from_civil(year as i64, month, day);
+    let secs = days * 86400 + (hour * 3600 + minute * 60 + second) as i64 - offset;
+    let time = if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    };
+    Ok(time + Duration::from_nanos(u64::from(nanos)))
+}
+
+/// Parses a relative time based on `now`.
+///
+/// Accepted forms:
+/// - `-<N><UNIT>` (e.g., `-30s`, `-15m`, `-1h`, `-2d` and `-1w`): `N` units before `now`
+/// - `now`
+/// - `yesterday`: 24 hours before `now`
+pub fn parse_relative_time(s: &str, now: SystemTime) -> Result<SystemTime, ParseValueError> {
+    let error = || {
+        ParseValueError::new(
+            "expected a relative time such as '-30s', '-15m', '-1h', '-2d', '-1w', 'now' or 'yesterday'",
+        )
+    };
+    let secs = match s {
+        "now" => 0,
+        "yesterday" => 86400,
+        _ => {
+            let s = s.strip_prefix('-').ok_or_else(error)?;
+            let unit = match s.as_bytes().last() {
+                Some(b's') => 1,
+                Some(b'm') => 60,
+                Some(b'h') => 3600,
+                Some(b'd') => 86400,
+                Some(b'w') => 7 * 86400,
+                _ => return Err(error()),
+            };
+            let n = &s[..s.len() - 1];
+            if n.is_empty() || !n.bytes().all(|c| c.is_ascii_digit()) {
+                return Err(error());
+            }
+            let n: u64 = n.parse().map_err(|_| error())?;
+            n.checked_mul(unit).ok_or_else(error)?
+        }
+    };
+    now.checked_sub(Duration::from_secs(secs)).ok_or_else(error)
+}
+
+/// Parses a byte size consisting of an integer and an optional unit suffix (ignoring ASCII case).
+///
+/// Accepted units:
+/// - `B` (or no suffix): bytes
+/// - `K`, `KiB`, `M`, `MiB`, `G`, `GiB`, `T` and `TiB`: powers of 1024
+/// - `KB`, `MB`, `GB` and `TB`: powers of 1000
+///
+/// # Examples
+///
+/// ```
+/// use noargs::values::parse_byte_size;
+///
+/// assert_eq!(parse_byte_size("512M"), Ok(512 * 1024 * 1024));
+/// assert_eq!(parse_byte_size("2kb"), Ok(2000));
+/// assert_eq!(parse_byte_size("100"), Ok(100));
+/// assert!(parse_byte_size("1.5G").is_err());
+/// ```
+pub fn parse_byte_size(s: &str) -> Result<u64, ParseValueError> {
+    let error = || {
+        ParseValueError::new("expected a byte size such as '1024', '512K', '512MiB', '1GB' or '2T'")
+    };
+    let len = s.bytes().take_while(|c| c.is_ascii_digit()).count();
+    let (digits, unit) = s.split_at(len);
+    if digits.is_empty() {
+        return Err(error());
+    }
+    let n: u64 = digits.parse().map_err(|_| error())?;
+    let unit = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        _ => return Err(error()),
+    };
+    n.checked_mul(unit).ok_or_else(error)
+}
+
+/// Formats a byte size using the largest binary unit that represents it exactly (e.g., `512MiB`).
+///
+/// The result can be parsed back with [`parse_byte_size()`].
+///
+/// # Examples
+///
+/// ```
+/// use noargs::values::format_byte_size;
+///
+/// assert_eq!(format_byte_size(512 * 1024 * 1024), "512MiB");
+/// assert_eq!(format_byte_size(1536), "1536B");
+/// assert_eq!(format_byte_size(0), "0B");
+/// ```
+pub fn format_byte_size(bytes: u64) -> String {
+    const UNITS: [(&str, u64); 4] = [
+        ("TiB", 1 << 40),
+        ("GiB", 1 << 30),
+        ("MiB", 1 << 20),
+        ("KiB", 1 << 10),
+    ];
+    UNITS
+        .iter()
+        .find(|(_, size)| bytes != 0 && bytes % size == 0)
+        .map(|(unit, size)| format!("{}{unit}", bytes / size))
+        .unwrap_or_else(|| format!("{bytes}B"))
+}
+
+fn is_leap_year(year: u64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// See: https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bool() {
+        for s in ["true", "Yes", "ON", "1"] {
+            assert_eq!(parse_bool(s), Ok(true), "{s}");
+        }
+        for s in ["FALSE", "no", "Off", "0"] {
+            assert_eq!(parse_bool(s), Ok(false), "{s}");
+        }
+        assert_eq!(
+            parse_bool("y").map_err(|e| e.to_string()),
+            Err("expected one of 'true', 'false', 'yes', 'no', 'on', 'off', '1' or '0'".to_owned())
+        );
+    }
+
+    #[test]
+    fn rfc3339() {
+        let secs = |s| {
+            parse_rfc3339(s)
+                .map(|t| t.duration_since(UNIX_EPOCH).expect("after epoch"))
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(secs("1970-01-01T00:00:00Z"), Ok(Duration::ZERO));
+        assert_eq!(
+            secs("2024-02-29T12:34:56Z"),
+            Ok(Duration::from_secs(1709210096))
+        );
+        assert_eq!(
+            secs("2024-02-29 21:34:56.25+09:00"),
+            Ok(Duration::from_millis(1709210096250))
+        );
+        assert!(parse_rfc3339("1969-12-31T23:59:59Z").is_ok_and(|t| t < UNIX_EPOCH));
+
+        for invalid in [
+            "2023-02-29T00:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:00:00+9:00",
+            "2024-01-01T00:00:00+09:+5",
+            "2024-1-01T00:00:00Z",
+        ] {
+            assert_eq!(
+                secs(invalid),
+                Err("expected an RFC 3339 timestamp (e.g., '2024-01-02T03:04:05Z')".to_owned()),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn relative_time() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let before =
+            |s| parse_relative_time(s, now).map(|t| now.duration_since(t).expect("before now"));
+        assert_eq!(before("now"), Ok(Duration::ZERO));
+        assert_eq!(before("-30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(before("-15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(before("-1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(before("-2d"), Ok(Duration::from_secs(172800)));
+        assert_eq!(before("-1w"), Ok(Duration::from_secs(604800)));
+        assert_eq!(before("yesterday"), Ok(Duration::from_secs(86400)));
+        assert!(before("1h").is_err());
+        assert!(before("-h").is_err());
+        assert!(before("-1y").is_err());
+    }
+
+    #[test]
+    fn byte_size() {
+        assert_eq!(parse_byte_size("0"), Ok(0));
+        assert_eq!(parse_byte_size("10b"), Ok(10));
+        assert_eq!(parse_byte_size("512MiB"), Ok(512 << 20));
+        assert_eq!(parse_byte_size("1GB"), Ok(1_000_000_000));
+        assert_eq!(parse_byte_size("3t"), Ok(3 << 40));
+        for invalid in ["", "M", "-1K", "1 K", "1PB", "99999999999T"] {
+            assert!(parse_byte_size(invalid).is_err(), "{invalid}");
+        }
+
+        for bytes in [0, 1, 1000, 1024, 1536, 512 << 20, 3 << 40, u64::MAX] {
+            assert_eq!(parse_byte_size(&format_byte_size(bytes)), Ok(bytes));
+        }
+        assert_eq!(format_byte_size(2048), "2KiB");
+        assert_eq!(format_byte_size(5 << 30), "5GiB");
+    }
+
+    #[test]
+    fn time() {
+        assert!(parse_time("-1h").is_ok());
+        assert!(parse_time("2024-01-02T03:04:05Z").is_ok());
+        assert_eq!(
+            parse_time("last week").map_err(|e| e.to_string()),
+            Err("expected an RFC 3339 timestamp (e.g., '2024-01-02T03:04:05Z') or a relative time (e.g., '-1h')".to_owned())
+        );
+    }
+}